@@ -0,0 +1,287 @@
+//! Accès au support de stockage par blocs.
+//!
+//! Le système de fichiers n’a pas besoin que tout le volume tienne en mémoire :
+//! il lit et écrit des blocs (secteurs) via les traits `BlockDevice` et
+//! `BlockDeviceMut`. Ça permet de travailler sur une carte SD, une image de
+//! plusieurs Go ouverte comme fichier, ou un simple buffer en mémoire.
+//!
+//! Implémentations fournies :
+//! - `[u8]` (buffer en mémoire, blocs de 512 octets)
+//! - `std::fs::File` (feature `std`)
+//! - `&T` et `&mut T` pour tout `T` qui implémente déjà le trait
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use crate::FatError;
+
+/// Taille de bloc des implémentations fournies (buffer mémoire, fichier).
+pub const DEFAULT_BLOCK_SIZE: usize = 512;
+
+/// Support lisible bloc par bloc.
+///
+/// Les lectures prennent `&self` : un driver qui a besoin d’un état mutable
+/// (bus SPI, position de lecture…) utilise une mutabilité intérieure.
+pub trait BlockDevice {
+    /// Taille d’un bloc en octets (en général 512).
+    fn block_size(&self) -> usize;
+
+    /// Nombre total de blocs du support.
+    fn num_blocks(&self) -> u64;
+
+    /// Lit des blocs consécutifs à partir du bloc `lba`.
+    ///
+    /// `buf.len()` est toujours un multiple de `block_size()`.
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError>;
+}
+
+/// Support inscriptible bloc par bloc.
+pub trait BlockDeviceMut: BlockDevice {
+    /// Écrit des blocs consécutifs à partir du bloc `lba`.
+    ///
+    /// `buf.len()` est toujours un multiple de `block_size()`.
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError>;
+}
+
+impl BlockDevice for [u8] {
+    fn block_size(&self) -> usize {
+        DEFAULT_BLOCK_SIZE
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (self.len() / DEFAULT_BLOCK_SIZE) as u64
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        let start = slice_range(self.len(), lba, buf.len())?;
+        buf.copy_from_slice(&self[start..start + buf.len()]);
+        Ok(())
+    }
+}
+
+impl BlockDeviceMut for [u8] {
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+        let start = slice_range(self.len(), lba, buf.len())?;
+        self[start..start + buf.len()].copy_from_slice(buf);
+        Ok(())
+    }
+}
+
+/// Offset de départ dans un buffer mémoire, après vérification des bornes.
+fn slice_range(len: usize, lba: u64, count: usize) -> Result<usize, FatError> {
+    let start = usize::try_from(lba)
+        .ok()
+        .and_then(|l| l.checked_mul(DEFAULT_BLOCK_SIZE))
+        .ok_or(FatError::OutOfBounds)?;
+    match start.checked_add(count) {
+        Some(end) if end <= len => Ok(start),
+        _ => Err(FatError::OutOfBounds),
+    }
+}
+
+impl<T: BlockDevice + ?Sized> BlockDevice for &T {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (**self).num_blocks()
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        (**self).read_blocks(lba, buf)
+    }
+}
+
+impl<T: BlockDevice + ?Sized> BlockDevice for &mut T {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        (**self).num_blocks()
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        (**self).read_blocks(lba, buf)
+    }
+}
+
+impl<T: BlockDeviceMut + ?Sized> BlockDeviceMut for &mut T {
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+        (**self).write_blocks(lba, buf)
+    }
+}
+
+/// Image disque ouverte comme fichier : on lit/écrit à la demande,
+/// sans charger l’image en mémoire.
+///
+/// Les lectures passent par `&File` (qui implémente `Read + Seek`).
+#[cfg(feature = "std")]
+impl BlockDevice for std::fs::File {
+    fn block_size(&self) -> usize {
+        DEFAULT_BLOCK_SIZE
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.metadata()
+            .map(|m| m.len() / DEFAULT_BLOCK_SIZE as u64)
+            .unwrap_or(0)
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut f = self;
+        f.seek(SeekFrom::Start(lba * DEFAULT_BLOCK_SIZE as u64))
+            .map_err(|_| FatError::Io)?;
+        f.read_exact(buf).map_err(|_| FatError::Io)
+    }
+}
+
+#[cfg(feature = "std")]
+impl BlockDeviceMut for std::fs::File {
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        self.seek(SeekFrom::Start(lba * DEFAULT_BLOCK_SIZE as u64))
+            .map_err(|_| FatError::Io)?;
+        self.write_all(buf).map_err(|_| FatError::Io)
+    }
+}
+
+/// Taille utile du support en octets.
+pub(crate) fn device_len<D: BlockDevice + ?Sized>(dev: &D) -> u64 {
+    dev.num_blocks().saturating_mul(dev.block_size() as u64)
+}
+
+/// Lit `buf.len()` octets à l’offset `offset` (en octets) du support.
+///
+/// Demande la taille du support à chaque appel : pour les accès répétés,
+/// `read_bytes_within` prend une taille déjà connue.
+pub(crate) fn read_bytes<D: BlockDevice + ?Sized>(
+    dev: &D,
+    offset: u64,
+    buf: &mut [u8],
+) -> Result<(), FatError> {
+    read_bytes_within(dev, device_len(dev), offset, buf)
+}
+
+/// Comme `read_bytes`, avec `dev_len` comme taille du support (ex: celle
+/// lue à l’ouverture du volume ; pour un fichier, `num_blocks` coûte un
+/// appel système).
+///
+/// Les blocs entiers sont lus directement dans `buf` ; les morceaux de bloc
+/// (début ou fin non alignés) passent par un bloc temporaire.
+pub(crate) fn read_bytes_within<D: BlockDevice + ?Sized>(
+    dev: &D,
+    dev_len: u64,
+    offset: u64,
+    buf: &mut [u8],
+) -> Result<(), FatError> {
+    let bs = check_range(dev, dev_len, offset, buf.len())?;
+
+    let mut lba = offset / bs as u64;
+    let mut skip = (offset % bs as u64) as usize;
+    let mut done = 0usize;
+    let mut scratch = Vec::new();
+
+    while done < buf.len() {
+        let remaining = buf.len() - done;
+
+        if skip == 0 && remaining >= bs {
+            let whole = remaining - remaining % bs;
+            dev.read_blocks(lba, &mut buf[done..done + whole])?;
+            done += whole;
+            lba += (whole / bs) as u64;
+            continue;
+        }
+
+        if scratch.is_empty() {
+            scratch = vec![0u8; bs];
+        }
+        dev.read_blocks(lba, &mut scratch)?;
+        let take = core::cmp::min(bs - skip, remaining);
+        buf[done..done + take].copy_from_slice(&scratch[skip..skip + take]);
+
+        done += take;
+        lba += 1;
+        skip = 0;
+    }
+
+    Ok(())
+}
+
+/// Écrit `buf` à l’offset `offset` (en octets) du support.
+///
+/// Comme `read_bytes`, demande la taille du support à chaque appel.
+pub(crate) fn write_bytes<D: BlockDeviceMut + ?Sized>(
+    dev: &mut D,
+    offset: u64,
+    buf: &[u8],
+) -> Result<(), FatError> {
+    let len = device_len(dev);
+    write_bytes_within(dev, len, offset, buf)
+}
+
+/// Comme `write_bytes`, avec `dev_len` comme taille du support.
+///
+/// Un bloc partiellement modifié est d’abord relu (read-modify-write).
+pub(crate) fn write_bytes_within<D: BlockDeviceMut + ?Sized>(
+    dev: &mut D,
+    dev_len: u64,
+    offset: u64,
+    buf: &[u8],
+) -> Result<(), FatError> {
+    let bs = check_range(dev, dev_len, offset, buf.len())?;
+
+    let mut lba = offset / bs as u64;
+    let mut skip = (offset % bs as u64) as usize;
+    let mut done = 0usize;
+    let mut scratch = Vec::new();
+
+    while done < buf.len() {
+        let remaining = buf.len() - done;
+
+        if skip == 0 && remaining >= bs {
+            let whole = remaining - remaining % bs;
+            dev.write_blocks(lba, &buf[done..done + whole])?;
+            done += whole;
+            lba += (whole / bs) as u64;
+            continue;
+        }
+
+        if scratch.is_empty() {
+            scratch = vec![0u8; bs];
+        }
+        dev.read_blocks(lba, &mut scratch)?;
+        let take = core::cmp::min(bs - skip, remaining);
+        scratch[skip..skip + take].copy_from_slice(&buf[done..done + take]);
+        dev.write_blocks(lba, &scratch)?;
+
+        done += take;
+        lba += 1;
+        skip = 0;
+    }
+
+    Ok(())
+}
+
+/// Vérifie que `[offset, offset + len)` tient dans les `dev_len` octets du
+/// support ; retourne la taille de bloc.
+fn check_range<D: BlockDevice + ?Sized>(
+    dev: &D,
+    dev_len: u64,
+    offset: u64,
+    len: usize,
+) -> Result<usize, FatError> {
+    let bs = dev.block_size();
+    if bs == 0 {
+        return Err(FatError::Io);
+    }
+    match offset.checked_add(len as u64) {
+        Some(end) if end <= dev_len => Ok(bs),
+        _ => Err(FatError::OutOfBounds),
+    }
+}
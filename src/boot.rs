@@ -0,0 +1,147 @@
+//! Copie de secours du secteur de boot (FAT32).
+//!
+//! Un volume FAT32 garde une copie du secteur de boot dans ses secteurs
+//! réservés (champ du BPB à l’offset 50, en général le secteur 6), suivie
+//! d’une copie de FSInfo. Si le secteur 0 est abîmé, le champ qui indique
+//! où est la copie l’est peut-être aussi : on essaie alors le secteur 6 pour
+//! chaque taille de secteur possible.
+//!
+//! - `Fat32::open_with_backup` se rabat sur ces copies quand le secteur de
+//!   boot ou FSInfo principal est invalide (les compteurs du FSInfo de
+//!   secours sont mis à jour avec ceux du principal)
+//! - `Fat32Mut::restore_boot_sector` réécrit une copie à partir de l’autre
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use crate::block::{read_bytes, BlockDevice, BlockDeviceMut};
+use crate::{parse_bpb, Fat32, Fat32Mut, FatError};
+
+/// Emplacement habituel de la copie de secours (spécification Microsoft).
+const DEFAULT_BACKUP_BOOT_SECTOR: u16 = 6;
+
+/// Copie du secteur de boot : la principale (secteur 0) ou celle de secours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootSector {
+    /// Secteur 0.
+    Primary,
+    /// Copie de secours désignée par le BPB (en général le secteur 6).
+    Backup,
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Ouvre le volume comme `open`, mais se rabat sur la copie de secours
+    /// du secteur de boot si le BPB principal est invalide, et sur celle de
+    /// FSInfo si le FSInfo principal l’est.
+    ///
+    /// `boot_source` indique la copie utilisée. Si aucune copie n’est
+    /// valide, l’erreur est celle du secteur principal.
+    pub fn open_with_backup(dev: D) -> Result<Self, FatError> {
+        let mut fs = match parse_bpb(&dev, 0) {
+            Ok(p) => Self::from_params(dev, p, BootSector::Primary),
+            Err(e) => {
+                let found = backup_candidates(&dev)
+                    .into_iter()
+                    .find_map(|(sector, bps)| {
+                        parse_bpb(&dev, sector as u64 * bps as u64)
+                            .ok()
+                            .filter(|p| p.bytes_per_sector == bps && p.backup_boot_sector == sector)
+                    });
+                match found {
+                    Some(p) => Self::from_params(dev, p, BootSector::Backup),
+                    None => return Err(e),
+                }
+            }
+        };
+
+        // FSInfo de secours : même position, comptée depuis la copie du boot
+        // (tenue à jour avec le FSInfo principal, voir `update_fs_info`)
+        if fs.fs_info().is_none() && fs.read_fs_info(fs.fs_info_backup).is_some() {
+            fs.fs_info_sector = fs.fs_info_backup;
+        }
+
+        Ok(fs)
+    }
+
+    /// Copie du secteur de boot d’où vient la géométrie du volume
+    /// (toujours `Primary` avec `open`).
+    pub fn boot_source(&self) -> BootSector {
+        self.boot_source
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Ouvre en écriture avec repli sur les copies de secours
+    /// (voir `Fat32::open_with_backup`).
+    pub fn open_with_backup(dev: D) -> Result<Self, FatError> {
+        Self::from_fs(Fat32::open_with_backup(dev)?)
+    }
+
+    /// Recopie le secteur de boot `from` et son FSInfo sur l’autre copie :
+    /// `Backup` répare le secteur 0, `Primary` remet la copie de secours à jour.
+    ///
+    /// - `MissingBackupBootSector` si le volume n’a pas de copie de secours
+    /// - l’erreur de validation du BPB si la source est elle-même invalide
+    pub fn restore_boot_sector(&mut self, from: BootSector) -> Result<(), FatError> {
+        let result = self.copy_boot_sector(from);
+        self.finish(result)
+    }
+
+    /// Corps de `restore_boot_sector`.
+    fn copy_boot_sector(&mut self, from: BootSector) -> Result<(), FatError> {
+        let backup = self.fs.backup_boot_sector;
+        if backup == 0 {
+            return Err(FatError::MissingBackupBootSector);
+        }
+        let (src, dst) = match from {
+            BootSector::Primary => (0, backup),
+            BootSector::Backup => (backup, 0),
+        };
+
+        let bps = self.fs.bytes_per_sector() as u64;
+        let p = parse_bpb(&self.fs.dev, src as u64 * bps)?;
+
+        let mut sectors = vec![0u16];
+        let fs_info = p.fs_info_sector;
+        if fs_info != 0 && (backup as u32 + fs_info as u32) < self.fs.reserved_sectors as u32 {
+            sectors.push(fs_info);
+        }
+
+        let mut buf = vec![0u8; bps as usize];
+        for rel in sectors {
+            self.fs.read_at((src + rel) as u64 * bps, &mut buf)?;
+            self.write_at((dst + rel) as u64 * bps, &buf)?;
+        }
+
+        // Le FSInfo principal est de nouveau valide : c’est lui qu’on tient à jour
+        if from == BootSector::Backup && fs_info != 0 {
+            self.fs.fs_info_sector = fs_info;
+        }
+
+        Ok(())
+    }
+}
+
+/// Emplacements possibles de la copie de secours `(secteur, taille de
+/// secteur)` : celui annoncé par le secteur 0 s’il est lisible, puis le
+/// secteur 6 pour chaque taille de secteur.
+fn backup_candidates<D: BlockDevice>(dev: &D) -> Vec<(u16, u16)> {
+    let mut candidates = Vec::new();
+
+    let mut b = [0u8; 512];
+    if read_bytes(dev, 0, &mut b).is_ok() {
+        let bps = u16::from_le_bytes([b[11], b[12]]);
+        let sector = u16::from_le_bytes([b[50], b[51]]);
+        if bps.is_power_of_two() && (512..=4096).contains(&bps) && sector != 0 && sector != 0xFFFF {
+            candidates.push((sector, bps));
+        }
+    }
+
+    for bps in [512, 1024, 2048, 4096] {
+        if !candidates.contains(&(DEFAULT_BACKUP_BOOT_SECTOR, bps)) {
+            candidates.push((DEFAULT_BACKUP_BOOT_SECTOR, bps));
+        }
+    }
+    candidates
+}
@@ -0,0 +1,507 @@
+//! Vérification de cohérence d’un volume (équivalent de `fsck.fat -n`).
+//!
+//! `Fat32::check` charge la FAT en mémoire, parcourt toute l’arborescence
+//! depuis la racine et attribue chaque cluster à l’entrée qui le référence.
+//! Tout ce qui ne colle pas est remonté dans un `CheckReport` :
+//! - cluster partagé par deux chaînes (cross-link)
+//! - chaîne qui boucle, ou qui pointe vers un cluster libre, réservé ou hors volume
+//! - chaîne plus courte ou plus longue que la taille du fichier (une chaîne
+//!   plus longue mais bien terminée est une réserve, qui ne rend pas le
+//!   volume incohérent)
+//! - chaîne allouée dans la FAT mais référencée par aucune entrée (perdue)
+//! - copies de la FAT qui diffèrent (si elles sont censées être en miroir)
+//!
+//! Les chaînes sont suivies sans limite de longueur : une boucle se voit
+//! parce qu’on retombe sur un cluster déjà attribué à la même entrée.
+//! La vérification n’écrit jamais sur le support.
+//!
+//! `Fat32::compare_fats` donne le détail des écarts entre copies de la FAT,
+//! plage par plage, que le miroir soit actif ou non.
+
+extern crate alloc;
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::block::BlockDevice;
+use crate::{parse_dir_slots, Fat32, FatError};
+
+/// Entrée FAT d’un cluster défectueux (échelle FAT32).
+const FAT_BAD_CLUSTER: u32 = 0x0FFF_FFF7;
+
+/// Problème détecté par `Fat32::check`.
+///
+/// Les chemins sont absolus, avec les noms longs quand ils existent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Le cluster `cluster` appartient à deux chaînes : celle de `first`
+    /// (rencontrée en premier) et celle de `second`.
+    ///
+    /// `from` est le cluster de `second` qui pointe sur `cluster` ; `None`
+    /// si `second` commence directement par `cluster`.
+    CrossLinked {
+        cluster: u32,
+        first: String,
+        second: String,
+        from: Option<u32>,
+    },
+    /// La chaîne de `path` revient sur `cluster`, déjà parcouru : l’entrée
+    /// FAT de `from` ferme la boucle.
+    ChainLoop {
+        path: String,
+        from: u32,
+        cluster: u32,
+    },
+    /// La chaîne de `path` contient une valeur qui n’est pas un cluster de
+    /// données : libre (0), réservée, défectueuse ou hors volume.
+    ///
+    /// `from` est le cluster dont l’entrée FAT contient `value` ; `None`
+    /// si c’est le premier cluster noté dans l’entrée de répertoire.
+    InvalidCluster {
+        path: String,
+        from: Option<u32>,
+        value: u32,
+    },
+    /// La chaîne de `path` a moins de clusters que sa taille n’en demande.
+    ChainTooShort {
+        path: String,
+        size: u32,
+        clusters: u32,
+    },
+    /// La chaîne de `path`, bien terminée, a plus de clusters que sa taille
+    /// n’en demande : clusters réservés à l’avance (`Fat32Mut::preallocate`),
+    /// que `CheckReport::is_clean` tolère (voir `Problem::is_reserve`).
+    ChainTooLong {
+        path: String,
+        size: u32,
+        clusters: u32,
+    },
+    /// Chaîne allouée dans la FAT mais référencée par aucune entrée.
+    LostChain { start: u32, clusters: u32 },
+    /// La copie numéro `copy` de la FAT (la première est 0) diffère de la
+    /// première sur `entries` entrées, dont la première est celle de `cluster`.
+    ///
+    /// Seulement quand les copies sont en miroir (voir `Fat32::active_fat`).
+    FatMismatch {
+        copy: u8,
+        cluster: u32,
+        entries: u32,
+    },
+}
+
+impl Problem {
+    /// Indique un constat qui ne rend pas le volume incohérent : une chaîne
+    /// plus longue que la taille du fichier mais terminée par une fin de
+    /// chaîne est une réserve, gardée par `repair` (sauf `trim_reserved`).
+    pub fn is_reserve(&self) -> bool {
+        matches!(self, Problem::ChainTooLong { .. })
+    }
+}
+
+/// Une ligne `type clé=valeur ...` par problème, facile à filtrer
+/// (les chemins sont entre guillemets, échappés comme en Rust).
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::CrossLinked {
+                cluster,
+                first,
+                second,
+                from,
+            } => write!(
+                f,
+                "cross-linked cluster={cluster} first={first:?} second={second:?} from={}",
+                link_source(from)
+            ),
+            Problem::ChainLoop {
+                path,
+                from,
+                cluster,
+            } => write!(f, "chain-loop path={path:?} from={from} cluster={cluster}"),
+            Problem::InvalidCluster { path, from, value } => {
+                let from = link_source(from);
+                write!(
+                    f,
+                    "invalid-cluster path={path:?} from={from} value={value:#x}"
+                )
+            }
+            Problem::ChainTooShort {
+                path,
+                size,
+                clusters,
+            } => write!(
+                f,
+                "chain-too-short path={path:?} size={size} clusters={clusters}"
+            ),
+            Problem::ChainTooLong {
+                path,
+                size,
+                clusters,
+            } => write!(
+                f,
+                "chain-too-long path={path:?} size={size} clusters={clusters}"
+            ),
+            Problem::LostChain { start, clusters } => {
+                write!(f, "lost-chain start={start} clusters={clusters}")
+            }
+            Problem::FatMismatch {
+                copy,
+                cluster,
+                entries,
+            } => write!(
+                f,
+                "fat-mismatch copy={copy} cluster={cluster} entries={entries}"
+            ),
+        }
+    }
+}
+
+/// Origine d’un lien : numéro du cluster, ou `entry` pour l’entrée de répertoire.
+fn link_source(from: &Option<u32>) -> String {
+    from.map_or_else(|| "entry".to_string(), |c| c.to_string())
+}
+
+/// Plage d’entrées où une copie de la FAT diffère de la FAT de référence
+/// (voir `Fat32::compare_fats`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FatDivergence {
+    /// Copie comparée (la première est 0).
+    pub copy: u8,
+    /// Premier cluster de la plage.
+    pub first: u32,
+    /// Dernier cluster de la plage (inclus).
+    pub last: u32,
+}
+
+/// Même format que `Problem` : `fat-divergence copy= first= last=`.
+impl fmt::Display for FatDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fat-divergence copy={} first={} last={}",
+            self.copy, self.first, self.last
+        )
+    }
+}
+
+/// Résultat de `Fat32::check`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    /// Problèmes trouvés, dans l’ordre de découverte : FAT, arborescence,
+    /// puis chaînes perdues.
+    pub problems: Vec<Problem>,
+    /// Fichiers parcourus.
+    pub files: u32,
+    /// Répertoires parcourus (hors racine).
+    pub directories: u32,
+    /// Clusters attribués à une entrée de l’arborescence.
+    pub used_clusters: u32,
+}
+
+impl CheckReport {
+    /// Indique un volume cohérent : aucun problème, hormis des réserves
+    /// (voir `Problem::is_reserve`).
+    pub fn is_clean(&self) -> bool {
+        self.problems.iter().all(Problem::is_reserve)
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Vérifie la cohérence du volume (voir le module `check`).
+    ///
+    /// Les incohérences sont remontées dans le rapport ; une erreur n’est
+    /// retournée que si le support lui-même ne peut pas être lu.
+    pub fn check(&self) -> Result<CheckReport, FatError> {
+        let max = self.max_cluster_number()?;
+        let fat = self.load_fat(self.reference_fat(), max)?;
+
+        let mut checker = Checker {
+            fs: self,
+            max,
+            owner: vec![0; fat.len()],
+            paths: Vec::new(),
+            fat,
+            report: CheckReport::default(),
+        };
+
+        checker.compare_fat_copies()?;
+        checker.walk_tree()?;
+        checker.find_lost_chains();
+
+        Ok(checker.report)
+    }
+
+    /// Compare chaque copie de la FAT à la FAT de référence (la FAT active
+    /// si le miroir est désactivé, la première sinon), entrées 2 et suivantes.
+    ///
+    /// Chaque plage de clusters consécutifs dont les entrées diffèrent donne
+    /// une `FatDivergence`, copie par copie. Miroir désactivé, les autres
+    /// copies ne sont plus tenues à jour : des écarts y sont normaux.
+    pub fn compare_fats(&self) -> Result<Vec<FatDivergence>, FatError> {
+        let max = self.max_cluster_number()?;
+        let reference = self.reference_fat();
+        let fat = self.load_fat(reference, max)?;
+
+        let mut found = Vec::new();
+        for copy in (0..self.num_fats).filter(|&c| c != reference) {
+            let other = self.load_fat(copy, max)?;
+            found.extend(divergences(copy, &fat, &other));
+        }
+        Ok(found)
+    }
+
+    /// Charge et décode la copie `copy` de la FAT, entrées 0 à `max`.
+    fn load_fat(&self, copy: u8, max: u32) -> Result<Vec<u32>, FatError> {
+        let ft = self.fat_type;
+        let len = ft.entry_offset(max) as usize + ft.entry_bytes();
+        let start = self.fat_copy_start(copy);
+
+        let mut raw = vec![0u8; len];
+        self.read_at(start, &mut raw)?;
+
+        Ok((0..=max)
+            .map(|cl| ft.decode(&raw[ft.entry_offset(cl) as usize..], cl))
+            .collect())
+    }
+}
+
+/// État du parcours de `Fat32::check`.
+struct Checker<'a, D> {
+    fs: &'a Fat32<D>,
+    max: u32,
+    /// FAT de référence décodée (la FAT active d’ext_flags, sinon la
+    /// première copie), indexée par numéro de cluster.
+    fat: Vec<u32>,
+    /// Propriétaire de chaque cluster : 0 si aucun, sinon index + 1 dans `paths`.
+    owner: Vec<u32>,
+    /// Chemins des entrées qui possèdent une chaîne.
+    paths: Vec<String>,
+    report: CheckReport,
+}
+
+impl<D: BlockDevice> Checker<'_, D> {
+    /// Compare chaque copie de la FAT à la première, si elles sont en miroir.
+    fn compare_fat_copies(&mut self) -> Result<(), FatError> {
+        if self.fs.active_fat.is_some() {
+            return Ok(());
+        }
+
+        for copy in 1..self.fs.num_fats {
+            let other = self.fs.load_fat(copy, self.max)?;
+
+            let diff = divergences(copy, &self.fat, &other);
+            if let Some(d) = diff.first() {
+                self.report.problems.push(Problem::FatMismatch {
+                    copy,
+                    cluster: d.first,
+                    entries: diff.iter().map(|d| d.last - d.first + 1).sum(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Parcourt l’arborescence depuis la racine (en profondeur, sans récursion).
+    fn walk_tree(&mut self) -> Result<(), FatError> {
+        let root = self.fs.root_cluster;
+        let root_slots = if self.fs.is_fixed_root(root) {
+            self.fs.dir_slots(root)?
+        } else {
+            let (chain, _) = self.claim_chain(root, "/");
+            self.fs.chain_slots(&chain)?
+        };
+
+        let mut pending = vec![(String::from("/"), root_slots)];
+
+        while let Some((dir_path, slots)) = pending.pop() {
+            for located in parse_dir_slots(slots) {
+                let e = located.entry;
+                if e.name == "." || e.name == ".." {
+                    continue;
+                }
+
+                let path = if dir_path == "/" {
+                    format!("/{}", e.name)
+                } else {
+                    format!("{dir_path}/{}", e.name)
+                };
+
+                if e.is_dir() {
+                    self.report.directories += 1;
+                    // Un cluster déjà attribué n’est pas relu : un répertoire
+                    // qui pointe vers un ancêtre ne fait pas boucler le parcours.
+                    let (chain, _) = self.claim_chain(e.first_cluster, &path);
+                    if !chain.is_empty() {
+                        let slots = self.fs.chain_slots(&chain)?;
+                        pending.push((path, slots));
+                    }
+                    continue;
+                }
+
+                self.report.files += 1;
+                let cs = self.fs.cluster_size() as u64;
+                let expected = (e.size as u64).div_ceil(cs) as u32;
+
+                if e.first_cluster == 0 {
+                    if expected > 0 {
+                        self.report.problems.push(Problem::ChainTooShort {
+                            path,
+                            size: e.size,
+                            clusters: 0,
+                        });
+                    }
+                    continue;
+                }
+
+                let (chain, intact) = self.claim_chain(e.first_cluster, &path);
+                // Chaîne déjà signalée comme abîmée : sa longueur ne veut rien dire
+                if !intact {
+                    continue;
+                }
+
+                let clusters = chain.len() as u32;
+                if clusters < expected {
+                    self.report.problems.push(Problem::ChainTooShort {
+                        path,
+                        size: e.size,
+                        clusters,
+                    });
+                } else if clusters > expected {
+                    self.report.problems.push(Problem::ChainTooLong {
+                        path,
+                        size: e.size,
+                        clusters,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Suit la chaîne de `path` depuis `start` et attribue ses clusters.
+    ///
+    /// Retourne les clusters attribués (jusqu’au premier problème), et
+    /// `false` si la chaîne est abîmée (problème déjà ajouté au rapport).
+    fn claim_chain(&mut self, start: u32, path: &str) -> (Vec<u32>, bool) {
+        self.paths.push(path.to_string());
+        let id = self.paths.len() as u32;
+
+        let mut chain = Vec::new();
+        let mut from = None;
+        let mut cl = start;
+
+        loop {
+            if !(2..=self.max).contains(&cl) {
+                self.report.problems.push(Problem::InvalidCluster {
+                    path: path.to_string(),
+                    from,
+                    value: cl,
+                });
+                return (chain, false);
+            }
+
+            match self.owner[cl as usize] {
+                0 => {}
+                o if o == id => {
+                    // Revenir sur sa propre chaîne suppose au moins un lien
+                    let from = from.unwrap_or(cl);
+                    self.report.problems.push(Problem::ChainLoop {
+                        path: path.to_string(),
+                        from,
+                        cluster: cl,
+                    });
+                    return (chain, false);
+                }
+                o => {
+                    self.report.problems.push(Problem::CrossLinked {
+                        cluster: cl,
+                        first: self.paths[o as usize - 1].clone(),
+                        second: path.to_string(),
+                        from,
+                    });
+                    return (chain, false);
+                }
+            }
+
+            self.owner[cl as usize] = id;
+            self.report.used_clusters += 1;
+            chain.push(cl);
+
+            let next = self.fat[cl as usize];
+            if next >= 0x0FFF_FFF8 {
+                return (chain, true);
+            }
+            from = Some(cl);
+            cl = next;
+        }
+    }
+
+    /// Regroupe en chaînes les clusters alloués que personne ne référence.
+    ///
+    /// On part des têtes (clusters vers lesquels aucun autre cluster perdu
+    /// ne pointe) ; ce qui reste ensuite forme des boucles sans tête.
+    fn find_lost_chains(&mut self) {
+        const COUNTED: u32 = u32::MAX;
+
+        let max = self.max;
+        let lost = |owner: &[u32], fat: &[u32], cl: u32| {
+            owner[cl as usize] == 0 && fat[cl as usize] != 0 && fat[cl as usize] != FAT_BAD_CLUSTER
+        };
+
+        let mut pointed = vec![false; self.fat.len()];
+        for cl in 2..=max {
+            let next = self.fat[cl as usize];
+            if lost(&self.owner, &self.fat, cl)
+                && (2..=max).contains(&next)
+                && lost(&self.owner, &self.fat, next)
+            {
+                pointed[next as usize] = true;
+            }
+        }
+
+        for heads_only in [true, false] {
+            for start in 2..=max {
+                if !lost(&self.owner, &self.fat, start) || (heads_only && pointed[start as usize]) {
+                    continue;
+                }
+
+                let mut clusters = 0;
+                let mut cl = start;
+                while (2..=max).contains(&cl) && lost(&self.owner, &self.fat, cl) {
+                    self.owner[cl as usize] = COUNTED;
+                    clusters += 1;
+                    cl = self.fat[cl as usize];
+                }
+
+                self.report
+                    .problems
+                    .push(Problem::LostChain { start, clusters });
+            }
+        }
+    }
+}
+
+/// Plages de clusters (à partir de 2) où `other` diffère de `reference`.
+fn divergences(copy: u8, reference: &[u32], other: &[u32]) -> Vec<FatDivergence> {
+    let mut found: Vec<FatDivergence> = Vec::new();
+    for cl in 2..reference.len() as u32 {
+        if reference[cl as usize] == other[cl as usize] {
+            continue;
+        }
+        match found.last_mut() {
+            Some(d) if d.last + 1 == cl => d.last = cl,
+            _ => found.push(FatDivergence {
+                copy,
+                first: cl,
+                last: cl,
+            }),
+        }
+    }
+    found
+}
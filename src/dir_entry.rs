@@ -1,12 +1,16 @@
 //! Entrées de répertoire FAT32 (format court 8.3).
 //!
-//! Dans ce projet on ne gère pas les Long File Names (LFN).
-//! On parse donc uniquement les entrées “courtes” de 32 octets.
+//! `DirEntry::parse` ne lit qu’une entrée “courte” de 32 octets.
+//! Les Long File Names (slots LFN) sont recollés au niveau du listage
+//! de répertoire (voir `lfn`), puis rattachés à l’entrée courte.
 
 extern crate alloc;
 
 use alloc::string::String;
 
+use crate::lfn::is_lfn_slot;
+use crate::time::{FatDate, FatTimestamp};
+
 /// Attributs FAT d’une entrée de répertoire.
 ///
 /// Les bits viennent directement du champ `ATTR` (offset 11).
@@ -40,19 +44,27 @@ impl Attributes {
     }
 }
 
-/// Entrée de répertoire FAT32 (nom court 8.3).
+/// Entrée de répertoire FAT32.
 ///
-/// Exemple: `HELLO.TXT`, `DIR`, `A.BIN`.
+/// Exemple: `HELLO.TXT`, `DIR`, `A.BIN`, ou `Program Files` avec un nom long.
 #[derive(Debug, Clone)]
 pub struct DirEntry {
-    /// Nom court reconstitué (ex: `HELLO.TXT`).
+    /// Nom de l’entrée : le nom long (LFN) s’il existe, sinon le nom court.
     pub name: String,
+    /// Nom court 8.3 reconstitué (ex: `HELLO.TXT`, `PROGRA~1`).
+    pub short_name: String,
     /// Attributs FAT.
     pub attrs: Attributes,
     /// Premier cluster de la chaîne (0 si fichier vide dans notre écriture simple).
     pub first_cluster: u32,
     /// Taille du fichier en octets (0 pour un répertoire).
     pub size: u32,
+    /// Date/heure de création (`None` si le champ n’est pas renseigné).
+    pub created: Option<FatTimestamp>,
+    /// Date du dernier accès (FAT ne stocke pas l’heure).
+    pub accessed: Option<FatDate>,
+    /// Date/heure de dernière modification.
+    pub modified: Option<FatTimestamp>,
 }
 
 impl DirEntry {
@@ -60,7 +72,11 @@ impl DirEntry {
     ///
     /// Retourne `None` si:
     /// - l’entrée est libre (`0x00`) ou supprimée (`0xE5`)
+    /// - l’entrée est un slot de nom long (attribut `0x0F`)
     /// - l’entrée est un Volume ID
+    ///
+    /// Le champ `name` vaut ici le nom court ; c’est le listage de répertoire
+    /// qui le remplace par le nom long quand une suite LFN valide précède l’entrée.
     pub fn parse(entry: &[u8]) -> Option<Self> {
         if entry.len() < 32 {
             return None;
@@ -71,26 +87,16 @@ impl DirEntry {
             return None;
         }
 
+        if is_lfn_slot(entry) {
+            return None;
+        }
+
         let attrs = Attributes::from_byte(entry[11]);
         if attrs.volume_id {
             return None;
         }
 
-        let name_raw = &entry[0..8];
-        let ext_raw = &entry[8..11];
-
-        let name = decode_ascii_trim(name_raw);
-        let ext = decode_ascii_trim(ext_raw);
-
-        let full_name = if !ext.is_empty() {
-            let mut s = String::with_capacity(name.len() + 1 + ext.len());
-            s.push_str(&name);
-            s.push('.');
-            s.push_str(&ext);
-            s
-        } else {
-            name
-        };
+        let full_name = short_name_to_string(&entry[0..11]);
 
         let first_cluster_high = u16::from_le_bytes([entry[20], entry[21]]) as u32;
         let first_cluster_low = u16::from_le_bytes([entry[26], entry[27]]) as u32;
@@ -98,11 +104,20 @@ impl DirEntry {
 
         let size = u32::from_le_bytes([entry[28], entry[29], entry[30], entry[31]]);
 
+        let le16 = |i: usize| u16::from_le_bytes([entry[i], entry[i + 1]]);
+        let created = FatTimestamp::from_raw(le16(16), le16(14), entry[13]);
+        let accessed = FatDate::from_raw(le16(18));
+        let modified = FatTimestamp::from_raw(le16(24), le16(22), 0);
+
         Some(Self {
-            name: full_name,
+            name: full_name.clone(),
+            short_name: full_name,
             attrs,
             first_cluster,
             size,
+            created,
+            accessed,
+            modified,
         })
     }
 
@@ -130,3 +145,19 @@ fn decode_ascii_trim(bytes: &[u8]) -> String {
     }
     s
 }
+
+/// Reconstitue un nom court lisible (`HELLO.TXT`, `DIR`) depuis les 11 octets bruts.
+pub(crate) fn short_name_to_string(raw: &[u8]) -> String {
+    let name = decode_ascii_trim(&raw[0..8]);
+    let ext = decode_ascii_trim(&raw[8..11]);
+
+    if ext.is_empty() {
+        return name;
+    }
+
+    let mut s = String::with_capacity(name.len() + 1 + ext.len());
+    s.push_str(&name);
+    s.push('.');
+    s.push_str(&ext);
+    s
+}
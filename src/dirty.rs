@@ -0,0 +1,136 @@
+//! Drapeaux “volume propre” et “erreur matérielle” de `FAT[1]`.
+//!
+//! L’entrée 1 de la FAT ne sert pas au chaînage. En FAT16 et FAT32, Windows
+//! et Linux utilisent ses bits de poids fort comme drapeaux :
+//! - bit “propre” (`0x8000` en FAT16, `0x0800_0000` en FAT32) : à 0 tant
+//!   qu’une écriture n’a pas été terminée proprement
+//! - bit “pas d’erreur” (`0x4000` en FAT16, `0x0400_0000` en FAT32) : à 0
+//!   quand une erreur d’entrée/sortie a été rencontrée sur le volume
+//!
+//! `Fat32Mut` baisse le bit “propre” juste avant sa première écriture et le
+//! remonte à la fin de chaque opération réussie : s’il reste à 0, un outil
+//! s’est arrêté au milieu d’une écriture (plantage, coupure, erreur). Chaque
+//! opération publique passe son résultat à `finish` : après une erreur, le
+//! volume reste sale même si les opérations suivantes réussissent. Un
+//! volume déjà sale à l’ouverture est laissé tel quel, c’est `repair` qui le
+//! remet propre. FAT12 n’a pas ces drapeaux.
+
+use crate::block::{write_bytes_within, BlockDevice, BlockDeviceMut};
+use crate::{Fat32, Fat32Mut, FatError, FatType};
+
+/// Ce que fait un `Fat32Mut` du bit “propre” de `FAT[1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DirtyState {
+    /// Volume propre : la prochaine écriture le marque sale.
+    Clean,
+    /// Marqué sale par cette vue : `commit` le remet propre.
+    Marked,
+    /// Bit laissé tel quel : volume déjà sale à l’ouverture ou laissé sale
+    /// par une opération en échec, FAT12, ou surcouche de `repair`.
+    Untracked,
+}
+
+/// Masques des bits (propre, pas d’erreur) de `FAT[1]` ; `None` en FAT12.
+fn flag_masks(fat_type: FatType) -> Option<(u32, u32)> {
+    match fat_type {
+        FatType::Fat12 => None,
+        FatType::Fat16 => Some((0x8000, 0x4000)),
+        FatType::Fat32 => Some((0x0800_0000, 0x0400_0000)),
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Indique si le volume est marqué sale : une écriture a commencé sans
+    /// se terminer proprement (toujours `false` en FAT12).
+    pub fn is_dirty(&self) -> Result<bool, FatError> {
+        match flag_masks(self.fat_type) {
+            Some((clean, _)) => Ok(self.read_fat1(self.reference_fat())? & clean == 0),
+            None => Ok(false),
+        }
+    }
+
+    /// Indique si un système a signalé une erreur d’entrée/sortie sur le
+    /// volume (toujours `false` en FAT12). Ce drapeau n’est jamais modifié
+    /// ici.
+    pub fn has_hard_error(&self) -> Result<bool, FatError> {
+        match flag_masks(self.fat_type) {
+            Some((_, no_error)) => Ok(self.read_fat1(self.reference_fat())? & no_error == 0),
+            None => Ok(false),
+        }
+    }
+
+    /// État à adopter par un `Fat32Mut` qui ouvre ce volume.
+    pub(crate) fn initial_dirty_state(&self) -> Result<DirtyState, FatError> {
+        if self.fat_type == FatType::Fat12 || self.is_dirty()? {
+            Ok(DirtyState::Untracked)
+        } else {
+            Ok(DirtyState::Clean)
+        }
+    }
+
+    /// Valeur brute de `FAT[1]` dans la copie `copy`.
+    fn read_fat1(&self, copy: u8) -> Result<u32, FatError> {
+        let mut b = [0u8; 4];
+        let n = self.fat_type.entry_bytes();
+        let off = self.fat_copy_start(copy) + self.fat_type.entry_offset(1);
+        self.read_at(off, &mut b[..n])?;
+        Ok(u32::from_le_bytes(b))
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Fin d’une opération publique : `commit` si elle a réussi, `abandon`
+    /// sinon.
+    pub(crate) fn finish<T>(&mut self, result: Result<T, FatError>) -> Result<T, FatError> {
+        match result {
+            Ok(value) => self.commit().map(|()| value),
+            Err(e) => {
+                self.abandon();
+                Err(e)
+            }
+        }
+    }
+
+    /// Opération interrompue par une erreur : le volume a pu rester
+    /// incohérent, cette vue ne remet plus le bit “propre” (seul `repair`
+    /// le fait).
+    pub(crate) fn abandon(&mut self) {
+        if self.dirty == DirtyState::Marked {
+            self.dirty = DirtyState::Untracked;
+        }
+    }
+
+    /// Fin d’une opération réussie : remet le volume propre s’il a été
+    /// marqué sale par cette vue.
+    pub(crate) fn commit(&mut self) -> Result<(), FatError> {
+        if self.dirty == DirtyState::Marked {
+            self.set_clean_flag(true)?;
+            self.dirty = DirtyState::Clean;
+        }
+        Ok(())
+    }
+
+    /// Écrit le bit “propre” de `FAT[1]` dans les FAT tenues à jour.
+    ///
+    /// Passe directement par le support : `write_at` appelle cette fonction
+    /// avant la première écriture.
+    pub(crate) fn set_clean_flag(&mut self, clean: bool) -> Result<(), FatError> {
+        let Some((mask, _)) = flag_masks(self.fs.fat_type) else {
+            return Ok(());
+        };
+
+        let n = self.fs.fat_type.entry_bytes();
+        for copy in self.fs.written_fats() {
+            let old = self.fs.read_fat1(copy)?;
+            let value = if clean { old | mask } else { old & !mask };
+            let off = self.fs.fat_copy_start(copy) + self.fs.fat_type.entry_offset(1);
+            write_bytes_within(
+                &mut self.fs.dev,
+                self.fs.dev_len,
+                off,
+                &value.to_le_bytes()[..n],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Handles de fichier : lecture et écriture à la demande, cluster par cluster.
+//!
+//! Contrairement à `Fat32::read_file`, un `FileReader` ne charge jamais le
+//! fichier en entier : chaque `read` ne touche que le cluster courant, et un
+//! `seek` suit la chaîne FAT jusqu’au cluster qui contient la position visée.
+//!
+//! `FileWriter` fait la même chose en écriture : il réécrit les octets en place,
+//! et n’alloue un nouveau cluster que quand les données dépassent la chaîne.
+
+extern crate alloc;
+
+use alloc::vec;
+
+use crate::block::{BlockDevice, BlockDeviceMut};
+use crate::io::{resolve_seek, Read, Seek, SeekFrom, Write};
+use crate::{Fat32, Fat32Mut, FatError, MAX_FILE_SIZE};
+
+/// Lecteur de fichier sur un volume FAT32 (voir `Fat32::open_file`).
+///
+/// Implémente `io::Read` + `io::Seek` (et `std::io::Read` + `std::io::Seek`
+/// avec la feature `std`).
+#[derive(Debug)]
+pub struct FileReader<'a, D> {
+    fs: &'a Fat32<D>,
+    first_cluster: u32,
+    size: u64,
+    /// Position courante, en octets depuis le début du fichier.
+    pos: u64,
+    /// Dernier cluster atteint dans la chaîne : (index dans la chaîne, numéro).
+    cursor: Option<(u64, u32)>,
+}
+
+impl<'a, D: BlockDevice> FileReader<'a, D> {
+    pub(crate) fn new(fs: &'a Fat32<D>, first_cluster: u32, size: u32) -> Self {
+        Self {
+            fs,
+            first_cluster,
+            size: size as u64,
+            pos: 0,
+            cursor: None,
+        }
+    }
+
+    /// Taille du fichier en octets.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Position courante du curseur.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Retourne le numéro du cluster d’index `index` dans la chaîne.
+    ///
+    /// On repart du dernier cluster atteint si on avance (cas de la lecture
+    /// séquentielle), sinon du premier cluster du fichier.
+    fn cluster_at(&mut self, index: u64) -> Result<u32, FatError> {
+        let (mut i, mut cluster) = match self.cursor {
+            Some((i, cl)) if i <= index => (i, cl),
+            _ => (0, self.first_cluster),
+        };
+
+        if cluster < 2 {
+            return Err(FatError::InvalidCluster);
+        }
+
+        while i < index {
+            let next = self.fs.read_fat_entry(cluster)?;
+            if !(2..0x0FFF_FFF8).contains(&next) {
+                // Chaîne trop courte pour la taille annoncée : image incohérente
+                return Err(FatError::InvalidCluster);
+            }
+            cluster = next;
+            i += 1;
+        }
+
+        self.cursor = Some((index, cluster));
+        Ok(cluster)
+    }
+}
+
+impl<D: BlockDevice> Read for FileReader<'_, D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, FatError> {
+        if buf.is_empty() || self.pos >= self.size {
+            return Ok(0);
+        }
+
+        let cs = self.fs.cluster_size() as u64;
+        let index = self.pos / cs;
+        let in_cluster = self.pos % cs;
+
+        // On ne dépasse ni la fin du cluster courant, ni la fin du fichier.
+        let n = (buf.len() as u64)
+            .min(cs - in_cluster)
+            .min(self.size - self.pos) as usize;
+
+        let cluster = self.cluster_at(index)?;
+        let off = self.fs.cluster_to_offset(cluster)? + in_cluster;
+        self.fs.read_at(off, &mut buf[..n])?;
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<D: BlockDevice> Seek for FileReader<'_, D> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, FatError> {
+        self.pos = resolve_seek(pos, self.pos, self.size)?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDevice> std::io::Read for FileReader<'_, D> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(Read::read(self, buf)?)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDevice> std::io::Seek for FileReader<'_, D> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Ok(Seek::seek(self, pos.into())?)
+    }
+}
+
+/// Écrivain de fichier sur un volume FAT32 (voir `Fat32Mut::open_file_mut`).
+///
+/// - écrire à une position existante remplace les octets en place
+/// - écrire après la fin agrandit le fichier ; la chaîne est prolongée
+///   d’un cluster à la fois, au fil des données
+/// - un `seek` au-delà de la fin suivi d’un `write` remplit le trou de zéros
+///
+/// La taille et la date de modification sont reportées dans l’entrée de
+/// répertoire au `flush` (et automatiquement quand le handle est détruit).
+#[derive(Debug)]
+pub struct FileWriter<'a, D: BlockDeviceMut> {
+    fs: &'a mut Fat32Mut<D>,
+    /// Offset de l’entrée courte du fichier, mise à jour au `flush`.
+    entry_offset: u64,
+    first_cluster: u32,
+    size: u64,
+    pos: u64,
+    cursor: Option<(u64, u32)>,
+    /// L’entrée de répertoire doit être réécrite.
+    dirty: bool,
+}
+
+impl<'a, D: BlockDeviceMut> FileWriter<'a, D> {
+    pub(crate) fn new(
+        fs: &'a mut Fat32Mut<D>,
+        entry_offset: u64,
+        first_cluster: u32,
+        size: u32,
+    ) -> Self {
+        Self {
+            fs,
+            entry_offset,
+            first_cluster,
+            size: size as u64,
+            pos: 0,
+            cursor: None,
+            dirty: false,
+        }
+    }
+
+    /// Taille actuelle du fichier en octets.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Position courante du curseur.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Corps de `write`.
+    fn write_at_cursor(&mut self, buf: &[u8]) -> Result<usize, FatError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Trou entre la fin actuelle et la position : rempli de zéros
+        if self.pos > self.size {
+            let zeros = vec![0u8; self.fs.fs.cluster_size()];
+            while self.size < self.pos {
+                let gap = core::cmp::min(self.pos - self.size, zeros.len() as u64) as usize;
+                self.write_chunk(self.size, &zeros[..gap])?;
+            }
+        }
+
+        let n = self.write_chunk(self.pos, buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    /// Écrit au plus un cluster à la position `at` ; retourne le nombre d’octets écrits.
+    fn write_chunk(&mut self, at: u64, buf: &[u8]) -> Result<usize, FatError> {
+        let cs = self.fs.fs.cluster_size() as u64;
+        let index = at / cs;
+        let in_cluster = (at % cs) as usize;
+        let n = core::cmp::min(buf.len() as u64, cs - in_cluster as u64) as usize;
+
+        if at + n as u64 > MAX_FILE_SIZE {
+            return Err(FatError::FileTooLarge);
+        }
+
+        let (cluster, fresh) = self.cluster_for_write(index)?;
+        let off = self.fs.fs.cluster_to_offset(cluster)?;
+
+        if fresh {
+            // Cluster tout neuf : on l’écrit en entier pour ne pas laisser
+            // d’anciennes données après la fin du fichier.
+            let mut data = vec![0u8; cs as usize];
+            data[in_cluster..in_cluster + n].copy_from_slice(&buf[..n]);
+            self.fs.write_at(off, &data)?;
+        } else {
+            self.fs.write_at(off + in_cluster as u64, &buf[..n])?;
+        }
+
+        self.size = self.size.max(at + n as u64);
+        self.dirty = true;
+        Ok(n)
+    }
+
+    /// Retourne le cluster d’index `index` dans la chaîne, en prolongeant la
+    /// chaîne si besoin. Le booléen indique un cluster qui vient d’être alloué.
+    fn cluster_for_write(&mut self, index: u64) -> Result<(u32, bool), FatError> {
+        let mut fresh = false;
+
+        if self.first_cluster < 2 {
+            // Fichier vide : pas encore de chaîne
+            self.first_cluster = self.fs.alloc_chain(1)?[0];
+            self.cursor = None;
+            fresh = true;
+        }
+
+        let (mut i, mut cluster) = match self.cursor {
+            Some((i, cl)) if i <= index => (i, cl),
+            _ => (0, self.first_cluster),
+        };
+
+        while i < index {
+            let next = self.fs.fs.read_fat_entry(cluster)?;
+            if next >= 0x0FFF_FFF8 {
+                cluster = self.fs.extend_chain(cluster)?;
+                fresh = true;
+            } else if next < 2 {
+                return Err(FatError::InvalidCluster);
+            } else {
+                cluster = next;
+                fresh = false;
+            }
+            i += 1;
+        }
+
+        self.cursor = Some((index, cluster));
+        Ok((cluster, fresh))
+    }
+}
+
+impl<D: BlockDeviceMut> Write for FileWriter<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FatError> {
+        let result = self.write_at_cursor(buf);
+        if result.is_err() {
+            // Écriture interrompue : le volume reste sale (module `dirty`)
+            self.fs.abandon();
+        }
+        result
+    }
+
+    fn flush(&mut self) -> Result<(), FatError> {
+        if self.dirty {
+            if let Err(e) = self.fs.update_entry_after_write(
+                self.entry_offset,
+                self.first_cluster,
+                self.size as u32,
+            ) {
+                self.fs.abandon();
+                return Err(e);
+            }
+            self.dirty = false;
+        }
+        // Fin d’une écriture : le volume redevient propre (module `dirty`)
+        self.fs.commit()
+    }
+}
+
+impl<D: BlockDeviceMut> Seek for FileWriter<'_, D> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, FatError> {
+        self.pos = resolve_seek(pos, self.pos, self.size)?;
+        Ok(self.pos)
+    }
+}
+
+impl<D: BlockDeviceMut> Drop for FileWriter<'_, D> {
+    fn drop(&mut self) {
+        // Erreur ignorée : appeler `flush` explicitement pour la récupérer.
+        let _ = Write::flush(self);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDeviceMut> std::io::Write for FileWriter<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(Write::write(self, buf)?)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(Write::flush(self)?)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDeviceMut> std::io::Seek for FileWriter<'_, D> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Ok(Seek::seek(self, pos.into())?)
+    }
+}
@@ -0,0 +1,306 @@
+//! Table de partitions GPT.
+//!
+//! Un disque GPT garde un MBR “protecteur” (une seule entrée de type 0xEE)
+//! pour les anciens outils ; la vraie table est ailleurs :
+//! - l’en-tête principal au bloc 1, puis le tableau d’entrées (en général au bloc 2)
+//! - une copie de secours de l’en-tête au dernier bloc du disque, précédée
+//!   de sa copie du tableau d’entrées
+//!
+//! L’en-tête et le tableau sont protégés par un CRC32 : si l’en-tête principal
+//! ou son tableau est abîmé, on relit la copie de secours.
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::block::{read_bytes, BlockDevice};
+use crate::FatError;
+
+/// Signature de l’en-tête GPT.
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+
+/// Taille minimale d’un en-tête (champs définis par la spec UEFI).
+const GPT_HEADER_MIN_SIZE: usize = 92;
+
+/// Taille minimale d’une entrée de partition.
+const GPT_ENTRY_MIN_SIZE: usize = 128;
+
+/// Taille maximale acceptée pour le tableau d’entrées (garde-fou avant allocation).
+const GPT_MAX_ARRAY_BYTES: usize = 1 << 20;
+
+/// GUID tel que stocké sur disque (16 octets, 3 premiers champs en little endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    /// Type de la partition système EFI (ESP), formatée en FAT.
+    pub const EFI_SYSTEM: Guid = Guid::from_fields(
+        0xC12A_7328,
+        0xF81F,
+        0x11D2,
+        [0xBA, 0x4B, 0x00, 0xA0, 0xC9, 0x3E, 0xC9, 0x3B],
+    );
+
+    /// Type “Microsoft basic data” (FAT, exFAT ou NTFS).
+    pub const BASIC_DATA: Guid = Guid::from_fields(
+        0xEBD0_A0A2,
+        0xB9E5,
+        0x4433,
+        [0x87, 0xC0, 0x68, 0xB6, 0xB7, 0x26, 0x99, 0xC7],
+    );
+
+    /// Construit un GUID à partir de sa forme texte `d1-d2-d3-d4[0..2]-d4[2..8]`.
+    pub const fn from_fields(d1: u32, d2: u16, d3: u16, d4: [u8; 8]) -> Self {
+        let a = d1.to_le_bytes();
+        let b = d2.to_le_bytes();
+        let c = d3.to_le_bytes();
+        Guid([
+            a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1], d4[0], d4[1], d4[2], d4[3], d4[4],
+            d4[5], d4[6], d4[7],
+        ])
+    }
+
+    /// GUID nul (entrée de partition inutilisée).
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+/// Forme texte usuelle, ex: `C12A7328-F81F-11D2-BA4B-00A0C93EC93B`.
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let g = &self.0;
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-",
+            u32::from_le_bytes([g[0], g[1], g[2], g[3]]),
+            u16::from_le_bytes([g[4], g[5]]),
+            u16::from_le_bytes([g[6], g[7]]),
+            g[8],
+            g[9]
+        )?;
+        for b in &g[10..] {
+            write!(f, "{b:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Partition décrite par une entrée du tableau GPT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GptPartition {
+    /// Numéro de partition : position dans le tableau d’entrées, à partir de 1.
+    pub index: usize,
+    /// Type de partition (ex: `Guid::EFI_SYSTEM`).
+    pub type_guid: Guid,
+    /// Identifiant unique de la partition.
+    pub unique_guid: Guid,
+    /// Premier bloc de la partition.
+    pub first_lba: u64,
+    /// Dernier bloc de la partition (inclus).
+    pub last_lba: u64,
+    /// Attributs (bit 0 : partition requise par la plateforme, etc.).
+    pub attributes: u64,
+    /// Nom de la partition (UTF-16 sur disque).
+    pub name: String,
+}
+
+impl GptPartition {
+    /// Nombre de blocs de la partition.
+    pub fn num_sectors(&self) -> u64 {
+        self.last_lba - self.first_lba + 1
+    }
+
+    /// Indique une partition système EFI.
+    pub fn is_efi_system(&self) -> bool {
+        self.type_guid == Guid::EFI_SYSTEM
+    }
+}
+
+/// Table GPT lue sur le support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gpt {
+    /// Identifiant du disque.
+    pub disk_guid: Guid,
+    /// Partitions utilisées, dans l’ordre du tableau d’entrées.
+    pub partitions: Vec<GptPartition>,
+    /// La table vient de la copie de secours (l’en-tête principal ou son
+    /// tableau d’entrées est invalide).
+    pub from_backup: bool,
+}
+
+/// Champs utiles d’un en-tête GPT validé.
+struct GptHeader {
+    alternate_lba: u64,
+    disk_guid: Guid,
+    entries_lba: u64,
+    num_entries: u32,
+    entry_size: u32,
+    entries_crc: u32,
+}
+
+/// Lit la table GPT du support.
+///
+/// Le secteur 0 doit être un MBR protecteur (entrée de type 0xEE). On valide
+/// l’en-tête principal (signature, CRC32, position) et le CRC32 de son
+/// tableau d’entrées ; en cas d’échec, on passe à l’en-tête de secours.
+/// Retourne `InvalidPartitionTable` si aucune des deux copies n’est valide.
+pub fn read_gpt<D: BlockDevice + ?Sized>(dev: &D) -> Result<Gpt, FatError> {
+    if !crate::partition::is_protective_mbr(dev)? {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    let primary = read_header(dev, 1);
+    if let Ok(h) = &primary {
+        if let Ok(partitions) = read_entries(dev, h) {
+            return Ok(Gpt {
+                disk_guid: h.disk_guid,
+                partitions,
+                from_backup: false,
+            });
+        }
+    }
+
+    // Copie de secours : à l’adresse annoncée par l’en-tête principal s’il
+    // est lisible, sinon au dernier bloc du disque.
+    let last = dev.num_blocks().saturating_sub(1);
+    let backup_lba = match &primary {
+        Ok(h) if h.alternate_lba != 0 => h.alternate_lba,
+        _ => last,
+    };
+    let h = read_header(dev, backup_lba)?;
+    let partitions = read_entries(dev, &h)?;
+
+    Ok(Gpt {
+        disk_guid: h.disk_guid,
+        partitions,
+        from_backup: true,
+    })
+}
+
+/// Lit et valide l’en-tête GPT du bloc `lba`.
+fn read_header<D: BlockDevice + ?Sized>(dev: &D, lba: u64) -> Result<GptHeader, FatError> {
+    let bs = dev.block_size();
+    let offset = lba
+        .checked_mul(bs as u64)
+        .ok_or(FatError::InvalidPartitionTable)?;
+    let mut h = vec![0u8; bs.max(GPT_HEADER_MIN_SIZE)];
+    read_bytes(dev, offset, &mut h)?;
+
+    let le32 = |i: usize| u32::from_le_bytes([h[i], h[i + 1], h[i + 2], h[i + 3]]);
+    let le64 = |i: usize| {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(&h[i..i + 8]);
+        u64::from_le_bytes(b)
+    };
+
+    let header_size = le32(12) as usize;
+    if &h[0..8] != GPT_SIGNATURE || !(GPT_HEADER_MIN_SIZE..=h.len()).contains(&header_size) {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    // Le CRC de l’en-tête se calcule avec son propre champ mis à zéro
+    let stored_crc = le32(16);
+    let mut copy = h[..header_size].to_vec();
+    copy[16..20].fill(0);
+    if crc32(&copy) != stored_crc || le64(24) != lba {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    let mut disk_guid = [0u8; 16];
+    disk_guid.copy_from_slice(&h[56..72]);
+
+    Ok(GptHeader {
+        alternate_lba: le64(32),
+        disk_guid: Guid(disk_guid),
+        entries_lba: le64(72),
+        num_entries: le32(80),
+        entry_size: le32(84),
+        entries_crc: le32(88),
+    })
+}
+
+/// Lit le tableau d’entrées d’un en-tête, vérifie son CRC32 et garde les
+/// entrées utilisées (type non nul).
+fn read_entries<D: BlockDevice + ?Sized>(
+    dev: &D,
+    h: &GptHeader,
+) -> Result<Vec<GptPartition>, FatError> {
+    let entry_size = h.entry_size as usize;
+    let len = (h.num_entries as usize)
+        .checked_mul(entry_size)
+        .filter(|&n| n <= GPT_MAX_ARRAY_BYTES)
+        .ok_or(FatError::InvalidPartitionTable)?;
+    if entry_size < GPT_ENTRY_MIN_SIZE || !entry_size.is_multiple_of(8) {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    let offset = h
+        .entries_lba
+        .checked_mul(dev.block_size() as u64)
+        .ok_or(FatError::InvalidPartitionTable)?;
+    let mut array = vec![0u8; len];
+    read_bytes(dev, offset, &mut array)?;
+    if crc32(&array) != h.entries_crc {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    let mut parts = Vec::new();
+    for (i, e) in array.chunks_exact(entry_size).enumerate() {
+        let guid = |at: usize| {
+            let mut g = [0u8; 16];
+            g.copy_from_slice(&e[at..at + 16]);
+            Guid(g)
+        };
+        let le64 = |at: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&e[at..at + 8]);
+            u64::from_le_bytes(b)
+        };
+
+        let type_guid = guid(0);
+        if type_guid.is_zero() {
+            continue;
+        }
+
+        let (first_lba, last_lba) = (le64(32), le64(40));
+        if first_lba > last_lba {
+            return Err(FatError::InvalidPartitionTable);
+        }
+
+        // Nom : 36 unités UTF-16, complété par des zéros
+        let units = e[56..128]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&u| u != 0);
+        let name = char::decode_utf16(units)
+            .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+
+        parts.push(GptPartition {
+            index: i + 1,
+            type_guid,
+            unique_guid: guid(16),
+            first_lba,
+            last_lba,
+            attributes: le64(48),
+            name,
+        });
+    }
+
+    Ok(parts)
+}
+
+/// CRC32 (IEEE 802.3, polynôme réfléchi `0xEDB88320`), celui de la spec GPT.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
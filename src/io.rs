@@ -0,0 +1,129 @@
+//! Traits de lecture/écriture séquentielle, utilisables sans `std`.
+//!
+//! Ce sont les équivalents minimaux de `std::io::Read` / `Write` / `Seek`,
+//! avec `FatError` comme type d’erreur. Avec la feature `std`, les handles de
+//! fichier implémentent aussi les traits de `std::io`.
+
+use crate::FatError;
+
+/// Position de départ d’un déplacement (équivalent de `std::io::SeekFrom`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// Depuis le début du fichier.
+    Start(u64),
+    /// Depuis la fin du fichier (souvent négatif).
+    End(i64),
+    /// Depuis la position courante.
+    Current(i64),
+}
+
+/// Source d’octets lue morceau par morceau.
+pub trait Read {
+    /// Lit au plus `buf.len()` octets ; retourne 0 en fin de fichier.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, FatError>;
+
+    /// Remplit entièrement `buf`, ou retourne `OutOfBounds` si la fin
+    /// du fichier arrive avant.
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), FatError> {
+        while !buf.is_empty() {
+            match self.read(buf)? {
+                0 => return Err(FatError::OutOfBounds),
+                n => buf = &mut buf[n..],
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Destination d’octets écrite morceau par morceau.
+pub trait Write {
+    /// Écrit au plus `buf.len()` octets et retourne le nombre d’octets écrits.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, FatError>;
+
+    /// Rend visibles les écritures en attente (ex: taille dans l’entrée de répertoire).
+    fn flush(&mut self) -> Result<(), FatError>;
+
+    /// Écrit tout `buf`, en enchaînant les appels à `write`.
+    fn write_all(&mut self, mut buf: &[u8]) -> Result<(), FatError> {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return Err(FatError::NoSpaceLeft),
+                n => buf = &buf[n..],
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Curseur qu’on peut déplacer dans un flux.
+pub trait Seek {
+    /// Déplace le curseur et retourne la nouvelle position (depuis le début).
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, FatError>;
+}
+
+/// Calcule la position absolue visée par un `SeekFrom`.
+///
+/// Aller au-delà de la fin est permis (comme pour `std`), mais pas avant le début.
+pub(crate) fn resolve_seek(pos: SeekFrom, current: u64, len: u64) -> Result<u64, FatError> {
+    let (base, delta) = match pos {
+        SeekFrom::Start(n) => return Ok(n),
+        SeekFrom::End(d) => (len, d),
+        SeekFrom::Current(d) => (current, d),
+    };
+    base.checked_add_signed(delta).ok_or(FatError::InvalidSeek)
+}
+
+#[cfg(feature = "std")]
+impl From<SeekFrom> for std::io::SeekFrom {
+    fn from(pos: SeekFrom) -> Self {
+        match pos {
+            SeekFrom::Start(n) => std::io::SeekFrom::Start(n),
+            SeekFrom::End(d) => std::io::SeekFrom::End(d),
+            SeekFrom::Current(d) => std::io::SeekFrom::Current(d),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::SeekFrom> for SeekFrom {
+    fn from(pos: std::io::SeekFrom) -> Self {
+        match pos {
+            std::io::SeekFrom::Start(n) => SeekFrom::Start(n),
+            std::io::SeekFrom::End(d) => SeekFrom::End(d),
+            std::io::SeekFrom::Current(d) => SeekFrom::Current(d),
+        }
+    }
+}
+
+/// Convertit une `FatError` en `std::io::Error` pour les impls `std::io`.
+#[cfg(feature = "std")]
+impl From<FatError> for std::io::Error {
+    fn from(e: FatError) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match e {
+            FatError::PathNotFound | FatError::PartitionNotFound => ErrorKind::NotFound,
+            FatError::AlreadyExists => ErrorKind::AlreadyExists,
+            FatError::NoSpaceLeft => ErrorKind::StorageFull,
+            FatError::FileTooLarge => ErrorKind::FileTooLarge,
+            FatError::InvalidSeek | FatError::InvalidName => ErrorKind::InvalidInput,
+            FatError::OutOfBounds => ErrorKind::UnexpectedEof,
+            FatError::NotFat32
+            | FatError::MissingBootSignature
+            | FatError::InvalidBytesPerSector
+            | FatError::InvalidSectorsPerCluster
+            | FatError::InvalidReservedSectors
+            | FatError::InvalidFatCount
+            | FatError::InvalidFatSize
+            | FatError::InvalidTotalSectors
+            | FatError::VolumeExceedsDevice
+            | FatError::InvalidRootEntryCount
+            | FatError::ClusterCountMismatch
+            | FatError::InvalidRootCluster
+            | FatError::InvalidActiveFat
+            | FatError::ClusterLoop => ErrorKind::InvalidData,
+            _ => ErrorKind::Other,
+        };
+        std::io::Error::new(kind, std::format!("{e:?}"))
+    }
+}
@@ -0,0 +1,323 @@
+//! Long File Names (VFAT).
+//!
+//! Un nom long est stocké dans une suite de slots de 32 octets (attribut `0x0F`)
+//! placés juste avant l’entrée courte 8.3 qu’ils décrivent. Chaque slot porte
+//! 13 caractères UTF-16, un numéro d’ordre et le checksum du nom court.
+//!
+//! Sur le disque les slots sont rangés à l’envers : le premier slot rencontré
+//! porte le dernier morceau du nom (ordre `N | 0x40`), le dernier slot avant
+//! l’entrée courte porte l’ordre 1.
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+
+/// Valeur de l’octet d’attributs d’un slot LFN.
+pub(crate) const ATTR_LONG_NAME: u8 = 0x0F;
+
+/// Bit posé sur l’ordre du premier slot physique (dernier morceau logique).
+const LAST_LONG_ENTRY: u8 = 0x40;
+
+/// Nombre de caractères UTF-16 portés par un slot.
+const CHARS_PER_SLOT: usize = 13;
+
+/// Offsets des 13 caractères UTF-16 dans un slot (3 zones : 1..11, 14..26, 28..32).
+const CHAR_OFFSETS: [usize; CHARS_PER_SLOT] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+/// Indique si un slot de 32 octets est un slot LFN.
+pub(crate) fn is_lfn_slot(slot: &[u8]) -> bool {
+    slot.len() >= 32 && slot[11] & 0x3F == ATTR_LONG_NAME
+}
+
+/// Checksum du nom court (11 octets nom + extension) tel que défini par VFAT.
+///
+/// Chaque slot LFN recopie cette valeur, ce qui permet de vérifier que la suite
+/// de slots appartient bien à l’entrée courte qui la suit.
+pub(crate) fn short_name_checksum(raw: &[u8]) -> u8 {
+    let mut sum: u8 = 0;
+    for &b in &raw[..11] {
+        sum = ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(b);
+    }
+    sum
+}
+
+/// Reconstitue un nom long à partir des slots lus dans l’ordre du disque.
+///
+/// Usage : on appelle `push_slot` pour chaque slot LFN, puis `take_name`
+/// quand on tombe sur l’entrée courte. Toute incohérence (ordre inattendu,
+/// checksum différent, suite incomplète) fait simplement oublier la suite :
+/// l’entrée retombe alors sur son nom 8.3.
+#[derive(Debug, Default)]
+pub(crate) struct LfnAccumulator {
+    units: Vec<u16>,
+    checksum: u8,
+    /// Prochain ordre attendu (0 = aucune suite en cours ou suite terminée).
+    next_ord: u8,
+    complete: bool,
+}
+
+impl LfnAccumulator {
+    /// Ajoute un slot LFN (32 octets).
+    pub(crate) fn push_slot(&mut self, slot: &[u8]) {
+        let ord = slot[0];
+        let seq = ord & 0x1F;
+        let checksum = slot[13];
+
+        if ord & LAST_LONG_ENTRY != 0 {
+            // Début d’une nouvelle suite (le slot porte le dernier morceau du nom)
+            if seq == 0 {
+                self.reset();
+                return;
+            }
+            self.units = vec![0xFFFF; seq as usize * CHARS_PER_SLOT];
+            self.checksum = checksum;
+        } else if self.next_ord == 0 || seq != self.next_ord || checksum != self.checksum {
+            self.reset();
+            return;
+        }
+
+        let base = (seq as usize - 1) * CHARS_PER_SLOT;
+        for (i, &off) in CHAR_OFFSETS.iter().enumerate() {
+            self.units[base + i] = u16::from_le_bytes([slot[off], slot[off + 1]]);
+        }
+
+        self.next_ord = seq - 1;
+        self.complete = self.next_ord == 0;
+    }
+
+    /// Termine la suite courante pour l’entrée courte `short_raw` (11 octets).
+    ///
+    /// Retourne le nom long si la suite est complète et que le checksum correspond.
+    pub(crate) fn take_name(&mut self, short_raw: &[u8]) -> Option<String> {
+        let ok = self.complete && self.checksum == short_name_checksum(short_raw);
+        let units = core::mem::take(&mut self.units);
+        self.reset();
+
+        if !ok {
+            return None;
+        }
+
+        // Le nom se termine au premier 0x0000 ; le reste est du padding 0xFFFF.
+        let end = units
+            .iter()
+            .position(|&u| u == 0x0000)
+            .unwrap_or(units.len());
+        let name: String = char::decode_utf16(units[..end].iter().copied())
+            .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Oublie la suite en cours (slot supprimé, slot orphelin…).
+    pub(crate) fn reset(&mut self) {
+        self.units.clear();
+        self.next_ord = 0;
+        self.complete = false;
+    }
+}
+
+/// Reconstitue le nom long d’une entrée supprimée à partir de ses slots LFN,
+/// eux aussi supprimés (`slots` dans l’ordre du disque).
+///
+/// Les numéros d’ordre ont été écrasés par `0xE5` : on se fie à la position
+/// (le slot le plus proche de l’entrée courte porte le début du nom). Tous
+/// les slots doivent porter le même checksum, retourné avec le nom.
+pub(crate) fn decode_deleted_lfn(slots: &[[u8; 32]]) -> Option<(String, u8)> {
+    let checksum = slots.first()?[13];
+    if slots.iter().any(|s| s[13] != checksum) {
+        return None;
+    }
+
+    let units: Vec<u16> = slots
+        .iter()
+        .rev()
+        .flat_map(|slot| {
+            CHAR_OFFSETS
+                .iter()
+                .map(move |&off| u16::from_le_bytes([slot[off], slot[off + 1]]))
+        })
+        .take_while(|&u| u != 0x0000)
+        .collect();
+
+    let name: String = char::decode_utf16(units)
+        .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name, checksum))
+    }
+}
+
+/// Ordre à réécrire dans le slot `i` (ordre du disque) d’une suite de `count`
+/// slots, lors de la restauration d’un nom long.
+pub(crate) fn lfn_order(i: usize, count: usize) -> u8 {
+    let seq = (count - i) as u8;
+    if i == 0 {
+        seq | LAST_LONG_ENTRY
+    } else {
+        seq
+    }
+}
+
+/// Caractères interdits dans un nom long (en plus des caractères de contrôle).
+const FORBIDDEN_LONG_CHARS: &[char] = &['"', '*', '/', ':', '<', '>', '?', '\\', '|'];
+
+/// Longueur maximale d’un nom long, en unités UTF-16.
+const MAX_LONG_NAME_UNITS: usize = 255;
+
+/// Vérifie qu’un nom peut être écrit comme nom long.
+///
+/// On refuse `.`/`..`, les caractères interdits par VFAT, et les noms qui se
+/// terminent par un espace ou un point (Windows les supprimerait silencieusement).
+pub(crate) fn is_valid_long_name(name: &str) -> bool {
+    if name.is_empty() || name == "." || name == ".." {
+        return false;
+    }
+    if name.ends_with(' ') || name.ends_with('.') {
+        return false;
+    }
+    if name
+        .chars()
+        .any(|c| (c as u32) < 0x20 || FORBIDDEN_LONG_CHARS.contains(&c))
+    {
+        return false;
+    }
+    name.encode_utf16().count() <= MAX_LONG_NAME_UNITS
+}
+
+/// Construit les slots LFN d’un nom, dans l’ordre où ils doivent être écrits
+/// sur le disque (dernier morceau en premier), juste avant l’entrée courte.
+pub(crate) fn encode_lfn_slots(name: &str, checksum: u8) -> Vec<[u8; 32]> {
+    let mut units: Vec<u16> = name.encode_utf16().collect();
+
+    // Terminateur 0x0000 seulement si le dernier slot n’est pas plein, puis padding 0xFFFF.
+    if !units.len().is_multiple_of(CHARS_PER_SLOT) {
+        units.push(0x0000);
+    }
+    while !units.len().is_multiple_of(CHARS_PER_SLOT) {
+        units.push(0xFFFF);
+    }
+
+    let count = units.len() / CHARS_PER_SLOT;
+    let mut slots = Vec::with_capacity(count);
+
+    for seq in (1..=count).rev() {
+        let mut slot = [0u8; 32];
+        slot[0] = seq as u8;
+        if seq == count {
+            slot[0] |= LAST_LONG_ENTRY;
+        }
+        slot[11] = ATTR_LONG_NAME;
+        slot[13] = checksum;
+
+        let base = (seq - 1) * CHARS_PER_SLOT;
+        for (i, &off) in CHAR_OFFSETS.iter().enumerate() {
+            slot[off..off + 2].copy_from_slice(&units[base + i].to_le_bytes());
+        }
+        slots.push(slot);
+    }
+
+    slots
+}
+
+/// Indique si un caractère ASCII est accepté tel quel dans un nom court.
+pub(crate) fn is_short_name_char(b: u8) -> bool {
+    b.is_ascii_uppercase() || b.is_ascii_digit() || b"$%'-_@~`!(){}^#&".contains(&b)
+}
+
+/// Calcule la “base” du nom court d’un nom long (11 octets, padding espace).
+///
+/// Retourne aussi `lossy` : vrai si la conversion a perdu de l’information
+/// (caractère remplacé, point ou espace supprimé, nom tronqué). Dans ce cas
+/// il faut ajouter un suffixe numérique `~N` pour obtenir un alias.
+pub(crate) fn short_name_basis(name: &str) -> ([u8; 11], bool) {
+    let mut lossy = false;
+
+    let trimmed = name.trim_start_matches('.');
+    if trimmed.len() != name.len() {
+        lossy = true;
+    }
+
+    let (base, ext) = match trimmed.rfind('.') {
+        Some(dot) => (&trimmed[..dot], &trimmed[dot + 1..]),
+        None => (trimmed, ""),
+    };
+
+    let mut raw = [b' '; 11];
+    let mut fill = |part: &str, out: &mut [u8]| {
+        let mut n = 0;
+        for ch in part.chars() {
+            if ch == ' ' || ch == '.' {
+                lossy = true;
+                continue;
+            }
+
+            let b = if ch.is_ascii() {
+                let up = ch.to_ascii_uppercase() as u8;
+                if is_short_name_char(up) {
+                    up
+                } else {
+                    lossy = true;
+                    b'_'
+                }
+            } else {
+                lossy = true;
+                b'_'
+            };
+
+            if n == out.len() {
+                lossy = true;
+                break;
+            }
+            out[n] = b;
+            n += 1;
+        }
+    };
+
+    let (name_part, ext_part) = raw.split_at_mut(8);
+    fill(base, name_part);
+    fill(ext, ext_part);
+
+    if raw[0] == b' ' {
+        raw[0] = b'_';
+        lossy = true;
+    }
+
+    (raw, lossy)
+}
+
+/// Applique un suffixe numérique `~N` à une base de nom court.
+///
+/// Exemple : `PROGRAMF` + 1 -> `PROGRA~1`, `AB` + 12 -> `AB~12`.
+pub(crate) fn with_numeric_tail(basis: &[u8; 11], n: u32) -> [u8; 11] {
+    let mut digits = [0u8; 10];
+    let mut len = 0;
+    let mut v = n;
+    loop {
+        digits[len] = b'0' + (v % 10) as u8;
+        len += 1;
+        v /= 10;
+        if v == 0 {
+            break;
+        }
+    }
+
+    let tail_len = len + 1;
+    let base_len = basis[..8].iter().position(|&b| b == b' ').unwrap_or(8);
+    let keep = core::cmp::min(base_len, 8 - tail_len);
+
+    let mut raw = [b' '; 11];
+    raw[..keep].copy_from_slice(&basis[..keep]);
+    raw[keep] = b'~';
+    for i in 0..len {
+        raw[keep + 1 + i] = digits[len - 1 - i];
+    }
+    raw[8..11].copy_from_slice(&basis[8..11]);
+    raw
+}
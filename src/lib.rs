@@ -1,37 +1,102 @@
 //! Parseur FAT32 (lecture + écriture simple).
 //!
-//! Ce crate manipule un volume FAT32 directement depuis un buffer mémoire.
+//! Ce crate manipule un volume FAT32 à travers un support bloc (`BlockDevice`) :
+//! un buffer mémoire, un fichier image ou une carte SD.
 //! Il permet :
 //! - de lister des répertoires et lire des fichiers (lecture),
-//! - de créer ou écraser un fichier 8.3 et écrire ses données (écriture simple),
-//!   en modifiant réellement le buffer du “disque”.
+//! - de créer ou écraser un fichier et écrire ses données (écriture simple),
+//!   en modifiant réellement les secteurs du “disque”.
 //!
 //! Notes importantes :
-//! - Le cœur est en `no_std` (hors tests) et n’utilise que `core` et `alloc`.
-//! - L’écriture vise uniquement les noms courts FAT (format 8.3). Pas de LFN.
-//! - On ne gère pas la création de répertoires (le parent doit déjà exister).
-
-#![cfg_attr(not(test), no_std)]
+//! - Le cœur est en `no_std` (sans la feature `std`) et n’utilise que `core` et `alloc`.
+//! - Le volume n’est jamais chargé en entier : on lit/écrit les secteurs à la demande,
+//!   et `Fat32::open_file` lit un fichier cluster par cluster (`io::Read` + `io::Seek`).
+//! - `Fat32Mut::open_file_mut` / `append_file` / `create_file` donnent un `FileWriter`
+//!   (`io::Write` + `io::Seek`) pour écrire en flux, en place ou en fin de fichier.
+//! - Les noms longs (LFN/VFAT) sont reconstitués en lecture et créés en écriture,
+//!   avec un alias court 8.3 unique (`~N`).
+//! - `Fat32Mut::create_dir` crée un répertoire ; pour écrire un fichier,
+//!   le répertoire parent doit déjà exister.
+//! - Les horodatages sont décodés dans `DirEntry` ; en écriture, l’heure vient
+//!   d’une horloge fournie par l’appelant (`Fat32Mut::set_clock`).
+//! - Une image de disque complet (MBR avec partitions logiques, ou GPT) s’ouvre
+//!   partition par partition : `Fat32::open_partition` / `PartitionDevice`.
+//! - Les volumes FAT12 et FAT16 (disquettes, petites clés USB) sont aussi gérés,
+//!   en lecture et en écriture : le type vient du nombre de clusters
+//!   (`Fat32::fat_type`), et leur racine de taille fixe ne peut pas grandir.
+//! - `Fat32::check` vérifie la cohérence du volume (chaînes croisées, perdues,
+//!   qui bouclent…) et retourne un rapport typé (`CheckReport`).
+//! - Les `ext_flags` FAT32 sont respectés : sans miroir, seule la FAT active est
+//!   lue et écrite ; `Fat32::compare_fats` liste les écarts entre copies.
+//! - `Fat32Mut::repair` corrige ces problèmes ; en `dry_run`, il liste seulement
+//!   les octets qu’il modifierait (`ByteChange`).
+//! - `Fat32::list_deleted` retrouve les entrées supprimées et
+//!   `Fat32Mut::undelete` les restaure quand leurs clusters sont encore libres.
+//! - `Fat32Mut::format` formate un support en FAT32 (`FormatOptions`).
+//! - `Fat32::open_with_backup` se rabat sur la copie de secours du secteur de
+//!   boot et de FSInfo ; `Fat32Mut::restore_boot_sector` recopie l’une sur l’autre.
+//! - `Fat32::volume_info` lit le nom OEM, le numéro de série et le nom du volume
+//!   (`VolumeInfo`) ; `Fat32Mut::set_label` change le nom dans le BPB et la
+//!   racine, `Fat32Mut::set_serial` le numéro de série.
+//! - Le bit “volume propre” de `FAT[1]` est baissé pendant chaque écriture
+//!   (`Fat32::is_dirty`) : s’il reste baissé, une écriture a été interrompue.
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
 extern crate alloc;
 
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeSet, string::String, vec, vec::Vec};
 
+mod block;
+mod boot;
+mod check;
 mod dir_entry;
-
+mod dirty;
+mod file;
+mod gpt;
+pub mod io;
+mod lfn;
+mod mkfs;
+mod partition;
+mod repair;
+mod time;
+mod undelete;
+mod volume;
+
+pub use block::{BlockDevice, BlockDeviceMut, DEFAULT_BLOCK_SIZE};
+pub use boot::BootSector;
+pub use check::{CheckReport, FatDivergence, Problem};
 pub use dir_entry::{Attributes, DirEntry};
+pub use file::{FileReader, FileWriter};
+pub use gpt::{read_gpt, Gpt, GptPartition, Guid};
+pub use mkfs::FormatOptions;
+pub use partition::{find_partition, read_partitions, Partition, PartitionDevice};
+pub use repair::{ByteChange, RepairOptions, RepairReport};
+pub use time::{Clock, FatDate, FatTimestamp};
+pub use undelete::DeletedEntry;
+pub use volume::VolumeInfo;
+
+use block::{device_len, read_bytes, read_bytes_within, write_bytes_within};
+use dir_entry::short_name_to_string;
+use dirty::DirtyState;
+use lfn::{
+    encode_lfn_slots, is_lfn_slot, is_valid_long_name, short_name_basis, short_name_checksum,
+    with_numeric_tail, LfnAccumulator,
+};
 
 /// Erreurs possibles lors de l’accès à un volume FAT32.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FatError {
     /// Le buffer ne contient pas assez de données pour un volume valide.
     BufferTooSmall,
-    /// Les champs de l'en-tête ne correspondent pas à un volume FAT32 attendu.
+    /// Les champs de l'en-tête ne correspondent pas à un volume FAT (12/16/32) attendu.
     NotFat32,
     /// Tentative de lecture/écriture en dehors du buffer.
     OutOfBounds,
     /// Numéro de cluster invalide (ex: < 2).
     InvalidCluster,
+    /// Une chaîne de clusters repasse par un cluster déjà vu (FAT corrompue).
+    ClusterLoop,
     /// On tente de lire un répertoire comme un fichier.
     NotAFile,
     /// On tente de lister un fichier comme un répertoire.
@@ -42,6 +107,51 @@ pub enum FatError {
     InvalidName,
     /// Plus de place (pas assez de clusters libres ou pas de slot de dir libre).
     NoSpaceLeft,
+    /// Une entrée du même nom existe déjà dans le répertoire.
+    AlreadyExists,
+    /// On tente de supprimer un répertoire qui contient encore des entrées.
+    DirectoryNotEmpty,
+    /// Déplacement impossible (répertoire déplacé dans sa propre sous-arborescence).
+    InvalidMove,
+    /// Le support bloc a renvoyé une erreur de lecture ou d’écriture.
+    Io,
+    /// Déplacement (`seek`) vers une position avant le début du fichier.
+    InvalidSeek,
+    /// Le fichier dépasserait la taille maximale FAT (4 Gio - 1).
+    FileTooLarge,
+    /// Le secteur de boot ne se termine pas par la signature `0x55AA`.
+    MissingBootSignature,
+    /// Taille de secteur invalide (puissance de 2 entre 512 et 4096 attendue).
+    InvalidBytesPerSector,
+    /// Secteurs par cluster invalide (puissance de 2 entre 1 et 128 attendue).
+    InvalidSectorsPerCluster,
+    /// Aucun secteur réservé (le secteur de boot en fait partie).
+    InvalidReservedSectors,
+    /// Nombre de FAT nul.
+    InvalidFatCount,
+    /// Taille de FAT nulle, ou trop petite pour le nombre de clusters.
+    InvalidFatSize,
+    /// Nombre total de secteurs nul, ou trop petit pour contenir une zone data.
+    InvalidTotalSectors,
+    /// Le volume annoncé par le BPB dépasse la taille du support.
+    VolumeExceedsDevice,
+    /// Nombre d’entrées de la racine fixe incohérent avec le type de FAT.
+    InvalidRootEntryCount,
+    /// Nombre de clusters incompatible avec le type de FAT (ou nul).
+    ClusterCountMismatch,
+    /// Cluster racine FAT32 hors de la zone data.
+    InvalidRootCluster,
+    /// FAT active (`ext_flags` FAT32, miroir désactivé) au-delà du nombre de FAT.
+    InvalidActiveFat,
+    /// Pas de secteur de boot de secours (FAT12/16, ou champ du BPB à 0).
+    MissingBackupBootSector,
+    /// Pas de BPB étendu (signature `0x28` ou `0x29`) : aucun champ pour le
+    /// numéro de série.
+    MissingExtendedBpb,
+    /// Pas de table de partitions MBR ou GPT valide (signature ou CRC32 faux).
+    InvalidPartitionTable,
+    /// Aucune partition ne porte le numéro demandé.
+    PartitionNotFound,
     /// Erreur générique (ex: chemin relatif).
     Other,
 }
@@ -50,51 +160,268 @@ pub enum FatError {
 /// En pratique on considère EOC si `>= 0x0FFF_FFF8`.
 const FAT32_EOC: u32 = 0x0FFF_FFFF;
 
-/// Vue en lecture seule d’un volume FAT32 stocké dans un buffer mémoire.
+/// Attribut “archive” posé sur les fichiers qu’on écrit.
+const ATTR_ARCHIVE: u8 = 0x20;
+
+/// Attribut “répertoire”.
+const ATTR_DIRECTORY: u8 = 0x10;
+
+/// Taille maximale d’un fichier FAT (champ taille sur 32 bits).
+const MAX_FILE_SIZE: u64 = u32::MAX as u64;
+
+/// Nombre maximal d’entrées de 32 octets dans un répertoire FAT.
+const MAX_DIR_ENTRIES: usize = 65536;
+
+/// Entrée de répertoire avec sa position sur le support.
+///
+/// Sert aux opérations d’écriture qui doivent retrouver les slots à modifier.
+#[derive(Debug, Clone)]
+struct LocatedEntry {
+    entry: DirEntry,
+    /// Nom court brut (8 + 3 octets).
+    short_raw: [u8; 11],
+    /// Offset (en octets) de l’entrée courte sur le support.
+    offset: u64,
+    /// Offsets des slots LFN rattachés à l’entrée (ordre du disque).
+    lfn_offsets: Vec<u64>,
+}
+
+/// Contenu utile du secteur FSInfo (FAT32).
+///
+/// Ce sont des indices maintenus par le driver : ils évitent de rescanner
+/// toute la FAT pour connaître l’espace libre ou trouver un cluster libre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsInfo {
+    /// Nombre de clusters libres (`None` si inconnu ou incohérent).
+    pub free_count: Option<u32>,
+    /// Cluster à partir duquel chercher un cluster libre (`None` si inconnu).
+    pub next_free: Option<u32>,
+}
+
+/// Variante de FAT du volume (taille des entrées de la table).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FatType {
+    /// Entrées de 12 bits (disquettes, petits volumes).
+    Fat12,
+    /// Entrées de 16 bits.
+    Fat16,
+    /// Entrées de 32 bits (dont 28 utiles).
+    Fat32,
+}
+
+impl FatType {
+    /// Type FAT d’un volume d’après son nombre de clusters de données,
+    /// avec les seuils de la spécification Microsoft.
+    pub fn from_cluster_count(count: u64) -> Self {
+        if count < 4085 {
+            FatType::Fat12
+        } else if count < 65525 {
+            FatType::Fat16
+        } else {
+            FatType::Fat32
+        }
+    }
+
+    /// Bits utiles d’une entrée FAT.
+    fn entry_mask(self) -> u32 {
+        match self {
+            FatType::Fat12 => 0x0FFF,
+            FatType::Fat16 => 0xFFFF,
+            FatType::Fat32 => 0x0FFF_FFFF,
+        }
+    }
+
+    /// Ramène une entrée FAT12/16 sur l’échelle FAT32 : “cluster défectueux”
+    /// et “fin de chaîne” deviennent `0x0FFF_FFF7` et `>= 0x0FFF_FFF8`.
+    ///
+    /// Le reste du code ne connaît ainsi que les valeurs FAT32 ; à l’écriture,
+    /// il suffit de masquer la valeur (`entry_mask`).
+    fn widen(self, raw: u32) -> u32 {
+        let mask = self.entry_mask();
+        if raw >= mask - 8 {
+            raw | (0x0FFF_FFFF & !mask)
+        } else {
+            raw
+        }
+    }
+
+    /// Plus grand numéro de cluster utilisable (les suivants sont réservés).
+    fn max_cluster(self) -> u32 {
+        self.entry_mask() - 9
+    }
+
+    /// Offset de l’entrée d’un cluster depuis le début d’une FAT.
+    ///
+    /// En FAT12, une entrée tient sur un octet et demi : l’entrée `n`
+    /// commence à l’octet `n + n / 2`.
+    fn entry_offset(self, cluster: u32) -> u64 {
+        let cl = cluster as u64;
+        match self {
+            FatType::Fat12 => cl + cl / 2,
+            FatType::Fat16 => cl * 2,
+            FatType::Fat32 => cl * 4,
+        }
+    }
+
+    /// Octets à lire pour décoder une entrée (deux en FAT12 : elle est à cheval).
+    fn entry_bytes(self) -> usize {
+        match self {
+            FatType::Fat12 | FatType::Fat16 => 2,
+            FatType::Fat32 => 4,
+        }
+    }
+
+    /// Décode l’entrée du cluster `cluster` à partir de ses octets bruts
+    /// (`entry_bytes` octets lus à `entry_offset`), sur l’échelle FAT32.
+    fn decode(self, bytes: &[u8], cluster: u32) -> u32 {
+        let val = match self {
+            FatType::Fat12 => {
+                let pair = u16::from_le_bytes([bytes[0], bytes[1]]);
+                // Cluster impair : les 12 bits de poids fort
+                let v = if cluster & 1 == 1 {
+                    pair >> 4
+                } else {
+                    pair & 0x0FFF
+                };
+                v as u32
+            }
+            FatType::Fat16 => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            FatType::Fat32 => {
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) & 0x0FFF_FFFF
+            }
+        };
+        self.widen(val)
+    }
+}
+
+/// Signatures du secteur FSInfo (début, milieu, fin).
+const FSINFO_LEAD_SIG: u32 = 0x4161_5252;
+const FSINFO_STRUC_SIG: u32 = 0x6141_7272;
+const FSINFO_TRAIL_SIG: u32 = 0xAA55_0000;
+
+/// Slot de répertoire lu sur le support : offset et 32 octets bruts.
+type DirSlot = (u64, [u8; 32]);
+
+/// Slots libres trouvés dans un répertoire : offsets des slots, et offset
+/// où réécrire la fin de répertoire (`0x00`) si on l’a consommée.
+type FreeSlots = (Vec<u64>, Option<u64>);
+
+/// Vue en lecture seule d’un volume FAT32 posé sur un support bloc.
 ///
-/// Cette vue n’écrit jamais dans l’image.
+/// Malgré son nom, elle ouvre aussi les volumes FAT12 et FAT16 (voir `fat_type`).
+/// Cette vue n’écrit jamais sur le support.
 #[derive(Debug)]
-pub struct Fat32<'a> {
-    disk: &'a [u8],
+pub struct Fat32<D> {
+    dev: D,
+    /// Taille utile du support en octets (lue une fois à l’ouverture).
+    dev_len: u64,
     bytes_per_sector: u16,
     sectors_per_cluster: u8,
     reserved_sectors: u16,
     num_fats: u8,
+    /// Seule FAT tenue à jour quand le miroir est désactivé (`ext_flags`) ;
+    /// `None` : toutes les copies sont identiques et on lit la première.
+    active_fat: Option<u8>,
     sectors_per_fat: u32,
+    /// Nombre d’entrées de la racine fixe (FAT12/16 ; 0 en FAT32).
+    root_entry_count: u16,
+    /// Nombre total de secteurs du volume.
+    total_sectors: u32,
+    /// Cluster de la racine (FAT32) ; 0 désigne la racine fixe en FAT12/16.
     root_cluster: u32,
+    /// Secteur FSInfo lu et tenu à jour : celui du BPB, ou sa copie de
+    /// secours si `open_with_backup` a dû s’y rabattre.
+    fs_info_sector: u16,
+    /// Copie de secours de FSInfo, qui suit la copie du secteur de boot ;
+    /// ses compteurs sont tenus à jour avec ceux de `fs_info_sector` (0 si
+    /// le volume n’a pas de copie).
+    fs_info_backup: u16,
+    /// Copie de secours du secteur de boot (FAT32 ; 0 si absente).
+    backup_boot_sector: u16,
+    /// Copie du secteur de boot d’où vient la géométrie.
+    boot_source: BootSector,
+    fat_type: FatType,
 }
 
-/// Vue en lecture/écriture d’un volume FAT32 stocké dans un buffer mémoire.
+/// Vue en lecture/écriture d’un volume FAT32 posé sur un support bloc.
 ///
-/// Les opérations modifient directement `disk`.
-/// Si tu sauvegardes ce buffer dans un fichier (`disk.img`), la modification est persistante.
+/// Les opérations écrivent directement sur le support : avec un `File`,
+/// la modification est persistante dès le retour de la fonction.
 #[derive(Debug)]
-pub struct Fat32Mut<'a> {
-    disk: &'a mut [u8],
-    bytes_per_sector: u16,
-    sectors_per_cluster: u8,
-    reserved_sectors: u16,
-    num_fats: u8,
-    sectors_per_fat: u32,
-    root_cluster: u32,
+pub struct Fat32Mut<D> {
+    fs: Fat32<D>,
+    /// Horloge utilisée pour horodater les entrées (aucune par défaut).
+    clock: Option<Box<dyn Clock>>,
+    /// Suivi du bit “propre” de `FAT[1]` (voir le module `dirty`).
+    dirty: DirtyState,
 }
 
-impl<'a> Fat32<'a> {
+impl<'a> Fat32<&'a [u8]> {
     /// Construit une vue FAT32 depuis un dump en mémoire (lecture seule).
     ///
+    /// Raccourci pour `Fat32::open(disk)` sur un buffer.
+    pub fn new(disk: &'a [u8]) -> Result<Self, FatError> {
+        Self::open(disk)
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Ouvre un volume FAT32 sur un support bloc (lecture seule).
+    ///
     /// On lit un BPB minimal et on récupère les paramètres indispensables
     /// pour calculer les offsets (FAT, data, clusters).
-    pub fn new(disk: &'a [u8]) -> Result<Self, FatError> {
-        let p = parse_bpb(disk)?;
-        Ok(Self {
-            disk,
+    pub fn open(dev: D) -> Result<Self, FatError> {
+        let p = parse_bpb(&dev, 0)?;
+        Ok(Self::from_params(dev, p, BootSector::Primary))
+    }
+
+    /// Vue sur `dev` avec la géométrie d’un BPB déjà validé.
+    fn from_params(dev: D, p: BpbParams, boot_source: BootSector) -> Self {
+        Self {
+            dev_len: device_len(&dev),
+            dev,
             bytes_per_sector: p.bytes_per_sector,
             sectors_per_cluster: p.sectors_per_cluster,
             reserved_sectors: p.reserved_sectors,
             num_fats: p.num_fats,
+            active_fat: p.active_fat,
             sectors_per_fat: p.sectors_per_fat,
+            root_entry_count: p.root_entry_count,
+            total_sectors: p.total_sectors,
             root_cluster: p.root_cluster,
-        })
+            fs_info_sector: p.fs_info_sector,
+            fs_info_backup: match (p.backup_boot_sector, p.fs_info_sector) {
+                (0, _) | (_, 0) => 0,
+                (backup, fs_info) => backup.saturating_add(fs_info),
+            },
+            backup_boot_sector: p.backup_boot_sector,
+            boot_source,
+            fat_type: p.fat_type,
+        }
+    }
+
+    /// Ouvre le volume FAT de la partition numéro `index` du support.
+    ///
+    /// En MBR, les partitions primaires sont numérotées de 1 à 4, les logiques
+    /// à partir de 5 ; en GPT, c’est la position dans le tableau d’entrées.
+    pub fn open_partition(dev: D, index: usize) -> Result<Fat32<PartitionDevice<D>>, FatError> {
+        Fat32::open(PartitionDevice::open(dev, index)?)
+    }
+
+    /// Rend le support bloc sous-jacent.
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Variante de FAT du volume (FAT12, FAT16 ou FAT32).
+    pub fn fat_type(&self) -> FatType {
+        self.fat_type
+    }
+
+    /// FAT active (la première est 0) si le miroir est désactivé dans les
+    /// `ext_flags` du BPB FAT32 ; `None` si toutes les copies sont tenues à jour.
+    pub fn active_fat(&self) -> Option<u8> {
+        self.active_fat
     }
 
     /// Liste le contenu du répertoire racine.
@@ -141,8 +468,8 @@ impl<'a> Fat32<'a> {
     /// Résout un chemin absolu en une entrée de répertoire.
     ///
     /// - Le chemin doit commencer par `/`
-    /// - la recherche est case-insensitive sur les noms courts (8.3),
-    ///   parce qu’on normalise en majuscule
+    /// - chaque segment peut être le nom long ou le nom court 8.3
+    /// - la recherche est case-insensitive, parce qu’on normalise en majuscule
     pub fn open_path(&self, path: &str) -> Result<Option<DirEntry>, FatError> {
         if !path.starts_with('/') {
             return Err(FatError::Other);
@@ -155,20 +482,16 @@ impl<'a> Fat32<'a> {
         let mut last_entry: Option<DirEntry> = None;
 
         for part in path.split('/').filter(|s| !s.is_empty()) {
-            let target = normalize_name(part);
-            let entries = self.list_dir_cluster(current_cluster)?;
-
-            let mut found = None;
-            for e in entries {
-                if normalize_name(&e.name) == target {
-                    current_cluster = e.first_cluster;
-                    found = Some(e);
-                    break;
+            match self.find_in_dir(current_cluster, part)? {
+                Some(found) => {
+                    // `..` d’un sous-répertoire de la racine pointe vers le cluster 0
+                    current_cluster = if found.entry.is_dir() && found.entry.first_cluster == 0 {
+                        self.root_cluster
+                    } else {
+                        found.entry.first_cluster
+                    };
+                    last_entry = Some(found.entry);
                 }
-            }
-
-            match found {
-                Some(e) => last_entry = Some(e),
                 None => return Ok(None),
             }
         }
@@ -176,6 +499,25 @@ impl<'a> Fat32<'a> {
         Ok(last_entry)
     }
 
+    /// Ouvre un fichier en lecture à la demande (sans le charger en mémoire).
+    ///
+    /// Retourne `PathNotFound` si le chemin n’existe pas, `NotAFile` pour un répertoire.
+    pub fn open_file(&self, path: &str) -> Result<FileReader<'_, D>, FatError> {
+        let entry = self.open_path(path)?.ok_or(FatError::PathNotFound)?;
+        self.reader(&entry)
+    }
+
+    /// Crée un lecteur à partir d’une entrée (`DirEntry`) déjà résolue.
+    pub fn reader(&self, entry: &DirEntry) -> Result<FileReader<'_, D>, FatError> {
+        if !entry.is_file() {
+            return Err(FatError::NotAFile);
+        }
+        if entry.size > 0 && entry.first_cluster < 2 {
+            return Err(FatError::InvalidCluster);
+        }
+        Ok(FileReader::new(self, entry.first_cluster, entry.size))
+    }
+
     /// Lit un fichier à partir d’une entrée (`DirEntry`).
     ///
     /// On suit la chaîne de clusters dans la FAT, puis on reconstruit les octets
@@ -185,8 +527,8 @@ impl<'a> Fat32<'a> {
             return Err(FatError::NotAFile);
         }
 
-        let mut remaining = entry.size as usize;
-        if remaining == 0 {
+        let size = entry.size as usize;
+        if size == 0 {
             return Ok(Vec::new());
         }
 
@@ -196,17 +538,22 @@ impl<'a> Fat32<'a> {
         }
 
         let cluster_size = self.cluster_size();
-        let mut out = Vec::with_capacity(remaining);
+        let mut out = vec![0u8; size];
+        let mut pos = 0usize;
 
-        let chain = self.follow_chain(entry.first_cluster, 4096)?;
-        for cl in chain {
-            let cluster = self.read_cluster(cl)?;
-            let take = core::cmp::min(remaining, cluster_size);
+        let chain = self.follow_chain(entry.first_cluster)?;
+        if chain.len() < size.div_ceil(cluster_size) {
+            // Chaîne trop courte pour la taille annoncée : image incohérente
+            return Err(FatError::InvalidCluster);
+        }
 
-            out.extend_from_slice(&cluster[..take]);
-            remaining -= take;
+        for cl in chain {
+            let take = core::cmp::min(size - pos, cluster_size);
+            let off = self.cluster_to_offset(cl)?;
+            self.read_at(off, &mut out[pos..pos + take])?;
+            pos += take;
 
-            if remaining == 0 {
+            if pos == size {
                 break;
             }
         }
@@ -214,8 +561,120 @@ impl<'a> Fat32<'a> {
         Ok(out)
     }
 
+    /// Lit et valide le secteur FSInfo.
+    ///
+    /// Retourne `None` si le BPB ne pointe pas vers un FSInfo ou si les
+    /// signatures sont invalides. Les compteurs hors plage sont remontés en `None`.
+    pub fn fs_info(&self) -> Option<FsInfo> {
+        self.read_fs_info(self.fs_info_sector)
+    }
+
+    /// Lit et valide un secteur FSInfo (voir `fs_info`).
+    fn read_fs_info(&self, sector: u16) -> Option<FsInfo> {
+        let sector = sector as u64;
+        if sector == 0 || sector >= self.reserved_sectors as u64 {
+            return None;
+        }
+
+        let mut s = [0u8; 512];
+        self.read_at(sector * self.bytes_per_sector() as u64, &mut s)
+            .ok()?;
+
+        let le32 = |i: usize| u32::from_le_bytes([s[i], s[i + 1], s[i + 2], s[i + 3]]);
+        if le32(0) != FSINFO_LEAD_SIG
+            || le32(484) != FSINFO_STRUC_SIG
+            || le32(508) != FSINFO_TRAIL_SIG
+        {
+            return None;
+        }
+
+        let max = self.max_cluster_number().ok()?;
+        // Clusters 2..=max : il y en a `max - 1`, donc free_count < max
+        let free_count = Some(le32(488)).filter(|&n| n < max);
+        let next_free = Some(le32(492)).filter(|&n| (2..=max).contains(&n));
+
+        Some(FsInfo {
+            free_count,
+            next_free,
+        })
+    }
+
+    /// Compte les clusters libres en parcourant toute la FAT.
+    pub fn free_cluster_count(&self) -> Result<u32, FatError> {
+        let max = self.max_cluster_number()?;
+        let mut free = 0;
+        for cl in 2..=max {
+            if self.read_fat_entry(cl)? == 0 {
+                free += 1;
+            }
+        }
+        Ok(free)
+    }
+
     // ---------- internes (lecture) ----------
 
+    /// Même volume, vu à travers un autre support (ex: une surcouche qui
+    /// intercepte les écritures).
+    fn with_device<E>(&self, dev: E) -> Fat32<E> {
+        Fat32 {
+            dev,
+            dev_len: self.dev_len,
+            bytes_per_sector: self.bytes_per_sector,
+            sectors_per_cluster: self.sectors_per_cluster,
+            reserved_sectors: self.reserved_sectors,
+            num_fats: self.num_fats,
+            active_fat: self.active_fat,
+            sectors_per_fat: self.sectors_per_fat,
+            root_entry_count: self.root_entry_count,
+            total_sectors: self.total_sectors,
+            root_cluster: self.root_cluster,
+            fs_info_sector: self.fs_info_sector,
+            fs_info_backup: self.fs_info_backup,
+            backup_boot_sector: self.backup_boot_sector,
+            boot_source: self.boot_source,
+            fat_type: self.fat_type,
+        }
+    }
+
+    /// Dernier cluster valide, borné à la fois par:
+    /// - la taille de la zone data
+    /// - le nombre d’entrées disponibles dans la FAT
+    /// - les valeurs réservées du type de FAT
+    fn max_cluster_number(&self) -> Result<u32, FatError> {
+        let data_start = self.data_start_byte();
+        let volume_len = self.total_sectors as u64 * self.bytes_per_sector() as u64;
+        if data_start >= volume_len {
+            return Err(FatError::OutOfBounds);
+        }
+
+        let cs = self.cluster_size() as u64;
+        if cs == 0 {
+            return Err(FatError::NotFat32);
+        }
+
+        let data_len = volume_len - data_start;
+        let data_clusters = core::cmp::min(data_len / cs, u32::MAX as u64 - 2) as u32;
+        if data_clusters == 0 {
+            return Err(FatError::NotFat32);
+        }
+        let last_by_data = 2 + data_clusters - 1;
+
+        let fat_bits = match self.fat_type {
+            FatType::Fat12 => 12,
+            FatType::Fat16 => 16,
+            FatType::Fat32 => 32,
+        };
+        let fat_entries = (self.fat_bytes_len() * 8 / fat_bits).min(u32::MAX as u64) as u32;
+        if fat_entries < 3 {
+            return Err(FatError::NotFat32);
+        }
+        let last_by_fat = fat_entries - 1;
+
+        Ok(last_by_data
+            .min(last_by_fat)
+            .min(self.fat_type.max_cluster()))
+    }
+
     fn bytes_per_sector(&self) -> usize {
         self.bytes_per_sector as usize
     }
@@ -224,64 +683,114 @@ impl<'a> Fat32<'a> {
         self.bytes_per_sector() * self.sectors_per_cluster as usize
     }
 
-    fn fat_start_byte(&self) -> usize {
-        self.reserved_sectors as usize * self.bytes_per_sector()
+    fn fat_start_byte(&self) -> u64 {
+        self.reserved_sectors as u64 * self.bytes_per_sector() as u64
     }
 
-    fn data_start_byte(&self) -> usize {
-        self.fat_start_byte()
-            + (self.num_fats as usize * self.sectors_per_fat as usize) * self.bytes_per_sector()
+    fn fat_bytes_len(&self) -> u64 {
+        self.sectors_per_fat as u64 * self.bytes_per_sector() as u64
     }
 
-    fn cluster_to_offset(&self, cluster: u32) -> Result<usize, FatError> {
-        if cluster < 2 {
-            return Err(FatError::InvalidCluster);
-        }
+    /// Début de la copie `copy` de la FAT.
+    fn fat_copy_start(&self, copy: u8) -> u64 {
+        self.fat_start_byte() + copy as u64 * self.fat_bytes_len()
+    }
 
-        let index = (cluster - 2) as usize;
-        let offset = self.data_start_byte() + index * self.cluster_size();
+    /// Copie de la FAT qui fait foi : la FAT active, ou la première.
+    fn reference_fat(&self) -> u8 {
+        self.active_fat.unwrap_or(0)
+    }
 
-        if offset >= self.disk.len() {
-            return Err(FatError::OutOfBounds);
+    /// Copies de la FAT à tenir à jour : toutes, ou seulement la FAT active.
+    fn written_fats(&self) -> core::ops::Range<u8> {
+        match self.active_fat {
+            Some(n) => n..n + 1,
+            None => 0..self.num_fats,
         }
+    }
 
-        Ok(offset)
+    /// Début de la racine fixe FAT12/16, juste après les FAT.
+    fn root_dir_start_byte(&self) -> u64 {
+        self.fat_start_byte() + self.num_fats as u64 * self.fat_bytes_len()
     }
 
-    fn read_cluster(&self, cluster: u32) -> Result<&[u8], FatError> {
-        let offset = self.cluster_to_offset(cluster)?;
-        let size = self.cluster_size();
+    /// Taille de la racine fixe, arrondie au secteur (0 en FAT32).
+    fn root_dir_bytes_len(&self) -> u64 {
+        let bps = self.bytes_per_sector() as u64;
+        (self.root_entry_count as u64 * 32).div_ceil(bps) * bps
+    }
 
-        if offset + size > self.disk.len() {
-            return Err(FatError::OutOfBounds);
-        }
+    fn data_start_byte(&self) -> u64 {
+        self.root_dir_start_byte() + self.root_dir_bytes_len()
+    }
 
-        Ok(&self.disk[offset..offset + size])
+    /// Indique si `cluster` désigne la racine fixe d’un volume FAT12/16.
+    fn is_fixed_root(&self, cluster: u32) -> bool {
+        cluster == 0 && self.fat_type != FatType::Fat32
     }
 
-    fn read_fat_entry(&self, cluster: u32) -> Result<u32, FatError> {
-        let fat_start = self.fat_start_byte();
-        let entry_offset = fat_start + cluster as usize * 4;
+    /// Offset d’une entrée dans la FAT de référence (voir `reference_fat`).
+    fn fat_entry_offset(&self, cluster: u32) -> u64 {
+        self.fat_copy_start(self.reference_fat()) + self.fat_type.entry_offset(cluster)
+    }
 
-        if entry_offset + 4 > self.disk.len() {
+    fn cluster_to_offset(&self, cluster: u32) -> Result<u64, FatError> {
+        if cluster < 2 {
+            return Err(FatError::InvalidCluster);
+        }
+
+        let index = (cluster - 2) as u64;
+        let offset = self.data_start_byte() + index * self.cluster_size() as u64;
+
+        if offset >= self.dev_len {
             return Err(FatError::OutOfBounds);
         }
 
-        let bytes = &self.disk[entry_offset..entry_offset + 4];
-        let val = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        Ok(offset)
+    }
 
-        Ok(val & 0x0FFF_FFFF)
+    /// Lit `buf.len()` octets à `offset`, bornés par la taille du support
+    /// relevée à l’ouverture (sans la redemander au support à chaque accès).
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        read_bytes_within(&self.dev, self.dev_len, offset, buf)
     }
 
-    fn follow_chain(&self, start_cluster: u32, max_clusters: usize) -> Result<Vec<u32>, FatError> {
-        if start_cluster < 2 {
+    fn read_cluster(&self, cluster: u32) -> Result<Vec<u8>, FatError> {
+        let offset = self.cluster_to_offset(cluster)?;
+        let mut buf = vec![0u8; self.cluster_size()];
+        self.read_at(offset, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Lit l’entrée FAT d’un cluster, ramenée sur l’échelle FAT32
+    /// (fin de chaîne `>= 0x0FFF_FFF8` quel que soit le type de FAT).
+    fn read_fat_entry(&self, cluster: u32) -> Result<u32, FatError> {
+        let mut bytes = [0u8; 4];
+        let bytes = &mut bytes[..self.fat_type.entry_bytes()];
+        self.read_at(self.fat_entry_offset(cluster), bytes)?;
+        Ok(self.fat_type.decode(bytes, cluster))
+    }
+
+    /// Clusters d’une chaîne, dans l’ordre, jusqu’à la fin de chaîne.
+    ///
+    /// Chaque maillon doit désigner un cluster de la zone data
+    /// (`InvalidCluster` sinon, cluster défectueux compris) et n’apparaître
+    /// qu’une fois (`ClusterLoop`) : la chaîne ne dépasse donc jamais le
+    /// nombre de clusters du volume, et n’est jamais coupée en silence.
+    fn follow_chain(&self, start_cluster: u32) -> Result<Vec<u32>, FatError> {
+        let max = self.max_cluster_number()?;
+        if !(2..=max).contains(&start_cluster) {
             return Err(FatError::InvalidCluster);
         }
 
         let mut chain = Vec::new();
+        let mut seen = BTreeSet::new();
         let mut current = start_cluster;
 
-        for _ in 0..max_clusters {
+        loop {
+            if !seen.insert(current) {
+                return Err(FatError::ClusterLoop);
+            }
             chain.push(current);
 
             let next = self.read_fat_entry(current)?;
@@ -289,7 +798,7 @@ impl<'a> Fat32<'a> {
                 break;
             }
 
-            if next < 2 {
+            if !(2..=max).contains(&next) {
                 return Err(FatError::InvalidCluster);
             }
             current = next;
@@ -298,111 +807,204 @@ impl<'a> Fat32<'a> {
         Ok(chain)
     }
 
-    fn list_dir_cluster(&self, start_cluster: u32) -> Result<Vec<DirEntry>, FatError> {
-        let mut entries = Vec::new();
-        let chain = self.follow_chain(start_cluster, 4096)?;
+    /// Slots de 32 octets d’un répertoire (avec leur offset sur le support),
+    /// dans l’ordre logique (on suit la chaîne de clusters).
+    ///
+    /// La racine fixe FAT12/16 (cluster 0) est lue d’un bloc.
+    fn dir_slots(&self, start_cluster: u32) -> Result<Vec<DirSlot>, FatError> {
+        if self.is_fixed_root(start_cluster) {
+            let off = self.root_dir_start_byte();
+            let mut data = vec![0u8; self.root_entry_count as usize * 32];
+            self.read_at(off, &mut data)?;
+
+            let mut slots = Vec::with_capacity(self.root_entry_count as usize);
+            for (i, raw) in data.chunks_exact(32).enumerate() {
+                let mut slot = [0u8; 32];
+                slot.copy_from_slice(raw);
+                slots.push((off + i as u64 * 32, slot));
+            }
+            return Ok(slots);
+        }
 
-        let mut end_seen = false;
+        let chain = self.follow_chain(start_cluster)?;
+        self.chain_slots(&chain)
+    }
 
-        for cl in chain {
-            if end_seen {
-                break;
+    /// Slots de 32 octets des clusters `chain`, dans l’ordre.
+    fn chain_slots(&self, chain: &[u32]) -> Result<Vec<DirSlot>, FatError> {
+        let mut slots = Vec::with_capacity(chain.len() * self.cluster_size() / 32);
+        for &cl in chain {
+            let off = self.cluster_to_offset(cl)?;
+            let data = self.read_cluster(cl)?;
+            for (i, raw) in data.chunks_exact(32).enumerate() {
+                let mut slot = [0u8; 32];
+                slot.copy_from_slice(raw);
+                slots.push((off + i as u64 * 32, slot));
             }
+        }
 
-            let data = self.read_cluster(cl)?;
-            for chunk in data.chunks(32) {
-                if chunk.len() < 32 {
-                    break;
-                }
+        Ok(slots)
+    }
 
-                // 0x00 = fin de répertoire (à partir de là, tout est libre)
-                if chunk[0] == 0x00 {
-                    end_seen = true;
-                    break;
-                }
+    /// Liste les entrées d’un répertoire avec leur position sur le support
+    /// (voir `parse_dir_slots`).
+    fn list_dir_located(&self, start_cluster: u32) -> Result<Vec<LocatedEntry>, FatError> {
+        Ok(parse_dir_slots(self.dir_slots(start_cluster)?))
+    }
 
-                if let Some(e) = DirEntry::parse(chunk) {
-                    entries.push(e);
-                }
+    /// Liste les entrées d’un répertoire (noms longs recollés).
+    fn list_dir_cluster(&self, start_cluster: u32) -> Result<Vec<DirEntry>, FatError> {
+        Ok(self
+            .list_dir_located(start_cluster)?
+            .into_iter()
+            .map(|l| l.entry)
+            .collect())
+    }
+
+    /// Cherche une entrée par nom (long ou court, sans casse) dans un répertoire.
+    fn find_in_dir(&self, dir_cluster: u32, name: &str) -> Result<Option<LocatedEntry>, FatError> {
+        let target = normalize_name(name);
+        Ok(self.list_dir_located(dir_cluster)?.into_iter().find(|l| {
+            normalize_name(&l.entry.name) == target || normalize_name(&l.entry.short_name) == target
+        }))
+    }
+
+    /// Indique si `dir_cluster` est `ancestor` ou l’un de ses descendants.
+    ///
+    /// On remonte les entrées `..` depuis `dir_cluster` jusqu’à la racine.
+    fn is_same_or_descendant(&self, dir_cluster: u32, ancestor: u32) -> Result<bool, FatError> {
+        let mut current = dir_cluster;
+        let mut visited = Vec::new();
+
+        while current != self.root_cluster {
+            if current == ancestor {
+                return Ok(true);
+            }
+            if visited.contains(&current) {
+                // `..` qui boucle : image incohérente
+                return Err(FatError::InvalidCluster);
             }
+            visited.push(current);
+
+            let parent = self
+                .list_dir_cluster(current)?
+                .into_iter()
+                .find(|e| e.name == "..")
+                .ok_or(FatError::InvalidCluster)?;
+            current = if parent.first_cluster == 0 {
+                self.root_cluster
+            } else {
+                parent.first_cluster
+            };
+        }
+
+        Ok(current == ancestor)
+    }
+
+    /// Retourne le premier cluster du répertoire désigné par un chemin absolu.
+    fn resolve_dir_cluster(&self, path: &str) -> Result<u32, FatError> {
+        if path == "/" {
+            return Ok(self.root_cluster);
         }
 
-        Ok(entries)
+        let entry = self.open_path(path)?.ok_or(FatError::PathNotFound)?;
+        if !entry.is_dir() {
+            return Err(FatError::NotADirectory);
+        }
+        Ok(entry.first_cluster)
     }
 }
 
-impl<'a> Fat32Mut<'a> {
+impl<'a> Fat32Mut<&'a mut [u8]> {
     /// Construit une vue FAT32 depuis un dump en mémoire (lecture/écriture).
+    ///
+    /// Raccourci pour `Fat32Mut::open(disk)` sur un buffer.
     pub fn new(disk: &'a mut [u8]) -> Result<Self, FatError> {
-        let p = parse_bpb(&*disk)?;
+        Self::open(disk)
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Ouvre un volume FAT32 sur un support bloc (lecture/écriture).
+    pub fn open(dev: D) -> Result<Self, FatError> {
+        Self::from_fs(Fat32::open(dev)?)
+    }
+
+    /// Vue en écriture sur un volume déjà ouvert.
+    fn from_fs(fs: Fat32<D>) -> Result<Self, FatError> {
         Ok(Self {
-            disk,
-            bytes_per_sector: p.bytes_per_sector,
-            sectors_per_cluster: p.sectors_per_cluster,
-            reserved_sectors: p.reserved_sectors,
-            num_fats: p.num_fats,
-            sectors_per_fat: p.sectors_per_fat,
-            root_cluster: p.root_cluster,
+            dirty: fs.initial_dirty_state()?,
+            fs,
+            clock: None,
         })
     }
 
-    /// Donne une vue lecture seule sur le même buffer.
+    /// Ouvre en écriture le volume FAT de la partition numéro `index`
+    /// (voir `Fat32::open_partition`).
+    pub fn open_partition(dev: D, index: usize) -> Result<Fat32Mut<PartitionDevice<D>>, FatError> {
+        Fat32Mut::open(PartitionDevice::open(dev, index)?)
+    }
+
+    /// Rend le support bloc sous-jacent.
+    pub fn into_inner(self) -> D {
+        self.fs.dev
+    }
+
+    /// Définit l’horloge utilisée pour horodater les écritures.
+    ///
+    /// Sans horloge (cas par défaut, le cœur est `no_std`), les entrées créées
+    /// ou modifiées sont datées du 1980-01-01 00:00:00. Pour des builds
+    /// reproductibles, on peut passer un `FatTimestamp` fixe.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Some(Box::new(clock));
+    }
+
+    /// Donne une vue lecture seule sur le même support.
     ///
     /// Ça permet de réutiliser `open_path` / `list_root` sans dupliquer la logique.
-    pub fn as_read(&self) -> Fat32<'_> {
-        Fat32 {
-            disk: &*self.disk,
-            bytes_per_sector: self.bytes_per_sector,
-            sectors_per_cluster: self.sectors_per_cluster,
-            reserved_sectors: self.reserved_sectors,
-            num_fats: self.num_fats,
-            sectors_per_fat: self.sectors_per_fat,
-            root_cluster: self.root_cluster,
-        }
+    pub fn as_read(&self) -> &Fat32<D> {
+        &self.fs
     }
 
     /// Écrit un fichier (création ou overwrite) dans l’image FAT32.
     ///
     /// Règles simples (volontaires) :
     /// - `path` doit être absolu et viser un fichier (pas un répertoire)
-    /// - nom court 8.3 uniquement (ex: `HELLO.TXT`, `A.TXT`, `FILE`)
+    /// - le nom peut être long et en Unicode (ex: `Rapport final.pdf`)
     /// - le répertoire parent doit exister
     ///
     /// Comportement :
-    /// - si le fichier existe, on libère son ancienne chaîne de clusters
+    /// - si le fichier existe (nom long ou court), on libère son ancienne chaîne de clusters
     /// - puis on alloue une nouvelle chaîne, on écrit les données, et on met à jour l’entrée
     /// - si `content` est vide, on crée un fichier vide (cluster = 0)
+    /// - à la création, un nom qui n’est pas un 8.3 strict reçoit des slots LFN
+    ///   et un alias court unique (`RAPPOR~1.PDF`)
     pub fn write_file_by_path(&mut self, path: &str, content: &[u8]) -> Result<(), FatError> {
+        let result = self.store_file(path, content);
+        self.finish(result)
+    }
+
+    /// Corps de `write_file_by_path`.
+    fn store_file(&mut self, path: &str, content: &[u8]) -> Result<(), FatError> {
         if !path.starts_with('/') || path == "/" {
             return Err(FatError::Other);
         }
 
         let (parent_path, file_name) = split_parent(path)?;
-        let (name_raw, ext_raw) = encode_short_name_8_3(file_name)?;
-
-        let parent_cluster = if parent_path == "/" {
-            self.root_cluster
-        } else {
-            let entry = self
-                .as_read()
-                .open_path(parent_path)?
-                .ok_or(FatError::PathNotFound)?;
-            if !entry.is_dir() {
-                return Err(FatError::NotADirectory);
-            }
-            entry.first_cluster
-        };
+        if !is_valid_long_name(file_name) {
+            return Err(FatError::InvalidName);
+        }
 
-        let (existing_off, existing_entry) =
-            self.find_dir_entry_offset_by_short_name(parent_cluster, &name_raw, &ext_raw)?;
+        let parent_cluster = self.fs.resolve_dir_cluster(parent_path)?;
+        let existing = self.fs.find_in_dir(parent_cluster, file_name)?;
 
         // Overwrite: on libère l’ancienne chaîne
-        if let Some(e) = existing_entry.as_ref() {
-            if e.is_dir() {
+        if let Some(found) = existing.as_ref() {
+            if found.entry.is_dir() {
                 return Err(FatError::NotAFile);
             }
-            if e.first_cluster >= 2 {
-                self.free_chain(e.first_cluster)?;
+            if found.entry.first_cluster >= 2 {
+                self.free_chain(found.entry.first_cluster)?;
             }
         }
 
@@ -410,7 +1012,7 @@ impl<'a> Fat32Mut<'a> {
         let first_cluster = if content.is_empty() {
             0u32
         } else {
-            let needed = div_ceil(content.len(), self.cluster_size());
+            let needed = div_ceil(content.len(), self.fs.cluster_size());
             let chain = self.alloc_chain(needed)?;
             self.write_chain_data(&chain, content)?;
             chain[0]
@@ -419,162 +1021,487 @@ impl<'a> Fat32Mut<'a> {
         // Écriture / mise à jour de l’entrée de répertoire
         let size = content.len() as u32;
 
-        match existing_off {
-            Some(off) => {
-                self.write_dir_entry_at_offset(off, &name_raw, &ext_raw, first_cluster, size)?;
+        match existing {
+            Some(found) => {
+                self.update_entry_after_write(found.offset, first_cluster, size)?;
             }
             None => {
-                let (free_off, was_end_marker, entry_end_in_disk) =
-                    self.find_free_dir_entry_slot(parent_cluster)?;
-                self.write_dir_entry_at_offset(
-                    free_off,
-                    &name_raw,
-                    &ext_raw,
+                self.create_dir_entry(
+                    parent_cluster,
+                    file_name,
+                    ATTR_ARCHIVE,
                     first_cluster,
                     size,
+                    None,
                 )?;
-
-                // Si on a remplacé un 0x00 (end-of-dir), on remet un 0x00 juste après
-                // (si ça rentre dans le buffer). Ça garde un répertoire “propre”.
-                if was_end_marker {
-                    let next = free_off + 32;
-                    if next < entry_end_in_disk {
-                        self.disk[next] = 0x00;
-                    }
-                }
             }
         }
 
         Ok(())
     }
 
-    // ---------- internes (écriture) ----------
+    /// Ouvre un fichier existant en écriture, curseur au début.
+    ///
+    /// Rien n’est tronqué : on peut réécrire une plage d’octets en place
+    /// (après un `seek`), ou écrire au-delà de la fin pour l’agrandir.
+    pub fn open_file_mut(&mut self, path: &str) -> Result<FileWriter<'_, D>, FatError> {
+        let found = self.locate_path(path)?;
+        if found.entry.is_dir() {
+            return Err(FatError::NotAFile);
+        }
+        Ok(FileWriter::new(
+            self,
+            found.offset,
+            found.entry.first_cluster,
+            found.entry.size,
+        ))
+    }
 
-    fn bytes_per_sector(&self) -> usize {
-        self.bytes_per_sector as usize
+    /// Ouvre un fichier en ajout, curseur à la fin (créé vide s’il n’existe pas).
+    pub fn append_file(&mut self, path: &str) -> Result<FileWriter<'_, D>, FatError> {
+        if self.fs.open_path(path)?.is_none() {
+            self.write_file_by_path(path, &[])?;
+        }
+        let mut w = self.open_file_mut(path)?;
+        io::Seek::seek(&mut w, io::SeekFrom::End(0))?;
+        Ok(w)
     }
 
-    fn cluster_size(&self) -> usize {
-        self.bytes_per_sector() * self.sectors_per_cluster as usize
+    /// Crée un fichier vide (ou tronque un fichier existant) et l’ouvre en écriture.
+    pub fn create_file(&mut self, path: &str) -> Result<FileWriter<'_, D>, FatError> {
+        self.write_file_by_path(path, &[])?;
+        self.open_file_mut(path)
     }
 
-    fn fat_start_byte(&self) -> usize {
-        self.reserved_sectors as usize * self.bytes_per_sector()
+    /// Change la taille d’un fichier (équivalent de `truncate`).
+    ///
+    /// - en réduisant, la chaîne est coupée au dernier cluster utile (nouvel EOC)
+    ///   et la fin de chaîne est libérée ; `new_len == 0` libère tout
+    /// - en agrandissant, les clusters manquants sont alloués et tous les octets
+    ///   entre l’ancienne et la nouvelle taille sont mis à zéro
+    ///
+    /// Des clusters déjà réservés par `preallocate` sont réutilisés.
+    pub fn set_len(&mut self, path: &str, new_len: u64) -> Result<(), FatError> {
+        let result = self.resize_file(path, new_len);
+        self.finish(result)
     }
 
-    fn fat_bytes_len(&self) -> usize {
-        self.sectors_per_fat as usize * self.bytes_per_sector()
+    /// Corps de `set_len`.
+    fn resize_file(&mut self, path: &str, new_len: u64) -> Result<(), FatError> {
+        if new_len > MAX_FILE_SIZE {
+            return Err(FatError::FileTooLarge);
+        }
+
+        let found = self.locate_path(path)?;
+        if found.entry.is_dir() {
+            return Err(FatError::NotAFile);
+        }
+
+        let cs = self.fs.cluster_size() as u64;
+        let needed = div_ceil(new_len as usize, cs as usize);
+        let mut chain = self.file_chain(found.entry.first_cluster)?;
+
+        if chain.len() > needed {
+            // Coupe : le dernier cluster gardé devient la fin de chaîne
+            if needed > 0 {
+                self.write_fat_entry_all(chain[needed - 1], FAT32_EOC)?;
+            }
+            self.free_chain(chain[needed])?;
+            chain.truncate(needed);
+        } else if chain.len() < needed {
+            let extra = self.alloc_chain(needed - chain.len())?;
+            if let Some(&last) = chain.last() {
+                self.write_fat_entry_all(last, extra[0])?;
+            }
+            chain.extend(extra);
+        }
+
+        // Tout ce qui devient visible au-delà de l’ancienne taille doit lire 0.
+        let old_len = found.entry.size as u64;
+        if new_len > old_len {
+            let zeros = vec![0u8; cs as usize];
+            let mut at = old_len;
+            while at < new_len {
+                let in_cluster = at % cs;
+                let n = core::cmp::min(cs - in_cluster, new_len - at);
+                let off = self.fs.cluster_to_offset(chain[(at / cs) as usize])?;
+                self.write_at(off + in_cluster, &zeros[..n as usize])?;
+                at += n;
+            }
+        }
+
+        let first_cluster = chain.first().copied().unwrap_or(0);
+        self.update_entry_after_write(found.offset, first_cluster, new_len as u32)?;
+        Ok(())
     }
 
-    fn data_start_byte(&self) -> usize {
-        self.fat_start_byte()
-            + (self.num_fats as usize * self.sectors_per_fat as usize) * self.bytes_per_sector()
+    /// Réserve à l’avance des clusters contigus pour `len` octets, sans changer
+    /// la taille du fichier.
+    ///
+    /// Pensé pour les enregistreurs de données : les écritures suivantes
+    /// (`FileWriter`) remplissent les clusters réservés sans toucher à la FAT,
+    /// ce qui donne une latence prévisible. Les clusters ajoutés sont pris d’un
+    /// seul tenant, si possible juste après la fin actuelle de la chaîne ;
+    /// `NoSpaceLeft` s’il n’existe pas de zone libre assez grande.
+    ///
+    /// Un `set_len` à la taille finale libère ensuite la réserve inutilisée.
+    ///
+    /// Tant qu’elle n’est pas remplie, `check` signale la réserve comme une
+    /// chaîne trop longue (`Problem::ChainTooLong`) sans que le volume soit
+    /// incohérent ; `repair` la garde, sauf avec `trim_reserved`.
+    pub fn preallocate(&mut self, path: &str, len: u64) -> Result<(), FatError> {
+        let result = self.reserve_clusters(path, len);
+        self.finish(result)
     }
 
-    /// Dernier cluster valide, borné à la fois par:
-    /// - la taille de la zone data
-    /// - le nombre d’entrées disponibles dans la FAT
-    fn max_cluster_number(&self) -> Result<u32, FatError> {
-        let data_start = self.data_start_byte();
-        if data_start >= self.disk.len() {
-            return Err(FatError::OutOfBounds);
+    /// Corps de `preallocate`.
+    fn reserve_clusters(&mut self, path: &str, len: u64) -> Result<(), FatError> {
+        if len > MAX_FILE_SIZE {
+            return Err(FatError::FileTooLarge);
         }
 
-        let cs = self.cluster_size();
-        if cs == 0 {
-            return Err(FatError::NotFat32);
+        let found = self.locate_path(path)?;
+        if found.entry.is_dir() {
+            return Err(FatError::NotAFile);
         }
 
-        let data_len = self.disk.len() - data_start;
-        let data_clusters = (data_len / cs) as u32;
-        if data_clusters == 0 {
-            return Err(FatError::NotFat32);
+        let needed = div_ceil(len as usize, self.fs.cluster_size());
+        let chain = self.file_chain(found.entry.first_cluster)?;
+        if chain.len() >= needed {
+            return Ok(());
         }
-        let last_by_data = 2 + data_clusters - 1;
 
-        let fat_entries = (self.fat_bytes_len() / 4) as u32;
-        if fat_entries < 3 {
-            return Err(FatError::NotFat32);
+        let preferred = chain.last().map(|&c| c + 1);
+        let extra = self.alloc_contiguous(needed - chain.len(), preferred)?;
+        match chain.last() {
+            Some(&last) => self.write_fat_entry_all(last, extra[0])?,
+            None => self.set_entry_first_cluster(found.offset, extra[0])?,
         }
-        let last_by_fat = fat_entries - 1;
+        Ok(())
+    }
 
-        Ok(core::cmp::min(last_by_data, last_by_fat))
+    /// Crée un répertoire vide (équivalent de `mkdir`).
+    ///
+    /// - `path` doit être absolu, le parent doit exister
+    /// - on alloue un cluster, mis à zéro, qui reçoit les entrées `.` et `..`
+    /// - `..` vaut 0 quand le parent est la racine (convention FAT)
+    /// - retourne `AlreadyExists` si une entrée du même nom existe déjà
+    pub fn create_dir(&mut self, path: &str) -> Result<(), FatError> {
+        let result = self.make_dir(path);
+        self.finish(result)
     }
 
-    fn cluster_to_offset(&self, cluster: u32) -> Result<usize, FatError> {
-        if cluster < 2 {
-            return Err(FatError::InvalidCluster);
+    /// Corps de `create_dir`.
+    fn make_dir(&mut self, path: &str) -> Result<(), FatError> {
+        if !path.starts_with('/') || path == "/" {
+            return Err(FatError::Other);
         }
-        let index = (cluster - 2) as usize;
-        let offset = self.data_start_byte() + index * self.cluster_size();
-        if offset >= self.disk.len() {
-            return Err(FatError::OutOfBounds);
+
+        let (parent_path, dir_name) = split_parent(path)?;
+        if !is_valid_long_name(dir_name) {
+            return Err(FatError::InvalidName);
         }
-        Ok(offset)
+
+        let parent_cluster = self.fs.resolve_dir_cluster(parent_path)?;
+        if self.fs.find_in_dir(parent_cluster, dir_name)?.is_some() {
+            return Err(FatError::AlreadyExists);
+        }
+
+        let cluster = self.alloc_chain(1)?[0];
+
+        // Cluster à zéro : toutes les entrées sont “fin de répertoire”.
+        self.zero_cluster(cluster)?;
+
+        let dot_dot = if parent_cluster == self.fs.root_cluster {
+            0
+        } else {
+            parent_cluster
+        };
+        let off = self.fs.cluster_to_offset(cluster)?;
+        self.write_dir_entry_at_offset(off, b".          ", ATTR_DIRECTORY, cluster, 0)?;
+        self.write_dir_entry_at_offset(off + 32, b"..         ", ATTR_DIRECTORY, dot_dot, 0)?;
+
+        if let Err(e) =
+            self.create_dir_entry(parent_cluster, dir_name, ATTR_DIRECTORY, cluster, 0, None)
+        {
+            // Cluster rendu ; des slots LFN ou un agrandissement du parent ont
+            // pu être écrits, le volume reste donc sale (voir `finish`)
+            self.free_chain(cluster)?;
+            return Err(e);
+        }
+
+        Ok(())
     }
 
-    fn read_fat_entry(&self, cluster: u32) -> Result<u32, FatError> {
-        let fat_start = self.fat_start_byte();
-        let entry_offset = fat_start + cluster as usize * 4;
-        if entry_offset + 4 > self.disk.len() {
-            return Err(FatError::OutOfBounds);
+    /// Supprime un fichier.
+    ///
+    /// L’entrée (et ses slots LFN) est marquée `0xE5`, puis la chaîne de clusters
+    /// est libérée dans la FAT. Retourne `NotAFile` si le chemin vise un répertoire.
+    pub fn remove_file(&mut self, path: &str) -> Result<(), FatError> {
+        let result = self.delete_file(path);
+        self.finish(result)
+    }
+
+    /// Corps de `remove_file`.
+    fn delete_file(&mut self, path: &str) -> Result<(), FatError> {
+        let found = self.locate_path(path)?;
+        if found.entry.is_dir() {
+            return Err(FatError::NotAFile);
         }
-        let bytes = &self.disk[entry_offset..entry_offset + 4];
-        let val = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-        Ok(val & 0x0FFF_FFFF)
+        self.remove_located(&found)?;
+        Ok(())
     }
 
-    fn write_fat_entry_all(&mut self, cluster: u32, value: u32) -> Result<(), FatError> {
-        let val = value & 0x0FFF_FFFF;
-        let bytes = val.to_le_bytes();
+    /// Supprime un répertoire vide.
+    ///
+    /// Le répertoire ne doit contenir que `.` et `..`, sinon on retourne
+    /// `DirectoryNotEmpty`. La racine ne peut pas être supprimée.
+    pub fn remove_dir(&mut self, path: &str) -> Result<(), FatError> {
+        let result = self.delete_dir(path);
+        self.finish(result)
+    }
 
-        let fat0 = self.fat_start_byte();
-        let fat_len = self.fat_bytes_len();
+    /// Corps de `remove_dir`.
+    fn delete_dir(&mut self, path: &str) -> Result<(), FatError> {
+        let found = self.locate_path(path)?;
+        if !found.entry.is_dir() {
+            return Err(FatError::NotADirectory);
+        }
 
-        for i in 0..self.num_fats as usize {
-            let base = fat0 + i * fat_len;
-            let off = base + cluster as usize * 4;
-            if off + 4 > self.disk.len() {
-                return Err(FatError::OutOfBounds);
+        if found.entry.first_cluster >= 2 {
+            let children = self.fs.list_dir_cluster(found.entry.first_cluster)?;
+            if children.iter().any(|e| e.name != "." && e.name != "..") {
+                return Err(FatError::DirectoryNotEmpty);
             }
-            self.disk[off..off + 4].copy_from_slice(&bytes);
         }
 
+        self.remove_located(&found)?;
         Ok(())
     }
 
-    fn follow_chain(&self, start_cluster: u32, max_clusters: usize) -> Result<Vec<u32>, FatError> {
-        if start_cluster < 2 {
-            return Err(FatError::InvalidCluster);
+    /// Renomme ou déplace une entrée (fichier ou répertoire).
+    ///
+    /// - `from` et `to` sont des chemins absolus ; le parent de `to` doit exister
+    /// - les données ne bougent pas : la nouvelle entrée garde la même chaîne de
+    ///   clusters, la même taille et les mêmes attributs
+    /// - l’ancienne entrée et ses slots LFN sont marqués `0xE5`
+    /// - pour un répertoire déplacé, l’entrée `..` est mise à jour
+    /// - `AlreadyExists` si `to` existe déjà, `InvalidMove` si on tente de déplacer
+    ///   un répertoire dans sa propre sous-arborescence
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), FatError> {
+        let result = self.move_entry(from, to);
+        self.finish(result)
+    }
+
+    /// Corps de `rename`.
+    fn move_entry(&mut self, from: &str, to: &str) -> Result<(), FatError> {
+        let found = self.locate_path(from)?;
+
+        if !to.starts_with('/') || to == "/" {
+            return Err(FatError::Other);
+        }
+        let (to_parent, to_name) = split_parent(to)?;
+        if !is_valid_long_name(to_name) {
+            return Err(FatError::InvalidName);
         }
 
-        let mut result = Vec::new();
-        let mut current = start_cluster;
+        let ro = &self.fs;
+        let to_parent_cluster = ro.resolve_dir_cluster(to_parent)?;
 
-        for _ in 0..max_clusters {
-            result.push(current);
-            let next = self.read_fat_entry(current)?;
-            if next >= 0x0FFF_FFF8 {
-                break;
+        // Une cible existante n’est acceptée que si c’est la même entrée
+        // (changement de casse dans le même répertoire).
+        if let Some(target) = ro.find_in_dir(to_parent_cluster, to_name)? {
+            if target.offset != found.offset {
+                return Err(FatError::AlreadyExists);
             }
-            if next < 2 {
-                return Err(FatError::InvalidCluster);
+        }
+
+        let moved_dir = found.entry.is_dir() && found.entry.first_cluster >= 2;
+        if moved_dir && ro.is_same_or_descendant(to_parent_cluster, found.entry.first_cluster)? {
+            return Err(FatError::InvalidMove);
+        }
+
+        let mut old_raw = [0u8; 32];
+        self.fs.read_at(found.offset, &mut old_raw)?;
+
+        let new_off = self.create_dir_entry(
+            to_parent_cluster,
+            to_name,
+            old_raw[11],
+            found.entry.first_cluster,
+            found.entry.size,
+            Some(found.offset),
+        )?;
+
+        // On garde tout ce qui suit le nom (attributs, dates, cluster, taille).
+        self.write_at(new_off + 11, &old_raw[11..32])?;
+
+        self.mark_deleted(&found)?;
+
+        if moved_dir {
+            let dot_dot = if to_parent_cluster == self.fs.root_cluster {
+                0
+            } else {
+                to_parent_cluster
+            };
+            let children = self.fs.list_dir_located(found.entry.first_cluster)?;
+            if let Some(dd) = children.iter().find(|l| l.entry.name == "..") {
+                self.set_entry_first_cluster(dd.offset, dot_dot)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // ---------- internes (écriture) ----------
+
+    /// Écrit des octets à un offset quelconque du support.
+    ///
+    /// La première écriture d’une opération marque d’abord le volume sale ;
+    /// `commit` le remet propre quand l’opération réussit.
+    fn write_at(&mut self, offset: u64, bytes: &[u8]) -> Result<(), FatError> {
+        if self.dirty == DirtyState::Clean {
+            self.set_clean_flag(false)?;
+            self.dirty = DirtyState::Marked;
+        }
+        write_bytes_within(&mut self.fs.dev, self.fs.dev_len, offset, bytes)
+    }
+
+    /// Heure courante donnée par l’horloge (1980-01-01 00:00:00 sans horloge).
+    fn now(&self) -> FatTimestamp {
+        self.clock
+            .as_ref()
+            .map(|c| c.now())
+            .unwrap_or(FatTimestamp::EPOCH)
+    }
+
+    /// Met à jour une entrée existante après réécriture de son contenu.
+    ///
+    /// Le nom, les attributs et la date de création sont conservés ; on change
+    /// la chaîne, la taille, les dates de modification/accès et on pose le bit archive.
+    fn update_entry_after_write(
+        &mut self,
+        offset: u64,
+        first_cluster: u32,
+        size: u32,
+    ) -> Result<(), FatError> {
+        let (date, time, _) = self.now().to_raw();
+
+        let mut e = [0u8; 32];
+        self.fs.read_at(offset, &mut e)?;
+
+        e[11] |= ATTR_ARCHIVE;
+        e[18..20].copy_from_slice(&date.to_le_bytes());
+        e[20..22].copy_from_slice(&((first_cluster >> 16) as u16).to_le_bytes());
+        e[22..24].copy_from_slice(&time.to_le_bytes());
+        e[24..26].copy_from_slice(&date.to_le_bytes());
+        e[26..28].copy_from_slice(&((first_cluster & 0xFFFF) as u16).to_le_bytes());
+        e[28..32].copy_from_slice(&size.to_le_bytes());
+
+        self.write_at(offset, &e)
+    }
+
+    /// Met à jour le premier cluster (hi + lo) d’une entrée courte.
+    fn set_entry_first_cluster(&mut self, offset: u64, cluster: u32) -> Result<(), FatError> {
+        let hi = ((cluster >> 16) as u16).to_le_bytes();
+        let lo = ((cluster & 0xFFFF) as u16).to_le_bytes();
+        self.write_at(offset + 20, &hi)?;
+        self.write_at(offset + 26, &lo)
+    }
+
+    /// Retrouve l’entrée (avec sa position) visée par un chemin absolu.
+    ///
+    /// Les segments `.` et `..` ne sont pas acceptés comme dernier segment :
+    /// on ne manipule jamais ces entrées directement.
+    fn locate_path(&self, path: &str) -> Result<LocatedEntry, FatError> {
+        if !path.starts_with('/') || path == "/" {
+            return Err(FatError::Other);
+        }
+
+        let (parent_path, name) = split_parent(path)?;
+        if name == "." || name == ".." {
+            return Err(FatError::InvalidName);
+        }
+
+        let parent_cluster = self.fs.resolve_dir_cluster(parent_path)?;
+        self.fs
+            .find_in_dir(parent_cluster, name)?
+            .ok_or(FatError::PathNotFound)
+    }
+
+    /// Marque l’entrée et ses slots LFN comme supprimés (`0xE5`).
+    fn mark_deleted(&mut self, found: &LocatedEntry) -> Result<(), FatError> {
+        for &off in found
+            .lfn_offsets
+            .iter()
+            .chain(core::iter::once(&found.offset))
+        {
+            self.write_at(off, &[0xE5])?;
+        }
+        Ok(())
+    }
+
+    /// Marque l’entrée et ses slots LFN comme supprimés, puis libère sa chaîne.
+    fn remove_located(&mut self, found: &LocatedEntry) -> Result<(), FatError> {
+        self.mark_deleted(found)?;
+
+        if found.entry.first_cluster >= 2 {
+            self.free_chain(found.entry.first_cluster)?;
+        }
+
+        Ok(())
+    }
+
+    /// Écrit l’entrée FAT d’un cluster dans toutes les copies de la FAT.
+    ///
+    /// `value` est sur l’échelle FAT32 (ex: `FAT32_EOC`) ; on garde les bits
+    /// utiles du type de FAT.
+    fn write_fat_entry_all(&mut self, cluster: u32, value: u32) -> Result<(), FatError> {
+        let fat_type = self.fs.fat_type;
+        let val = value & fat_type.entry_mask();
+
+        let entry = self.fs.fat_type.entry_offset(cluster);
+
+        for copy in self.fs.written_fats() {
+            let off = self.fs.fat_copy_start(copy) + entry;
+            match fat_type {
+                FatType::Fat12 => {
+                    // Deux entrées se partagent un octet : read-modify-write
+                    let mut bytes = [0u8; 2];
+                    self.fs.read_at(off, &mut bytes)?;
+                    let old = u16::from_le_bytes(bytes);
+                    let v = val as u16;
+                    let pair = if cluster & 1 == 1 {
+                        (old & 0x000F) | (v << 4)
+                    } else {
+                        (old & 0xF000) | v
+                    };
+                    self.write_at(off, &pair.to_le_bytes())?;
+                }
+                FatType::Fat16 => self.write_at(off, &(val as u16).to_le_bytes())?,
+                FatType::Fat32 => self.write_at(off, &val.to_le_bytes())?,
             }
-            current = next;
         }
 
-        Ok(result)
+        Ok(())
+    }
+
+    /// Alloue un cluster et le chaîne après `last` (fin de chaîne actuelle).
+    fn extend_chain(&mut self, last: u32) -> Result<u32, FatError> {
+        let cluster = self.alloc_chain(1)?[0];
+        self.write_fat_entry_all(last, cluster)?;
+        Ok(cluster)
     }
 
     fn free_chain(&mut self, start_cluster: u32) -> Result<(), FatError> {
         if start_cluster < 2 {
             return Ok(());
         }
-        let chain = self.follow_chain(start_cluster, 4096)?;
-        for cl in chain {
+        let chain = self.fs.follow_chain(start_cluster)?;
+        for &cl in &chain {
             self.write_fat_entry_all(cl, 0)?;
         }
-        Ok(())
+        self.update_fs_info(chain.len() as i64, None)
     }
 
     fn alloc_chain(&mut self, needed: usize) -> Result<Vec<u32>, FatError> {
@@ -582,12 +1509,14 @@ impl<'a> Fat32Mut<'a> {
             return Ok(Vec::new());
         }
 
-        let max_cl = self.max_cluster_number()?;
+        let max_cl = self.fs.max_cluster_number()?;
         let mut found = Vec::with_capacity(needed);
 
-        // Scan simple : cluster libre = entrée FAT == 0
-        for cl in 2..=max_cl {
-            if self.read_fat_entry(cl)? == 0 {
+        // On commence au “next free” de FSInfo s’il est connu, puis on reboucle
+        // sur le début de la FAT. Cluster libre = entrée FAT == 0.
+        let hint = self.fs.fs_info().and_then(|i| i.next_free).unwrap_or(2);
+        for cl in (hint..=max_cl).chain(2..hint) {
+            if self.fs.read_fat_entry(cl)? == 0 {
                 found.push(cl);
                 if found.len() == needed {
                     break;
@@ -599,33 +1528,147 @@ impl<'a> Fat32Mut<'a> {
             return Err(FatError::NoSpaceLeft);
         }
 
+        self.link_allocated(&found, max_cl)?;
+        Ok(found)
+    }
+
+    /// Alloue `count` clusters consécutifs (une seule zone libre).
+    ///
+    /// On essaie d’abord la zone qui commence à `preferred` (ex: juste après
+    /// la fin d’une chaîne), puis la première zone assez grande de la FAT.
+    fn alloc_contiguous(
+        &mut self,
+        count: usize,
+        preferred: Option<u32>,
+    ) -> Result<Vec<u32>, FatError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let max_cl = self.fs.max_cluster_number()?;
+        let fits = |fs: &Fat32<D>, start: u32| -> Result<bool, FatError> {
+            let end = start as u64 + count as u64 - 1;
+            if start < 2 || end > max_cl as u64 {
+                return Ok(false);
+            }
+            for cl in start..=end as u32 {
+                if fs.read_fat_entry(cl)? != 0 {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        };
+
+        let mut start = match preferred {
+            Some(p) if fits(&self.fs, p)? => Some(p),
+            _ => None,
+        };
+
+        if start.is_none() {
+            let mut run = 0usize;
+            for cl in 2..=max_cl {
+                if self.fs.read_fat_entry(cl)? == 0 {
+                    run += 1;
+                    if run == count {
+                        start = Some(cl + 1 - count as u32);
+                        break;
+                    }
+                } else {
+                    run = 0;
+                }
+            }
+        }
+
+        let start = start.ok_or(FatError::NoSpaceLeft)?;
+        let found: Vec<u32> = (start..start + count as u32).collect();
+        self.link_allocated(&found, max_cl)?;
+        Ok(found)
+    }
+
+    /// Chaîne des clusters fraîchement trouvés libres (dernier -> EOC)
+    /// et reporte l’allocation dans FSInfo.
+    fn link_allocated(&mut self, found: &[u32], max_cl: u32) -> Result<(), FatError> {
         // Chaînage : cl[i] -> cl[i+1], dernier -> EOC
         for i in 0..found.len() {
-            let v = if i + 1 < found.len() { found[i + 1] } else { FAT32_EOC };
+            let v = if i + 1 < found.len() {
+                found[i + 1]
+            } else {
+                FAT32_EOC
+            };
             self.write_fat_entry_all(found[i], v)?;
         }
 
-        Ok(found)
+        let last = found[found.len() - 1];
+        let next = if last >= max_cl { 2 } else { last + 1 };
+        self.update_fs_info(-(found.len() as i64), Some(next))
+    }
+
+    /// Chaîne de clusters d’un fichier (vide si le fichier n’a pas de cluster).
+    fn file_chain(&self, first_cluster: u32) -> Result<Vec<u32>, FatError> {
+        if first_cluster < 2 {
+            return Ok(Vec::new());
+        }
+        self.fs.follow_chain(first_cluster)
+    }
+
+    /// Met à jour le secteur FSInfo après une allocation ou une libération.
+    ///
+    /// - `delta` : variation du nombre de clusters libres (négatif à l’allocation)
+    /// - `next_free` : nouvel indice de recherche, si on veut le déplacer
+    ///
+    /// Si le compteur de FSInfo était inconnu, on le recalcule en scannant la FAT,
+    /// pour que les OS n’aient pas à le faire au prochain montage.
+    /// Sans FSInfo valide, on ne fait rien.
+    ///
+    /// La copie de secours de FSInfo reçoit les mêmes valeurs : si
+    /// `open_with_backup` doit un jour s’y rabattre, ses compteurs sont justes.
+    fn update_fs_info(&mut self, delta: i64, next_free: Option<u32>) -> Result<(), FatError> {
+        let info = match self.fs.fs_info() {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+
+        let free = match info.free_count {
+            Some(n) => (n as i64 + delta).max(0) as u32,
+            None => self.fs.free_cluster_count()?,
+        };
+        let next_free = next_free.or(info.next_free);
+
+        let mut sectors = vec![self.fs.fs_info_sector];
+        let backup = self.fs.fs_info_backup;
+        if backup != self.fs.fs_info_sector && self.fs.read_fs_info(backup).is_some() {
+            sectors.push(backup);
+        }
+
+        let bps = self.fs.bytes_per_sector() as u64;
+        for sector in sectors {
+            let off = sector as u64 * bps;
+            self.write_at(off + 488, &free.to_le_bytes())?;
+            if let Some(next) = next_free {
+                self.write_at(off + 492, &next.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
     }
 
     fn write_chain_data(&mut self, chain: &[u32], content: &[u8]) -> Result<(), FatError> {
-        let cs = self.cluster_size();
+        let cs = self.fs.cluster_size();
         let mut pos = 0usize;
 
         for &cl in chain {
-            let off = self.cluster_to_offset(cl)?;
-            if off + cs > self.disk.len() {
-                return Err(FatError::OutOfBounds);
-            }
+            let off = self.fs.cluster_to_offset(cl)?;
 
             let end = core::cmp::min(pos + cs, content.len());
             let chunk = &content[pos..end];
 
-            self.disk[off..off + chunk.len()].copy_from_slice(chunk);
-
-            // Nettoyage du reste du cluster (c’est plus propre pour les tests et pour “cat”)
-            for b in &mut self.disk[off + chunk.len()..off + cs] {
-                *b = 0;
+            if chunk.len() == cs {
+                self.write_at(off, chunk)?;
+            } else {
+                // Nettoyage du reste du cluster (c’est plus propre pour les tests et pour “cat”)
+                let mut buf = vec![0u8; cs];
+                buf[..chunk.len()].copy_from_slice(chunk);
+                self.write_at(off, &buf)?;
             }
 
             pos = end;
@@ -637,120 +1680,189 @@ impl<'a> Fat32Mut<'a> {
         Ok(())
     }
 
-    fn find_dir_entry_offset_by_short_name(
-        &self,
+    /// Remplit un cluster de zéros.
+    fn zero_cluster(&mut self, cluster: u32) -> Result<(), FatError> {
+        let off = self.fs.cluster_to_offset(cluster)?;
+        let zeros = vec![0u8; self.fs.cluster_size()];
+        self.write_at(off, &zeros)
+    }
+
+    /// Crée une entrée (slots LFN éventuels + entrée courte) dans un répertoire.
+    ///
+    /// Si `name` est déjà un 8.3 strict en majuscules, on n’écrit que l’entrée courte.
+    /// Sinon on génère un alias `~N` qui n’existe pas encore dans le répertoire,
+    /// et on place les slots LFN juste avant l’entrée courte.
+    ///
+    /// `replacing` est l’offset de l’entrée que la nouvelle va remplacer (un
+    /// renommage) : si elle est dans le même répertoire, son nom court reste
+    /// disponible (`hello.txt` garde l’alias `HELLO.TXT` de `HELLO.TXT`).
+    ///
+    /// Retourne l’offset de l’entrée courte sur le support.
+    fn create_dir_entry(
+        &mut self,
         dir_cluster: u32,
-        name_raw: &[u8; 8],
-        ext_raw: &[u8; 3],
-    ) -> Result<(Option<usize>, Option<DirEntry>), FatError> {
-        let cs = self.cluster_size();
-        let chain = self.follow_chain(dir_cluster, 4096)?;
+        name: &str,
+        attr: u8,
+        first_cluster: u32,
+        size: u32,
+        replacing: Option<u64>,
+    ) -> Result<u64, FatError> {
+        let existing = self.fs.list_dir_located(dir_cluster)?;
+        let taken = |raw: &[u8; 11]| {
+            existing
+                .iter()
+                .any(|l| &l.short_raw == raw && Some(l.offset) != replacing)
+        };
 
-        for &cl in &chain {
-            let off = self.cluster_to_offset(cl)?;
-            if off + cs > self.disk.len() {
-                return Err(FatError::OutOfBounds);
-            }
+        let (basis, lossy) = short_name_basis(name);
+        let short_raw = if !lossy && !taken(&basis) {
+            basis
+        } else {
+            (1..=999_999)
+                .map(|n| with_numeric_tail(&basis, n))
+                .find(|raw| !taken(raw))
+                .ok_or(FatError::NoSpaceLeft)?
+        };
 
-            let data = &self.disk[off..off + cs];
-            for (i, chunk) in data.chunks(32).enumerate() {
-                if chunk.len() < 32 {
-                    break;
-                }
-                if chunk[0] == 0x00 {
-                    // fin de répertoire
-                    return Ok((None, None));
-                }
-                if chunk[0] == 0xE5 {
-                    continue;
-                }
+        let lfn_slots = if lossy || short_name_to_string(&short_raw) != name {
+            encode_lfn_slots(name, short_name_checksum(&short_raw))
+        } else {
+            Vec::new()
+        };
 
-                // match strict sur les octets 8.3
-                if &chunk[0..8] == &name_raw[..] && &chunk[8..11] == &ext_raw[..] {
-                    let abs_off = off + i * 32;
-                    let parsed = DirEntry::parse(chunk);
-                    return Ok((Some(abs_off), parsed));
-                }
-            }
+        let (offsets, end_marker) = self.find_free_dir_slots(dir_cluster, lfn_slots.len() + 1)?;
+
+        for (slot, &off) in lfn_slots.iter().zip(&offsets) {
+            self.write_at(off, slot)?;
+        }
+        let short_off = offsets[offsets.len() - 1];
+        self.write_dir_entry_at_offset(short_off, &short_raw, attr, first_cluster, size)?;
+
+        // Si on a consommé la fin de répertoire (0x00), on remet un 0x00 juste après.
+        // Ça garde un répertoire “propre”.
+        if let Some(next) = end_marker {
+            self.write_at(next, &[0x00])?;
         }
 
-        Ok((None, None))
+        Ok(short_off)
     }
 
-    /// Trouve un slot libre dans un répertoire.
+    /// Trouve `count` slots libres consécutifs dans un répertoire.
+    ///
+    /// Un slot est libre s’il est supprimé (`0xE5`) ou après la fin de répertoire (`0x00`).
+    /// Les slots consécutifs peuvent être à cheval sur deux clusters de la chaîne.
+    /// Si le répertoire est plein, on l’agrandit avec de nouveaux clusters mis à zéro.
     ///
     /// Retourne:
-    /// - l’offset dans `disk`
-    /// - `was_end_marker`: vrai si c’était un `0x00` (fin de répertoire)
-    /// - `entry_end_in_disk`: limite du cluster dans le buffer (pour écrire un 0x00 après)
-    fn find_free_dir_entry_slot(&self, dir_cluster: u32) -> Result<(usize, bool, usize), FatError> {
-        let cs = self.cluster_size();
-        let chain = self.follow_chain(dir_cluster, 4096)?;
-
-        for &cl in &chain {
-            let off = self.cluster_to_offset(cl)?;
-            let cluster_end = off + cs;
-
-            if cluster_end > self.disk.len() {
-                return Err(FatError::OutOfBounds);
+    /// - les offsets des slots sur le support
+    /// - l’offset où réécrire un `0x00` si on a consommé la fin de répertoire
+    ///   (`None` si on n’a utilisé que des slots supprimés, ou si le répertoire est plein)
+    fn find_free_dir_slots(
+        &mut self,
+        dir_cluster: u32,
+        count: usize,
+    ) -> Result<FreeSlots, FatError> {
+        loop {
+            if let Some(found) = self.search_free_dir_slots(dir_cluster, count)? {
+                return Ok(found);
             }
+            self.extend_dir(dir_cluster)?;
+        }
+    }
 
-            let data = &self.disk[off..cluster_end];
-            for (i, chunk) in data.chunks(32).enumerate() {
-                if chunk.len() < 32 {
-                    break;
-                }
-
-                if chunk[0] == 0x00 {
-                    return Ok((off + i * 32, true, cluster_end));
-                }
-                if chunk[0] == 0xE5 {
-                    return Ok((off + i * 32, false, cluster_end));
+    /// Cherche `count` slots libres consécutifs sans modifier le répertoire.
+    ///
+    /// Retourne `None` si la chaîne actuelle du répertoire n’a pas assez de place.
+    fn search_free_dir_slots(
+        &self,
+        dir_cluster: u32,
+        count: usize,
+    ) -> Result<Option<FreeSlots>, FatError> {
+        let slots = self.fs.dir_slots(dir_cluster)?;
+        let offsets = |range: &[DirSlot]| range.iter().map(|s| s.0).collect::<Vec<_>>();
+        let end = slots
+            .iter()
+            .position(|s| s.1[0] == 0x00)
+            .unwrap_or(slots.len());
+
+        // Slots supprimés avant la fin de répertoire
+        let mut run = 0usize;
+        for i in 0..end {
+            if slots[i].1[0] == 0xE5 {
+                run += 1;
+                if run == count {
+                    return Ok(Some((offsets(&slots[i + 1 - count..=i]), None)));
                 }
+            } else {
+                run = 0;
             }
         }
 
-        // Version simple: on n’alloue pas de nouveau cluster de répertoire.
-        Err(FatError::NoSpaceLeft)
+        // Tout ce qui suit le 0x00 est libre : on peut prolonger la dernière série.
+        let start = end - run;
+        if start + count <= slots.len() && end < slots.len() {
+            let marker = slots.get(start + count).map(|s| s.0);
+            return Ok(Some((offsets(&slots[start..start + count]), marker)));
+        }
+
+        Ok(None)
+    }
+
+    /// Agrandit un répertoire d’un cluster mis à zéro, chaîné en fin de chaîne.
+    ///
+    /// Un répertoire FAT est limité à 65536 entrées : au-delà on retourne `NoSpaceLeft`.
+    fn extend_dir(&mut self, dir_cluster: u32) -> Result<(), FatError> {
+        if self.fs.is_fixed_root(dir_cluster) {
+            // La racine FAT12/16 a une taille fixe
+            return Err(FatError::NoSpaceLeft);
+        }
+
+        let chain = self.fs.follow_chain(dir_cluster)?;
+        if (chain.len() + 1) * self.fs.cluster_size() > MAX_DIR_ENTRIES * 32 {
+            return Err(FatError::NoSpaceLeft);
+        }
+
+        let last = chain[chain.len() - 1];
+        let new_cluster = self.extend_chain(last)?;
+        self.zero_cluster(new_cluster)
     }
 
     fn write_dir_entry_at_offset(
         &mut self,
-        offset: usize,
-        name_raw: &[u8; 8],
-        ext_raw: &[u8; 3],
+        offset: u64,
+        short_raw: &[u8; 11],
+        attr: u8,
         first_cluster: u32,
         size: u32,
     ) -> Result<(), FatError> {
-        if offset + 32 > self.disk.len() {
-            return Err(FatError::OutOfBounds);
-        }
-
         let hi = ((first_cluster >> 16) as u16).to_le_bytes();
         let lo = ((first_cluster & 0xFFFF) as u16).to_le_bytes();
         let size_bytes = size.to_le_bytes();
+        let (date, time, hundredths) = self.now().to_raw();
 
-        let e = &mut self.disk[offset..offset + 32];
+        let mut e = [0u8; 32];
 
         // Name + ext
-        e[0..8].copy_from_slice(name_raw);
-        e[8..11].copy_from_slice(ext_raw);
+        e[0..11].copy_from_slice(short_raw);
 
-        // Attributs : archive (fichier)
-        e[11] = 0x20;
+        // Attributs : archive (fichier) ou répertoire
+        e[11] = attr;
 
-        // Champs “date/heure” et divers : on met à zéro (écriture simple)
-        for b in &mut e[12..20] {
-            *b = 0;
-        }
+        // Réservé (NT) : casse du nom, on ne l’utilise pas
+        e[12] = 0;
+
+        // Création, dernier accès et modification : tous à “maintenant”
+        e[13] = hundredths;
+        e[14..16].copy_from_slice(&time.to_le_bytes());
+        e[16..18].copy_from_slice(&date.to_le_bytes());
+        e[18..20].copy_from_slice(&date.to_le_bytes());
 
         // First cluster high
         e[20] = hi[0];
         e[21] = hi[1];
 
-        for b in &mut e[22..26] {
-            *b = 0;
-        }
+        e[22..24].copy_from_slice(&time.to_le_bytes());
+        e[24..26].copy_from_slice(&date.to_le_bytes());
 
         // First cluster low
         e[26] = lo[0];
@@ -759,7 +1871,7 @@ impl<'a> Fat32Mut<'a> {
         // Size
         e[28..32].copy_from_slice(&size_bytes);
 
-        Ok(())
+        self.write_at(offset, &e)
     }
 }
 
@@ -775,33 +1887,159 @@ struct BpbParams {
     sectors_per_cluster: u8,
     reserved_sectors: u16,
     num_fats: u8,
+    active_fat: Option<u8>,
     sectors_per_fat: u32,
+    root_entry_count: u16,
+    total_sectors: u32,
     root_cluster: u32,
+    fs_info_sector: u16,
+    backup_boot_sector: u16,
+    fat_type: FatType,
 }
 
-/// Parse le BPB du secteur 0 et extrait les paramètres utiles.
+/// Parse le BPB du secteur de boot situé à `offset` (0, ou la copie de
+/// secours) et extrait les paramètres utiles.
 ///
-/// Effectue des vérifications minimales pour éviter un état incohérent.
-fn parse_bpb(disk: &[u8]) -> Result<BpbParams, FatError> {
-    if disk.len() < 512 {
+/// Le type de FAT se déduit du nombre de clusters de données, comme dans la
+/// spécification (`FatType::from_cluster_count`), et la forme du BPB doit
+/// lui correspondre : un BPB au format FAT32 (taille de FAT 16 bits et racine
+/// fixe à 0) avec moins de 65 525 clusters serait lu en FAT12/16 par d’autres
+/// pilotes, il est donc refusé.
+///
+/// Chaque champ est validé avant d’être utilisé, pour qu’une image abîmée ou
+/// malveillante soit rejetée avec une erreur précise :
+/// - signature `0x55AA` en fin de secteur (`MissingBootSignature`)
+/// - taille de secteur : puissance de 2 entre 512 et 4096 (`InvalidBytesPerSector`)
+/// - secteurs par cluster : puissance de 2 entre 1 et 128 (`InvalidSectorsPerCluster`)
+/// - au moins 1 secteur réservé et 1 FAT (`InvalidReservedSectors`, `InvalidFatCount`)
+/// - nombre total de secteurs non nul et contenu dans le support
+///   (`InvalidTotalSectors`, `VolumeExceedsDevice`)
+/// - racine fixe présente en FAT12/16 et absente en FAT32 (`InvalidRootEntryCount`)
+/// - nombre de clusters compatible avec le type de FAT (`ClusterCountMismatch`)
+/// - FAT assez grande pour tous les clusters (`InvalidFatSize`)
+/// - cluster racine FAT32 dans la zone data (`InvalidRootCluster`)
+/// - FAT active existante si le miroir est désactivé (`InvalidActiveFat`)
+fn parse_bpb<D: BlockDevice + ?Sized>(dev: &D, offset: u64) -> Result<BpbParams, FatError> {
+    if device_len(dev) < offset + 512 {
         return Err(FatError::BufferTooSmall);
     }
 
-    let b = &disk[0..512];
+    let mut b = [0u8; 512];
+    read_bytes(dev, offset, &mut b)?;
+
+    if b[510] != 0x55 || b[511] != 0xAA {
+        return Err(FatError::MissingBootSignature);
+    }
 
     let bytes_per_sector = u16::from_le_bytes([b[11], b[12]]);
     let sectors_per_cluster = b[13];
     let reserved_sectors = u16::from_le_bytes([b[14], b[15]]);
     let num_fats = b[16];
-    let sectors_per_fat = u32::from_le_bytes([b[36], b[37], b[38], b[39]]);
-    let root_cluster = u32::from_le_bytes([b[44], b[45], b[46], b[47]]);
+    let root_entry_count = u16::from_le_bytes([b[17], b[18]]);
+    let total_sectors_16 = u16::from_le_bytes([b[19], b[20]]);
+    let sectors_per_fat_16 = u16::from_le_bytes([b[22], b[23]]);
+    let total_sectors_32 = u32::from_le_bytes([b[32], b[33], b[34], b[35]]);
 
-    // Checks minimalistes pour éviter un état incohérent
-    if bytes_per_sector == 0 || sectors_per_cluster == 0 || num_fats == 0 {
-        return Err(FatError::NotFat32);
+    if !bytes_per_sector.is_power_of_two() || !(512..=4096).contains(&bytes_per_sector) {
+        return Err(FatError::InvalidBytesPerSector);
+    }
+    if !sectors_per_cluster.is_power_of_two() || sectors_per_cluster > 128 {
+        return Err(FatError::InvalidSectorsPerCluster);
     }
+    if reserved_sectors == 0 {
+        return Err(FatError::InvalidReservedSectors);
+    }
+    if num_fats == 0 {
+        return Err(FatError::InvalidFatCount);
+    }
+
+    // Les champs 16 bits priment ; les champs 32 bits servent s’ils valent 0
+    let sectors_per_fat = match sectors_per_fat_16 {
+        0 => u32::from_le_bytes([b[36], b[37], b[38], b[39]]),
+        n => n as u32,
+    };
+    let total_sectors = match total_sectors_16 {
+        0 => total_sectors_32,
+        n => n as u32,
+    };
     if sectors_per_fat == 0 {
-        return Err(FatError::NotFat32);
+        return Err(FatError::InvalidFatSize);
+    }
+    if total_sectors == 0 {
+        return Err(FatError::InvalidTotalSectors);
+    }
+
+    let bps = bytes_per_sector as u64;
+    if total_sectors as u64 * bps > device_len(dev) {
+        return Err(FatError::VolumeExceedsDevice);
+    }
+
+    // Nombre de clusters de données, calculé comme dans la spec
+    let root_dir_sectors = (root_entry_count as u64 * 32).div_ceil(bps);
+    let meta_sectors =
+        reserved_sectors as u64 + num_fats as u64 * sectors_per_fat as u64 + root_dir_sectors;
+    if meta_sectors >= total_sectors as u64 {
+        // Réservés + FAT + racine occupent tout le volume : pas de zone data
+        return Err(FatError::InvalidTotalSectors);
+    }
+    let cluster_count = (total_sectors as u64 - meta_sectors) / sectors_per_cluster as u64;
+
+    let fat32_layout = match (sectors_per_fat_16, root_entry_count) {
+        (0, 0) => true,
+        // Racine fixe sans taille de FAT 16 bits, ou FAT12/16 sans racine
+        (0, _) | (_, 0) => return Err(FatError::InvalidRootEntryCount),
+        _ => false,
+    };
+    let fat_type = FatType::from_cluster_count(cluster_count);
+    if fat32_layout != (fat_type == FatType::Fat32) {
+        // Trop de clusters pour des entrées de 12 ou 16 bits, ou trop peu
+        // pour un FAT32
+        return Err(FatError::ClusterCountMismatch);
+    }
+
+    // Au moins un cluster, et pas plus que ce que les entrées peuvent adresser
+    let max_clusters = fat_type.max_cluster() as u64 - 1;
+    if cluster_count == 0 || cluster_count > max_clusters {
+        return Err(FatError::ClusterCountMismatch);
+    }
+
+    // La FAT doit avoir une entrée par cluster, plus les deux réservées
+    let fat_bits = match fat_type {
+        FatType::Fat12 => 12,
+        FatType::Fat16 => 16,
+        FatType::Fat32 => 32,
+    };
+    if sectors_per_fat as u64 * bps * 8 / fat_bits < cluster_count + 2 {
+        return Err(FatError::InvalidFatSize);
+    }
+
+    // Cluster racine, FSInfo et copie de secours n’existent que dans le BPB
+    // étendu FAT32
+    let (root_cluster, fs_info_sector, backup_boot_sector) = match fat_type {
+        FatType::Fat32 => (
+            u32::from_le_bytes([b[44], b[45], b[46], b[47]]),
+            u16::from_le_bytes([b[48], b[49]]),
+            u16::from_le_bytes([b[50], b[51]]),
+        ),
+        _ => (0, 0, 0),
+    };
+    // Une copie de secours hors des secteurs réservés n’en est pas une
+    let backup_boot_sector = match backup_boot_sector {
+        n if n < reserved_sectors && n != 0 => n,
+        _ => 0,
+    };
+    if fat_type == FatType::Fat32 && !(2..cluster_count + 2).contains(&(root_cluster as u64)) {
+        return Err(FatError::InvalidRootCluster);
+    }
+
+    // ext_flags (FAT32) : bit 7 = miroir désactivé, bits 0-3 = FAT active
+    let ext_flags = u16::from_le_bytes([b[40], b[41]]);
+    let active_fat = match fat_type {
+        FatType::Fat32 if ext_flags & 0x80 != 0 => Some((ext_flags & 0x0F) as u8),
+        _ => None,
+    };
+    if active_fat.is_some_and(|n| n >= num_fats) {
+        return Err(FatError::InvalidActiveFat);
     }
 
     Ok(BpbParams {
@@ -809,16 +2047,81 @@ fn parse_bpb(disk: &[u8]) -> Result<BpbParams, FatError> {
         sectors_per_cluster,
         reserved_sectors,
         num_fats,
+        active_fat,
         sectors_per_fat,
+        root_entry_count,
+        total_sectors,
         root_cluster,
+        fs_info_sector,
+        backup_boot_sector,
+        fat_type,
     })
 }
 
+/// Recolle les entrées d’un répertoire à partir de ses slots bruts.
+///
+/// Les slots LFN qui précèdent une entrée courte sont accumulés ; si leur
+/// checksum correspond au nom court, `DirEntry::name` reçoit le nom long
+/// et les offsets de ces slots sont rattachés à l’entrée.
+fn parse_dir_slots(slots: Vec<DirSlot>) -> Vec<LocatedEntry> {
+    let mut entries = Vec::new();
+    let mut lfn = LfnAccumulator::default();
+    let mut lfn_offsets = Vec::new();
+
+    for (off, slot) in slots {
+        // 0x00 = fin de répertoire (à partir de là, tout est libre)
+        if slot[0] == 0x00 {
+            break;
+        }
+
+        if slot[0] == 0xE5 {
+            lfn.reset();
+            lfn_offsets.clear();
+            continue;
+        }
+
+        if is_lfn_slot(&slot) {
+            if slot[0] & 0x40 != 0 {
+                lfn_offsets.clear();
+            }
+            lfn.push_slot(&slot);
+            lfn_offsets.push(off);
+            continue;
+        }
+
+        let long_name = lfn.take_name(&slot[0..11]);
+        let pending = core::mem::take(&mut lfn_offsets);
+
+        if let Some(mut entry) = DirEntry::parse(&slot) {
+            let lfn_offsets = match long_name {
+                Some(long) => {
+                    entry.name = long;
+                    pending
+                }
+                None => Vec::new(),
+            };
+
+            let mut short_raw = [0u8; 11];
+            short_raw.copy_from_slice(&slot[0..11]);
+            entries.push(LocatedEntry {
+                entry,
+                short_raw,
+                offset: off,
+                lfn_offsets,
+            });
+        }
+    }
+
+    entries
+}
+
 /// Normalise un nom pour comparer facilement (on passe en majuscule).
+///
+/// Les noms longs pouvant contenir de l’Unicode, on utilise la casse Unicode.
 fn normalize_name(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for ch in s.chars() {
-        out.push(ch.to_ascii_uppercase());
+        out.extend(ch.to_uppercase());
     }
     out
 }
@@ -840,59 +2143,12 @@ fn split_parent(path: &str) -> Result<(&str, &str), FatError> {
     Ok((parent, name))
 }
 
-/// Encode un nom en format court 8.3.
-///
-/// Exemples :
-/// - `"HELLO.TXT"` -> name=`"HELLO   "`, ext=`"TXT"`
-/// - `"DIR"`       -> name=`"DIR     "`, ext=`"   "`
-///
-/// Limites volontaires :
-/// - ASCII uniquement
-/// - 1 point max (séparateur extension)
-/// - base <= 8, ext <= 3
-/// - pas de `.` dans la base ou l’extension
-fn encode_short_name_8_3(name: &str) -> Result<([u8; 8], [u8; 3]), FatError> {
-    let mut base = name;
-    let mut ext = "";
-
-    if let Some(dot) = name.rfind('.') {
-        base = &name[..dot];
-        ext = &name[dot + 1..];
-    }
-
-    if base.is_empty() || base.len() > 8 || ext.len() > 3 {
-        return Err(FatError::InvalidName);
-    }
-    if base.contains('.') || ext.contains('.') {
-        return Err(FatError::InvalidName);
-    }
-
-    let mut n = [b' '; 8];
-    let mut e = [b' '; 3];
-
-    for (i, ch) in base.bytes().enumerate() {
-        if !ch.is_ascii() || ch == b'/' {
-            return Err(FatError::InvalidName);
-        }
-        n[i] = ch.to_ascii_uppercase();
-    }
-
-    for (i, ch) in ext.bytes().enumerate() {
-        if !ch.is_ascii() || ch == b'/' {
-            return Err(FatError::InvalidName);
-        }
-        e[i] = ch.to_ascii_uppercase();
-    }
-
-    Ok((n, e))
-}
-
 /// Division entière avec arrondi vers le haut.
 fn div_ceil(a: usize, b: usize) -> usize {
     if b == 0 {
         0
     } else {
-        (a + b - 1) / b
+        a.div_ceil(b)
     }
 }
 
@@ -900,18 +2156,38 @@ fn div_ceil(a: usize, b: usize) -> usize {
 mod tests {
     use super::*;
 
-    /// Mini volume FAT32 en mémoire :
+    /// Taille d’une FAT des images FAT32 de test, en secteurs : 65 536
+    /// entrées, de quoi passer le minimum FAT32 de 65 525 clusters.
+    const FAT32_FAT_SECTORS: usize = 512;
+
+    /// Premier secteur de la zone data (cluster 2) de `build_test_image`.
+    const TEST_DATA_SECTOR: usize = 1 + FAT32_FAT_SECTORS;
+
+    /// Seconde FAT et zone data de `build_formatted_image`, en secteurs.
+    const FORMATTED_FAT2_SECTOR: usize = 8 + FAT32_FAT_SECTORS;
+    const FORMATTED_DATA_SECTOR: usize = 8 + 2 * FAT32_FAT_SECTORS;
+
+    /// Taille de `build_formatted_image` en secteurs : 65 534 clusters.
+    const FORMATTED_SECTORS: usize = FORMATTED_DATA_SECTOR + 65_534;
+
+    /// Partition étendue (premier EBR) et second EBR de
+    /// `build_partitioned_disk`.
+    const EXTENDED_LBA: usize = 8 + FORMATTED_SECTORS;
+    const SECOND_EBR_LBA: usize = EXTENDED_LBA + 1 + FORMATTED_SECTORS;
+
+    /// Mini volume FAT32 en mémoire, 1 secteur par cluster :
     /// - 1 secteur BPB
-    /// - 1 secteur FAT
-    /// - data cluster (sector) 2 = racine : HELLO.TXT + DIR
-    /// - data cluster (sector) 3 = contenu "HELLO"
-    /// - data cluster (sector) 4 = répertoire DIR (vide)
+    /// - 1 FAT de `FAT32_FAT_SECTORS` secteurs
+    /// - cluster 2 = racine : HELLO.TXT + DIR
+    /// - cluster 3 = contenu "HELLO"
+    /// - cluster 4 = répertoire DIR (vide : `.` et `..`)
     ///
     /// Les clusters 5.. sont libres, ce qui permet de tester l’écriture.
-    fn build_test_image() -> [u8; 5120] {
+    /// Le nombre total de secteurs est pair, pour `BigBlocks`.
+    fn build_test_image() -> Vec<u8> {
         const SECTOR_SIZE: usize = 512;
-        const NUM_SECTORS: usize = 10;
-        let mut disk = [0u8; SECTOR_SIZE * NUM_SECTORS];
+        const NUM_SECTORS: usize = TEST_DATA_SECTOR + 65_533;
+        let mut disk = vec![0u8; SECTOR_SIZE * NUM_SECTORS];
 
         // BPB
         {
@@ -931,17 +2207,21 @@ mod tests {
             // num_fats = 1
             b[16] = 0x01;
 
-            // sectors_per_fat = 1
-            b[36] = 0x01;
-            b[37] = 0x00;
-            b[38] = 0x00;
-            b[39] = 0x00;
+            // total_sectors
+            b[32..36].copy_from_slice(&(NUM_SECTORS as u32).to_le_bytes());
+
+            // sectors_per_fat
+            b[36..40].copy_from_slice(&(FAT32_FAT_SECTORS as u32).to_le_bytes());
 
             // root_cluster = 2
             b[44] = 0x02;
             b[45] = 0x00;
             b[46] = 0x00;
             b[47] = 0x00;
+
+            // signature du secteur de boot
+            b[510] = 0x55;
+            b[511] = 0xAA;
         }
 
         // FAT (secteur 1)
@@ -961,9 +2241,9 @@ mod tests {
             // clusters 5.. = 0 -> libres
         }
 
-        // root dir = cluster 2 -> secteur 2
+        // root dir = cluster 2
         {
-            let root_off = 2 * SECTOR_SIZE;
+            let root_off = test_cluster_offset(2);
             let dir = &mut disk[root_off..root_off + SECTOR_SIZE];
 
             // HELLO.TXT
@@ -992,21 +2272,255 @@ mod tests {
             dir[64] = 0x00;
         }
 
-        // cluster 3 data -> secteur 3
+        // cluster 3 data
         {
-            let off = 3 * SECTOR_SIZE;
+            let off = test_cluster_offset(3);
             disk[off..off + 5].copy_from_slice(b"HELLO");
         }
 
-        // cluster 4 data -> secteur 4 (DIR empty)
+        // cluster 4 data (DIR empty)
+        {
+            let off = test_cluster_offset(4);
+            let dir = &mut disk[off..off + SECTOR_SIZE];
+
+            // `.` -> cluster 4, `..` -> 0 (parent = racine)
+            dir[0..11].copy_from_slice(b".          ");
+            dir[11] = 0x10;
+            dir[26] = 0x04;
+            dir[32..43].copy_from_slice(b"..         ");
+            dir[43] = 0x10;
+
+            dir[64] = 0x00;
+        }
+
+        disk
+    }
+
+    /// Offset d’un cluster dans `build_test_image`.
+    fn test_cluster_offset(cluster: usize) -> usize {
+        (TEST_DATA_SECTOR + cluster - 2) * 512
+    }
+
+    /// Volume FAT32 plus proche d’un vrai `mkfs.vfat` :
+    /// - 8 secteurs réservés : BPB (0), FSInfo (1), copie du BPB (6)
+    /// - 2 FAT de `FAT32_FAT_SECTORS` secteurs (à partir des secteurs 8 et
+    ///   `FORMATTED_FAT2_SECTOR`)
+    /// - data à partir de `FORMATTED_DATA_SECTOR`, racine vide en cluster 2
+    ///
+    /// 1 secteur par cluster, `FORMATTED_SECTORS` secteurs en tout.
+    fn build_formatted_image() -> Vec<u8> {
+        const SECTOR_SIZE: usize = 512;
+        let total_sectors = FORMATTED_SECTORS;
+        let mut disk = vec![0u8; SECTOR_SIZE * total_sectors];
+        let clusters = (total_sectors - FORMATTED_DATA_SECTOR) as u32;
+
+        {
+            let b = &mut disk[0..SECTOR_SIZE];
+            b[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+            b[3..11].copy_from_slice(b"MSWIN4.1");
+            b[11..13].copy_from_slice(&512u16.to_le_bytes());
+            b[13] = 1; // sectors_per_cluster
+            b[14..16].copy_from_slice(&8u16.to_le_bytes()); // reserved
+            b[16] = 2; // num_fats
+            b[21] = 0xF8; // media
+            b[32..36].copy_from_slice(&(total_sectors as u32).to_le_bytes());
+            b[36..40].copy_from_slice(&(FAT32_FAT_SECTORS as u32).to_le_bytes()); // sectors_per_fat
+            b[44..48].copy_from_slice(&2u32.to_le_bytes()); // root_cluster
+            b[48..50].copy_from_slice(&1u16.to_le_bytes()); // FSInfo
+            b[50..52].copy_from_slice(&6u16.to_le_bytes()); // backup boot sector
+            b[66] = 0x29;
+            b[67..71].copy_from_slice(&0x1234_ABCDu32.to_le_bytes());
+            b[71..82].copy_from_slice(b"NO NAME    ");
+            b[82..90].copy_from_slice(b"FAT32   ");
+            b[510] = 0x55;
+            b[511] = 0xAA;
+        }
+
+        {
+            let f = &mut disk[SECTOR_SIZE..2 * SECTOR_SIZE];
+            f[0..4].copy_from_slice(&FSINFO_LEAD_SIG.to_le_bytes());
+            f[484..488].copy_from_slice(&FSINFO_STRUC_SIG.to_le_bytes());
+            f[488..492].copy_from_slice(&(clusters - 1).to_le_bytes()); // tout libre sauf la racine
+            f[492..496].copy_from_slice(&3u32.to_le_bytes());
+            f[508..512].copy_from_slice(&FSINFO_TRAIL_SIG.to_le_bytes());
+        }
+
+        let boot = disk[0..SECTOR_SIZE].to_vec();
+        disk[6 * SECTOR_SIZE..7 * SECTOR_SIZE].copy_from_slice(&boot);
+
+        for fat in [8, FORMATTED_FAT2_SECTOR] {
+            let off = fat * SECTOR_SIZE;
+            disk[off..off + 4].copy_from_slice(&0x0FFF_FFF8u32.to_le_bytes());
+            disk[off + 4..off + 8].copy_from_slice(&0x0FFF_FFFFu32.to_le_bytes());
+            disk[off + 8..off + 12].copy_from_slice(&FAT32_EOC.to_le_bytes());
+        }
+
+        disk
+    }
+
+    /// Volume FAT12 (disquette 1,44 Mo) ou FAT16 (4200 clusters) vide.
+    ///
+    /// 1 secteur réservé, 2 FAT, racine fixe de 224 ou 512 entrées,
+    /// 1 secteur par cluster.
+    fn build_legacy_image(fat_type: FatType) -> Vec<u8> {
+        const SECTOR_SIZE: usize = 512;
+        let (total_sectors, spf, root_entries, label): (u16, u16, u16, &[u8; 8]) = match fat_type {
+            FatType::Fat12 => (2880, 9, 224, b"FAT12   "),
+            _ => (4267, 17, 512, b"FAT16   "),
+        };
+        let mut disk = vec![0u8; SECTOR_SIZE * total_sectors as usize];
+
         {
-            let off = 4 * SECTOR_SIZE;
-            disk[off] = 0x00;
+            let b = &mut disk[0..SECTOR_SIZE];
+            b[0..3].copy_from_slice(&[0xEB, 0x3C, 0x90]);
+            b[3..11].copy_from_slice(b"MSWIN4.1");
+            b[11..13].copy_from_slice(&512u16.to_le_bytes());
+            b[13] = 1; // sectors_per_cluster
+            b[14..16].copy_from_slice(&1u16.to_le_bytes()); // reserved
+            b[16] = 2; // num_fats
+            b[17..19].copy_from_slice(&root_entries.to_le_bytes());
+            b[19..21].copy_from_slice(&total_sectors.to_le_bytes());
+            b[21] = 0xF0; // media
+            b[22..24].copy_from_slice(&spf.to_le_bytes()); // sectors_per_fat (16 bits)
+            b[38] = 0x29;
+            b[39..43].copy_from_slice(&0x1234_ABCDu32.to_le_bytes());
+            b[43..54].copy_from_slice(b"NO NAME    ");
+            b[54..62].copy_from_slice(label);
+            b[510] = 0x55;
+            b[511] = 0xAA;
+        }
+
+        // FAT[0] = média, FAT[1] = fin de chaîne
+        for fat in 0..2 {
+            let off = (1 + fat * spf as usize) * SECTOR_SIZE;
+            match fat_type {
+                FatType::Fat12 => disk[off..off + 3].copy_from_slice(&[0xF0, 0xFF, 0xFF]),
+                _ => disk[off..off + 4].copy_from_slice(&[0xF0, 0xFF, 0xFF, 0xFF]),
+            }
         }
 
         disk
     }
 
+    /// Disque complet avec MBR :
+    /// - n°1 : primaire FAT32 (LBA 8, `FORMATTED_SECTORS` secteurs)
+    /// - n°2 : étendue (`EXTENDED_LBA`), avec deux EBR chaînés
+    /// - n°5 : logique FAT32 (juste après le premier EBR)
+    /// - n°6 : logique FAT12 (juste après `SECOND_EBR_LBA`, 2880 secteurs)
+    fn build_partitioned_disk() -> Vec<u8> {
+        const SECTOR_SIZE: usize = 512;
+        const FAT32: u32 = FORMATTED_SECTORS as u32;
+        const TOTAL: usize = SECOND_EBR_LBA + 1 + 2880;
+        let mut disk = vec![0u8; SECTOR_SIZE * TOTAL];
+
+        let entry = |disk: &mut [u8], sector: usize, i: usize, e: (u8, u8, u32, u32)| {
+            let off = sector * SECTOR_SIZE + 446 + i * 16;
+            disk[off] = e.0;
+            disk[off + 4] = e.1;
+            disk[off + 8..off + 12].copy_from_slice(&e.2.to_le_bytes());
+            disk[off + 12..off + 16].copy_from_slice(&e.3.to_le_bytes());
+            disk[sector * SECTOR_SIZE + 510] = 0x55;
+            disk[sector * SECTOR_SIZE + 511] = 0xAA;
+        };
+
+        // MBR
+        let ext = EXTENDED_LBA as u32;
+        entry(&mut disk, 0, 0, (0x80, 0x0C, 8, FAT32));
+        entry(&mut disk, 0, 1, (0x00, 0x0F, ext, TOTAL as u32 - ext));
+        // EBR 1 : logique à +1, EBR suivant à +1+FAT32 depuis l’étendue
+        entry(&mut disk, EXTENDED_LBA, 0, (0x00, 0x0C, 1, FAT32));
+        entry(&mut disk, EXTENDED_LBA, 1, (0x00, 0x05, 1 + FAT32, 2881));
+        // EBR 2 : dernière logique
+        entry(&mut disk, SECOND_EBR_LBA, 0, (0x00, 0x01, 1, 2880));
+
+        let fat32 = build_formatted_image();
+        let fat12 = build_legacy_image(FatType::Fat12);
+        disk[8 * SECTOR_SIZE..EXTENDED_LBA * SECTOR_SIZE].copy_from_slice(&fat32);
+        disk[(EXTENDED_LBA + 1) * SECTOR_SIZE..SECOND_EBR_LBA * SECTOR_SIZE]
+            .copy_from_slice(&fat32);
+        disk[(SECOND_EBR_LBA + 1) * SECTOR_SIZE..].copy_from_slice(&fat12);
+
+        disk
+    }
+
+    /// Disque GPT avec MBR protecteur :
+    /// - entrée 1 : partition système EFI (FAT32, à partir du LBA 40)
+    /// - entrée 3 : données (FAT32, juste après), l’entrée 2 est vide
+    ///
+    /// En-tête principal au bloc 1 (tableau au bloc 2), secours au dernier
+    /// bloc (tableau dans les 32 blocs qui le précèdent).
+    fn build_gpt_disk() -> Vec<u8> {
+        const SECTOR_SIZE: usize = 512;
+        const FAT32: u64 = FORMATTED_SECTORS as u64;
+        const DATA_LBA: u64 = 40 + FAT32;
+        const LAST_USABLE: u64 = DATA_LBA + FAT32 - 1;
+        const TOTAL: u64 = LAST_USABLE + 1 + 32 + 1;
+        let mut disk = vec![0u8; SECTOR_SIZE * TOTAL as usize];
+
+        // MBR protecteur
+        disk[446 + 4] = 0xEE;
+        disk[446 + 8..446 + 12].copy_from_slice(&1u32.to_le_bytes());
+        disk[446 + 12..446 + 16].copy_from_slice(&(TOTAL as u32 - 1).to_le_bytes());
+        disk[510] = 0x55;
+        disk[511] = 0xAA;
+
+        // Tableau de 128 entrées de 128 octets (32 secteurs)
+        let mut array = vec![0u8; 128 * 128];
+        let mut entry = |i: usize, type_guid: Guid, first: u64, last: u64, name: &str| {
+            let e = &mut array[i * 128..(i + 1) * 128];
+            e[0..16].copy_from_slice(&type_guid.0);
+            e[16..32].copy_from_slice(&[i as u8 + 1; 16]);
+            e[32..40].copy_from_slice(&first.to_le_bytes());
+            e[40..48].copy_from_slice(&last.to_le_bytes());
+            for (j, u) in name.encode_utf16().enumerate() {
+                e[56 + j * 2..58 + j * 2].copy_from_slice(&u.to_le_bytes());
+            }
+        };
+        entry(
+            0,
+            Guid::EFI_SYSTEM,
+            40,
+            DATA_LBA - 1,
+            "EFI system partition",
+        );
+        entry(2, Guid::BASIC_DATA, DATA_LBA, LAST_USABLE, "Données");
+        let array_crc = gpt::crc32(&array);
+
+        let header = |my_lba: u64, alt_lba: u64, entries_lba: u64| {
+            let mut h = [0u8; 92];
+            h[0..8].copy_from_slice(b"EFI PART");
+            h[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+            h[12..16].copy_from_slice(&92u32.to_le_bytes());
+            h[24..32].copy_from_slice(&my_lba.to_le_bytes());
+            h[32..40].copy_from_slice(&alt_lba.to_le_bytes());
+            h[40..48].copy_from_slice(&34u64.to_le_bytes());
+            h[48..56].copy_from_slice(&LAST_USABLE.to_le_bytes());
+            h[56..72].copy_from_slice(&[0x42; 16]);
+            h[72..80].copy_from_slice(&entries_lba.to_le_bytes());
+            h[80..84].copy_from_slice(&128u32.to_le_bytes());
+            h[84..88].copy_from_slice(&128u32.to_le_bytes());
+            h[88..92].copy_from_slice(&array_crc.to_le_bytes());
+            let crc = gpt::crc32(&h);
+            h[16..20].copy_from_slice(&crc.to_le_bytes());
+            h
+        };
+
+        let backup_array = LAST_USABLE as usize + 1;
+        let backup_header = TOTAL as usize - 1;
+        disk[SECTOR_SIZE..SECTOR_SIZE + 92].copy_from_slice(&header(1, TOTAL - 1, 2));
+        disk[2 * SECTOR_SIZE..34 * SECTOR_SIZE].copy_from_slice(&array);
+        disk[backup_array * SECTOR_SIZE..backup_header * SECTOR_SIZE].copy_from_slice(&array);
+        disk[backup_header * SECTOR_SIZE..backup_header * SECTOR_SIZE + 92]
+            .copy_from_slice(&header(TOTAL - 1, 1, backup_array as u64));
+
+        let fat32 = build_formatted_image();
+        let data = DATA_LBA as usize;
+        disk[40 * SECTOR_SIZE..data * SECTOR_SIZE].copy_from_slice(&fat32);
+        disk[data * SECTOR_SIZE..backup_array * SECTOR_SIZE].copy_from_slice(&fat32);
+
+        disk
+    }
+
     fn fat_entry_raw(disk: &[u8], cluster: u32) -> u32 {
         // Dans notre image de test: reserved=1, bytes_per_sector=512 donc FAT start = 512.
         let fat_start = 512usize;
@@ -1016,6 +2530,95 @@ mod tests {
         v & 0x0FFF_FFFF
     }
 
+    /// Construit à la main les slots LFN d’un nom (ordre disque : dernier morceau en premier).
+    fn make_lfn_slots(name: &str, checksum: u8) -> Vec<[u8; 32]> {
+        let mut units: Vec<u16> = name.encode_utf16().collect();
+        if !units.len().is_multiple_of(13) {
+            units.push(0x0000);
+        }
+        while !units.len().is_multiple_of(13) {
+            units.push(0xFFFF);
+        }
+
+        let count = units.len() / 13;
+        let offsets = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+        let mut slots = Vec::new();
+        for seq in (1..=count).rev() {
+            let mut slot = [0u8; 32];
+            slot[0] = seq as u8 | if seq == count { 0x40 } else { 0 };
+            slot[11] = 0x0F;
+            slot[13] = checksum;
+            for (i, &off) in offsets.iter().enumerate() {
+                let u = units[(seq - 1) * 13 + i].to_le_bytes();
+                slot[off] = u[0];
+                slot[off + 1] = u[1];
+            }
+            slots.push(slot);
+        }
+        slots
+    }
+
+    /// Ajoute dans la racine de l’image de test une entrée `LONGFI~1.TXT`
+    /// précédée des slots LFN de `long` (checksum éventuellement faussé).
+    fn add_long_named_file(disk: &mut [u8], long: &str, corrupt_checksum: bool) {
+        let short: &[u8; 11] = b"LONGFI~1TXT";
+        let mut checksum = lfn::short_name_checksum(short);
+        if corrupt_checksum {
+            checksum = checksum.wrapping_add(1);
+        }
+
+        let root_off = test_cluster_offset(2);
+        let mut off = root_off + 64; // après HELLO.TXT et DIR
+        for slot in make_lfn_slots(long, checksum) {
+            disk[off..off + 32].copy_from_slice(&slot);
+            off += 32;
+        }
+
+        let mut e = [0u8; 32];
+        e[0..11].copy_from_slice(short);
+        e[11] = 0x20;
+        e[26] = 0x03; // partage le contenu de HELLO.TXT
+        e[28] = 5;
+        disk[off..off + 32].copy_from_slice(&e);
+        disk[off + 32] = 0x00;
+    }
+
+    #[test]
+    fn list_root_reads_long_file_names() {
+        let mut disk = build_test_image();
+        add_long_named_file(&mut disk, "Un nom très long.txt", false);
+
+        let fs = Fat32::new(&disk).unwrap();
+        let root = fs.list_root().unwrap();
+        assert_eq!(root.len(), 3);
+
+        let e = root
+            .iter()
+            .find(|e| e.short_name == "LONGFI~1.TXT")
+            .unwrap();
+        assert_eq!(e.name, "Un nom très long.txt");
+
+        // On retrouve le fichier par son nom long (sans casse) ou par son nom court.
+        let by_long = fs
+            .read_file_by_path("/un NOM TRÈS long.TXT")
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_long, b"HELLO");
+        let by_short = fs.open_path("/longfi~1.txt").unwrap().unwrap();
+        assert_eq!(by_short.name, "Un nom très long.txt");
+    }
+
+    #[test]
+    fn lfn_with_bad_checksum_falls_back_to_short_name() {
+        let mut disk = build_test_image();
+        add_long_named_file(&mut disk, "Un nom très long.txt", true);
+
+        let fs = Fat32::new(&disk).unwrap();
+        let root = fs.list_root().unwrap();
+        assert_eq!(root.len(), 3);
+        assert!(root.iter().any(|e| e.name == "LONGFI~1.TXT"));
+    }
+
     #[test]
     fn new_on_too_small_buffer_fails() {
         let tiny = [0u8; 128];
@@ -1104,17 +2707,72 @@ mod tests {
     }
 
     #[test]
-    fn write_rejects_invalid_8_3_name() {
+    fn write_rejects_invalid_long_name() {
         let mut disk = build_test_image();
 
         let res = {
             let mut rw = Fat32Mut::new(&mut disk).unwrap();
-            rw.write_file_by_path("/TOO_LONG_NAME.TXT", b"x")
+            rw.write_file_by_path("/BAD:NAME?.TXT", b"x")
         };
 
         assert_eq!(res.unwrap_err(), FatError::InvalidName);
     }
 
+    #[test]
+    fn write_long_names_creates_lfn_and_unique_aliases() {
+        let mut disk = build_test_image();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/Rapport annuel 2024.txt", b"A")
+                .unwrap();
+            rw.write_file_by_path("/Rapport annuel 2025.txt", b"B")
+                .unwrap();
+            rw.write_file_by_path("/note.txt", b"C").unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let root = ro.list_root().unwrap();
+
+        let a = root
+            .iter()
+            .find(|e| e.name == "Rapport annuel 2024.txt")
+            .unwrap();
+        let b = root
+            .iter()
+            .find(|e| e.name == "Rapport annuel 2025.txt")
+            .unwrap();
+        assert_eq!(a.short_name, "RAPPOR~1.TXT");
+        assert_eq!(b.short_name, "RAPPOR~2.TXT");
+
+        // Un 8.3 en minuscules garde sa casse via un LFN, sans suffixe numérique.
+        let c = root.iter().find(|e| e.name == "note.txt").unwrap();
+        assert_eq!(c.short_name, "NOTE.TXT");
+
+        let content = ro
+            .read_file_by_path("/rapport ANNUEL 2025.TXT")
+            .unwrap()
+            .unwrap();
+        assert_eq!(content, b"B");
+    }
+
+    #[test]
+    fn overwrite_long_name_reuses_existing_entry() {
+        let mut disk = build_test_image();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/Mon fichier.bin", b"v1").unwrap();
+            rw.write_file_by_path("/MONFIC~1.BIN", b"v2").unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let root = ro.list_root().unwrap();
+        assert_eq!(root.len(), 3);
+        let content = ro.read_file_by_path("/Mon fichier.bin").unwrap().unwrap();
+        assert_eq!(content, b"v2");
+    }
+
     #[test]
     fn write_fails_when_parent_directory_missing() {
         let mut disk = build_test_image();
@@ -1166,4 +2824,1982 @@ mod tests {
         assert_eq!(e.size, 0);
         assert_eq!(e.first_cluster, 0);
     }
+
+    #[test]
+    fn create_dir_writes_dot_entries_and_accepts_files() {
+        let mut disk = build_test_image();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.create_dir("/Projets").unwrap();
+            rw.create_dir("/Projets/SUB").unwrap();
+            rw.write_file_by_path("/Projets/SUB/A.TXT", b"abc").unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let projets = ro.open_path("/Projets").unwrap().unwrap();
+        assert!(projets.is_dir());
+
+        let entries = ro.list_dir_path("/Projets").unwrap();
+        let dot = entries.iter().find(|e| e.name == ".").unwrap();
+        let dot_dot = entries.iter().find(|e| e.name == "..").unwrap();
+        assert_eq!(dot.first_cluster, projets.first_cluster);
+        // Parent = racine -> `..` vaut 0
+        assert_eq!(dot_dot.first_cluster, 0);
+
+        let sub = ro.list_dir_path("/Projets/SUB").unwrap();
+        let sub_dot_dot = sub.iter().find(|e| e.name == "..").unwrap();
+        assert_eq!(sub_dot_dot.first_cluster, projets.first_cluster);
+
+        let content = ro
+            .read_file_by_path("/Projets/SUB/../SUB/A.TXT")
+            .unwrap()
+            .unwrap();
+        assert_eq!(content, b"abc");
+        assert!(ro.open_path("/Projets/..").unwrap().unwrap().is_dir());
+    }
+
+    #[test]
+    fn create_dir_rejects_existing_name() {
+        let mut disk = build_test_image();
+
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        assert_eq!(rw.create_dir("/dir").unwrap_err(), FatError::AlreadyExists);
+        assert_eq!(
+            rw.create_dir("/HELLO.TXT").unwrap_err(),
+            FatError::AlreadyExists
+        );
+        assert_eq!(
+            rw.create_dir("/NOPE/X").unwrap_err(),
+            FatError::PathNotFound
+        );
+    }
+
+    #[test]
+    fn remove_file_marks_lfn_slots_and_frees_chain() {
+        let mut disk = build_test_image();
+        let big = vec![0x42u8; 600];
+
+        let first = {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/Gros fichier.bin", &big).unwrap();
+            let e = rw
+                .as_read()
+                .open_path("/Gros fichier.bin")
+                .unwrap()
+                .unwrap();
+            rw.remove_file("/gros FICHIER.bin").unwrap();
+            e.first_cluster
+        };
+
+        assert_eq!(fat_entry_raw(&disk, first), 0);
+
+        // Slots LFN + entrée courte marqués 0xE5 (juste après HELLO.TXT et DIR)
+        let root_off = test_cluster_offset(2);
+        assert_eq!(disk[root_off + 64], 0xE5);
+        assert_eq!(disk[root_off + 96], 0xE5);
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert!(ro.open_path("/Gros fichier.bin").unwrap().is_none());
+        assert_eq!(ro.list_root().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn remove_dir_refuses_non_empty_directories() {
+        let mut disk = build_test_image();
+
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/A").unwrap();
+        rw.write_file_by_path("/A/F.TXT", b"x").unwrap();
+
+        assert_eq!(
+            rw.remove_dir("/A").unwrap_err(),
+            FatError::DirectoryNotEmpty
+        );
+        assert_eq!(rw.remove_file("/A").unwrap_err(), FatError::NotAFile);
+        assert_eq!(
+            rw.remove_dir("/A/F.TXT").unwrap_err(),
+            FatError::NotADirectory
+        );
+
+        rw.remove_file("/A/F.TXT").unwrap();
+        rw.remove_dir("/A").unwrap();
+        rw.remove_dir("/DIR").unwrap();
+
+        let root = rw.as_read().list_root().unwrap();
+        assert_eq!(root.len(), 1);
+        assert_eq!(fat_entry_raw(&disk, 4), 0);
+    }
+
+    #[test]
+    fn rename_moves_file_without_touching_its_data() {
+        let mut disk = build_test_image();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.rename("/HELLO.TXT", "/DIR/Bonjour le monde.txt")
+                .unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert!(ro.open_path("/HELLO.TXT").unwrap().is_none());
+
+        let moved = ro.open_path("/DIR/Bonjour le monde.txt").unwrap().unwrap();
+        assert_eq!(moved.first_cluster, 3);
+        assert_eq!(moved.size, 5);
+        let content = ro.read_file(&moved).unwrap();
+        assert_eq!(content, b"HELLO");
+        assert_eq!(fat_entry_raw(&disk, 3), FAT32_EOC);
+    }
+
+    #[test]
+    fn rename_directory_updates_dot_dot() {
+        let mut disk = build_test_image();
+
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/A").unwrap();
+        rw.create_dir("/A/B").unwrap();
+        rw.rename("/A/B", "/DIR/Sous dossier").unwrap();
+
+        let dot_dot_of = |rw: &Fat32Mut<&mut [u8]>, path: &str| {
+            let entries = rw.as_read().list_dir_path(path).unwrap();
+            entries
+                .iter()
+                .find(|e| e.name == "..")
+                .unwrap()
+                .first_cluster
+        };
+
+        assert_eq!(dot_dot_of(&rw, "/DIR/Sous dossier"), 4);
+        assert!(rw
+            .as_read()
+            .list_dir_path("/A")
+            .unwrap()
+            .iter()
+            .all(|e| e.name != "B"));
+
+        // Retour sous la racine : `..` doit repasser à 0
+        rw.rename("/DIR/Sous dossier", "/B").unwrap();
+        assert_eq!(dot_dot_of(&rw, "/B"), 0);
+    }
+
+    #[test]
+    fn rename_refuses_cycles_and_existing_targets() {
+        let mut disk = build_test_image();
+
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/DIR/SUB").unwrap();
+
+        assert_eq!(
+            rw.rename("/DIR", "/DIR/SUB/X").unwrap_err(),
+            FatError::InvalidMove
+        );
+        assert_eq!(
+            rw.rename("/DIR", "/DIR/X").unwrap_err(),
+            FatError::InvalidMove
+        );
+        assert_eq!(
+            rw.rename("/DIR", "/HELLO.TXT").unwrap_err(),
+            FatError::AlreadyExists
+        );
+
+        // Changement de casse sur la même entrée : autorisé
+        rw.rename("/HELLO.TXT", "/hello.txt").unwrap();
+        let root = rw.as_read().list_root().unwrap();
+        let hello = root.iter().find(|e| e.name == "hello.txt").unwrap();
+        assert_eq!(hello.short_name, "HELLO.TXT");
+        assert_eq!(root.len(), 2);
+    }
+
+    #[test]
+    fn full_directory_grows_with_a_new_cluster() {
+        let mut disk = build_test_image();
+
+        // La racine tient sur un secteur (16 entrées) dont 2 déjà prises.
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            for i in 0..20 {
+                let name = format!("/F{i:02}.TXT");
+                rw.write_file_by_path(&name, b"").unwrap();
+            }
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let root = ro.list_root().unwrap();
+        assert_eq!(root.len(), 22);
+        assert!(root.iter().any(|e| e.name == "F19.TXT"));
+
+        // La racine est maintenant chaînée sur un 2e cluster.
+        let next = fat_entry_raw(&disk, 2);
+        assert!((2..0x0FFF_FFF8).contains(&next));
+        assert_eq!(fat_entry_raw(&disk, next), FAT32_EOC);
+    }
+
+    #[test]
+    fn timestamps_roundtrip_and_unix_conversion() {
+        // 2024-02-29 13:37:42 UTC
+        let ts = FatTimestamp::from_unix_seconds(1_709_213_862);
+        assert_eq!(ts.to_string(), "2024-02-29 13:37:42");
+
+        let (date, time, hundredths) = ts.to_raw();
+        assert_eq!(FatTimestamp::from_raw(date, time, hundredths), Some(ts));
+        // Sans les centièmes, on perd la seconde impaire (précision 2 s)
+        assert_eq!(FatTimestamp::from_raw(date, time, 0).unwrap().second, 42);
+
+        assert_eq!(FatTimestamp::from_unix_seconds(0), FatTimestamp::EPOCH);
+        assert_eq!(FatDate::from_raw(0), None);
+
+        // Champs hors plage d’une image abîmée : rien n’est inventé
+        let raw_date = |month: u16, day: u16| (5 << 9) | (month << 5) | day;
+        assert_eq!(FatDate::from_raw(raw_date(15, 31)), None);
+        assert_eq!(FatDate::from_raw(raw_date(0, 10)), None);
+        assert_eq!(FatDate::from_raw(raw_date(6, 0)), None);
+        assert_eq!(
+            FatDate::from_raw(raw_date(12, 31)).unwrap().to_string(),
+            "1985-12-31"
+        );
+
+        let date = raw_date(6, 15);
+        let raw_time = |h: u16, m: u16, s2: u16| (h << 11) | (m << 5) | s2;
+        for raw in [
+            raw_time(24, 0, 0),
+            raw_time(31, 0, 0),
+            raw_time(12, 60, 0),
+            raw_time(12, 63, 0),
+        ] {
+            assert_eq!(FatTimestamp::from_raw(date, raw, 0), None);
+        }
+        assert_eq!(FatTimestamp::from_raw(date, raw_time(12, 0, 30), 0), None);
+        assert_eq!(FatTimestamp::from_raw(date, raw_time(12, 0, 0), 200), None);
+        let last = FatTimestamp::from_raw(date, raw_time(23, 59, 29), 199).unwrap();
+        assert_eq!(
+            (last.to_string(), last.millis),
+            ("1985-06-15 23:59:59".into(), 990)
+        );
+    }
+
+    #[test]
+    fn writes_use_the_supplied_clock() {
+        let mut disk = build_test_image();
+        let t1 = FatTimestamp::from_unix_seconds(1_600_000_000);
+        let t2 = FatTimestamp::from_unix_seconds(1_700_000_001);
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.set_clock(t1);
+            rw.write_file_by_path("/LOG.TXT", b"v1").unwrap();
+            rw.create_dir("/D").unwrap();
+
+            rw.set_clock(move || t2);
+            rw.write_file_by_path("/LOG.TXT", b"v2").unwrap();
+            rw.rename("/D", "/DIR/D").unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let log = ro.open_path("/LOG.TXT").unwrap().unwrap();
+        assert_eq!(log.created, Some(t1));
+        assert_eq!(log.modified, Some(FatTimestamp { second: 20, ..t2 }));
+        assert_eq!(log.accessed, Some(t2.date));
+
+        // Le déplacement conserve les dates d’origine
+        let d = ro.open_path("/DIR/D").unwrap().unwrap();
+        assert_eq!(d.created, Some(t1));
+        assert_eq!(d.modified, Some(t1));
+    }
+
+    #[test]
+    fn fs_info_is_kept_in_sync_with_allocations() {
+        let mut disk = build_formatted_image();
+
+        let ro = Fat32::new(&disk).unwrap();
+        let info = ro.fs_info().unwrap();
+        assert_eq!(info.free_count, Some(65_533));
+        assert_eq!(info.next_free, Some(3));
+        assert_eq!(ro.free_cluster_count().unwrap(), 65_533);
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/A.BIN", &[1u8; 1500]).unwrap(); // 3 clusters
+            let info = rw.as_read().fs_info().unwrap();
+            assert_eq!(info.free_count, Some(65_530));
+            assert_eq!(info.next_free, Some(6));
+
+            rw.remove_file("/A.BIN").unwrap();
+            let ro = rw.as_read();
+            assert_eq!(ro.fs_info().unwrap().free_count, Some(65_533));
+            assert_eq!(ro.free_cluster_count().unwrap(), 65_533);
+        }
+    }
+
+    #[test]
+    fn alloc_starts_at_next_free_hint_and_recounts_unknown_free_count() {
+        let mut disk = build_formatted_image();
+        let fs_info = 512;
+        disk[fs_info + 488..fs_info + 492].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+        disk[fs_info + 492..fs_info + 496].copy_from_slice(&40u32.to_le_bytes());
+
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        assert_eq!(rw.as_read().fs_info().unwrap().free_count, None);
+
+        rw.write_file_by_path("/B.BIN", b"b").unwrap();
+        let ro = rw.as_read();
+        let b = ro.open_path("/B.BIN").unwrap().unwrap();
+        assert_eq!(b.first_cluster, 40);
+        assert_eq!(ro.fs_info().unwrap().free_count, Some(65_532));
+    }
+
+    /// Support en mémoire avec des blocs de 1024 octets : les secteurs FAT
+    /// (512 octets) ne sont plus alignés sur les blocs, ce qui force les
+    /// lectures/écritures partielles (read-modify-write).
+    struct BigBlocks(Vec<u8>);
+
+    impl BlockDevice for BigBlocks {
+        fn block_size(&self) -> usize {
+            1024
+        }
+
+        fn num_blocks(&self) -> u64 {
+            (self.0.len() / 1024) as u64
+        }
+
+        fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+            let start = lba as usize * 1024;
+            buf.copy_from_slice(&self.0[start..start + buf.len()]);
+            Ok(())
+        }
+    }
+
+    impl BlockDeviceMut for BigBlocks {
+        fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+            let start = lba as usize * 1024;
+            self.0[start..start + buf.len()].copy_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn block_device_with_larger_blocks_matches_slice_backend() {
+        let mut reference = build_test_image();
+        {
+            let mut rw = Fat32Mut::new(&mut reference).unwrap();
+            rw.write_file_by_path("/DIR/Notes de cours.txt", &[7u8; 700])
+                .unwrap();
+            rw.remove_file("/HELLO.TXT").unwrap();
+        }
+
+        let mut rw = Fat32Mut::open(BigBlocks(build_test_image().to_vec())).unwrap();
+        assert_eq!(
+            rw.as_read().read_file_by_path("/HELLO.TXT").unwrap(),
+            Some(b"HELLO".to_vec())
+        );
+        rw.write_file_by_path("/DIR/Notes de cours.txt", &[7u8; 700])
+            .unwrap();
+        rw.remove_file("/HELLO.TXT").unwrap();
+
+        let dev = rw.into_inner();
+        assert_eq!(&dev.0[..], &reference[..]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file_backed_volume_writes_in_place() {
+        use std::fs::OpenOptions;
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("fat32_parser_{}.img", std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(&build_test_image())
+            .unwrap();
+
+        {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .unwrap();
+            let mut rw = Fat32Mut::open(file).unwrap();
+            rw.write_file_by_path("/DIR/NOTE.TXT", b"sur le disque")
+                .unwrap();
+        }
+
+        let file = std::fs::File::open(&path).unwrap();
+        let ro = Fat32::open(&file).unwrap();
+        let content = ro.read_file_by_path("/DIR/NOTE.TXT").unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(content, Some(b"sur le disque".to_vec()));
+    }
+
+    #[test]
+    fn file_reader_reads_and_seeks_across_clusters() {
+        use crate::io::{Read, Seek, SeekFrom};
+
+        let content: Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+        let mut disk = build_test_image();
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/DIR/DATA.BIN", &content).unwrap();
+        }
+
+        let fs = Fat32::new(&disk).unwrap();
+        let mut r = fs.open_file("/DIR/DATA.BIN").unwrap();
+        assert_eq!(r.size(), 1500);
+
+        // Lecture séquentielle par petits morceaux (à cheval sur les clusters)
+        let mut out = Vec::new();
+        let mut buf = [0u8; 100];
+        loop {
+            let n = r.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(out, content);
+
+        // Retour en arrière dans un cluster précédent
+        assert_eq!(r.seek(SeekFrom::Start(1020)).unwrap(), 1020);
+        let mut small = [0u8; 20];
+        r.read_exact(&mut small).unwrap();
+        assert_eq!(&small[..], &content[1020..1040]);
+
+        assert_eq!(r.seek(SeekFrom::Current(-540)).unwrap(), 500);
+        r.read_exact(&mut small).unwrap();
+        assert_eq!(&small[..], &content[500..520]);
+
+        r.seek(SeekFrom::End(-10)).unwrap();
+        assert_eq!(r.read(&mut buf).unwrap(), 10);
+        assert_eq!(&buf[..10], &content[1490..]);
+
+        // Après la fin : lecture vide ; avant le début : erreur
+        r.seek(SeekFrom::Start(4000)).unwrap();
+        assert_eq!(r.read(&mut buf).unwrap(), 0);
+        assert_eq!(r.seek(SeekFrom::Current(-5000)), Err(FatError::InvalidSeek));
+
+        assert_eq!(fs.open_file("/DIR").unwrap_err(), FatError::NotAFile);
+        assert_eq!(
+            fs.open_file("/NOPE.TXT").unwrap_err(),
+            FatError::PathNotFound
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file_reader_implements_std_io() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let disk = build_test_image();
+        let fs = Fat32::new(&disk).unwrap();
+        let mut r = fs.open_file("/hello.txt").unwrap();
+
+        r.seek(SeekFrom::Start(1)).unwrap();
+        let mut s = String::new();
+        r.read_to_string(&mut s).unwrap();
+        assert_eq!(s, "ELLO");
+
+        let err = r.seek(SeekFrom::Current(-10)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn file_writer_appends_and_overwrites_in_place() {
+        use crate::io::{Seek, SeekFrom, Write};
+
+        let content: Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+        let mut disk = build_test_image();
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+
+            let mut w = rw.append_file("/HELLO.TXT").unwrap();
+            assert_eq!(w.position(), 5);
+            w.write_all(b" WORLD").unwrap();
+            w.flush().unwrap();
+            drop(w);
+
+            rw.write_file_by_path("/DIR/DATA.BIN", &content).unwrap();
+        }
+        let chain_before = {
+            let ro = Fat32::new(&disk).unwrap();
+            let e = ro.open_path("/DIR/DATA.BIN").unwrap().unwrap();
+            ro.follow_chain(e.first_cluster).unwrap()
+        };
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.open_file_mut("/DIR/DATA.BIN").unwrap();
+            // Plage à cheval sur la frontière entre le 1er et le 2e cluster
+            w.seek(SeekFrom::Start(510)).unwrap();
+            w.write_all(b"ABCD").unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert_eq!(
+            ro.read_file_by_path("/HELLO.TXT").unwrap(),
+            Some(b"HELLO WORLD".to_vec())
+        );
+
+        let mut expected = content.clone();
+        expected[510..514].copy_from_slice(b"ABCD");
+        let e = ro.open_path("/DIR/DATA.BIN").unwrap().unwrap();
+        assert_eq!(e.size, 1500);
+        assert_eq!(ro.read_file(&e).unwrap(), expected);
+        assert_eq!(ro.follow_chain(e.first_cluster).unwrap(), chain_before);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file_writer_streams_new_file_and_fills_holes() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut disk = build_test_image();
+        let content: Vec<u8> = (0..1300u32).map(|i| (i % 7) as u8 + b'a').collect();
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.create_file("/LOG.TXT").unwrap();
+            for chunk in content.chunks(7) {
+                w.write_all(chunk).unwrap();
+            }
+            assert_eq!(w.size(), 1300);
+
+            // Trou de 100 octets, puis une écriture au-delà
+            w.seek(SeekFrom::End(100)).unwrap();
+            w.write_all(b"END").unwrap();
+            w.flush().unwrap();
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        let e = ro.open_path("/LOG.TXT").unwrap().unwrap();
+        assert_eq!(e.size, 1403);
+        assert_eq!(ro.follow_chain(e.first_cluster).unwrap().len(), 3);
+
+        let data = ro.read_file(&e).unwrap();
+        assert_eq!(&data[..1300], &content[..]);
+        assert!(data[1300..1400].iter().all(|&b| b == 0));
+        assert_eq!(&data[1400..], b"END");
+
+        // `create_file` sur un fichier existant le tronque
+        let mut disk2 = disk;
+        {
+            let mut rw = Fat32Mut::new(&mut disk2).unwrap();
+            let w = rw.create_file("/LOG.TXT").unwrap();
+            assert_eq!(w.size(), 0);
+        }
+        let ro = Fat32::new(&disk2).unwrap();
+        assert_eq!(ro.read_file_by_path("/LOG.TXT").unwrap(), Some(Vec::new()));
+        assert_eq!(
+            ro.free_cluster_count().unwrap(),
+            Fat32::new(&build_test_image())
+                .unwrap()
+                .free_cluster_count()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn set_len_shrinks_and_grows_chain() {
+        let content: Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+        let mut disk = build_test_image();
+        // Données parasites après "HELLO" dans le cluster 3
+        disk[test_cluster_offset(3) + 5..test_cluster_offset(4)].fill(0xAA);
+
+        let (old_chain, free_before) = {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/DATA.BIN", &content).unwrap();
+            let ro = rw.as_read();
+            let e = ro.open_path("/DATA.BIN").unwrap().unwrap();
+            (
+                ro.follow_chain(e.first_cluster).unwrap(),
+                ro.free_cluster_count().unwrap(),
+            )
+        };
+        assert_eq!(old_chain.len(), 3);
+
+        Fat32Mut::new(&mut disk)
+            .unwrap()
+            .set_len("/DATA.BIN", 600)
+            .unwrap();
+
+        // Réduction : 2 clusters gardés, le 3e libéré, nouvel EOC
+        assert!(fat_entry_raw(&disk, old_chain[1]) >= 0x0FFF_FFF8);
+        assert_eq!(fat_entry_raw(&disk, old_chain[2]), 0);
+
+        Fat32Mut::new(&mut disk)
+            .unwrap()
+            .set_len("/HELLO.TXT", 1200)
+            .unwrap();
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert_eq!(
+            ro.read_file_by_path("/DATA.BIN").unwrap().unwrap(),
+            &content[..600]
+        );
+
+        // Agrandissement : 3 clusters, tout ce qui suit "HELLO" lit 0
+        let hello = ro.open_path("/HELLO.TXT").unwrap().unwrap();
+        assert_eq!(hello.size, 1200);
+        assert_eq!(ro.follow_chain(hello.first_cluster).unwrap().len(), 3);
+        let data = ro.read_file(&hello).unwrap();
+        assert_eq!(&data[..5], b"HELLO");
+        assert!(data[5..].iter().all(|&b| b == 0));
+        assert_eq!(ro.free_cluster_count().unwrap(), free_before + 1 - 2);
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.set_len("/DATA.BIN", 0).unwrap();
+            assert_eq!(rw.set_len("/DIR", 10).unwrap_err(), FatError::NotAFile);
+        }
+        let ro = Fat32::new(&disk).unwrap();
+        let e = ro.open_path("/DATA.BIN").unwrap().unwrap();
+        assert_eq!((e.first_cluster, e.size), (0, 0));
+        assert_eq!(fat_entry_raw(&disk, old_chain[0]), 0);
+    }
+
+    #[test]
+    fn preallocate_reserves_contiguous_clusters_used_by_writer() {
+        use crate::io::Write;
+
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+
+        // Trou d’un seul cluster en 3 : trop petit pour la réserve
+        rw.write_file_by_path("/A.BIN", b"a").unwrap();
+        rw.write_file_by_path("/B.BIN", b"b").unwrap();
+        rw.remove_file("/A.BIN").unwrap();
+
+        rw.write_file_by_path("/LOG.BIN", &[]).unwrap();
+        rw.preallocate("/LOG.BIN", 2048).unwrap();
+
+        let ro = rw.as_read();
+        let e = ro.open_path("/LOG.BIN").unwrap().unwrap();
+        assert_eq!(e.size, 0);
+        let chain = ro.follow_chain(e.first_cluster).unwrap();
+        assert_eq!(chain, vec![5, 6, 7, 8]);
+        let free = ro.fs_info().unwrap().free_count.unwrap();
+
+        // Les écritures remplissent la réserve sans nouvelle allocation
+        {
+            let mut w = rw.append_file("/LOG.BIN").unwrap();
+            w.write_all(&[0x55; 1500]).unwrap();
+        }
+        let ro = rw.as_read();
+        let e = ro.open_path("/LOG.BIN").unwrap().unwrap();
+        assert_eq!(e.size, 1500);
+        assert_eq!(ro.follow_chain(e.first_cluster).unwrap(), chain);
+        assert_eq!(ro.fs_info().unwrap().free_count, Some(free));
+
+        // `set_len` à la taille finale rend la réserve inutilisée
+        rw.set_len("/LOG.BIN", 1500).unwrap();
+        let ro = rw.as_read();
+        assert_eq!(ro.follow_chain(e.first_cluster).unwrap(), &chain[..3]);
+        assert_eq!(ro.fs_info().unwrap().free_count, Some(free + 1));
+        assert_eq!(
+            ro.read_file_by_path("/LOG.BIN").unwrap().unwrap(),
+            vec![0x55; 1500]
+        );
+    }
+
+    #[test]
+    fn preallocated_reserve_survives_check_and_repair() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.write_file_by_path("/LOG.BIN", b"abc").unwrap();
+        rw.preallocate("/LOG.BIN", 64 * 1024).unwrap();
+        let first = rw
+            .as_read()
+            .open_path("/LOG.BIN")
+            .unwrap()
+            .unwrap()
+            .first_cluster;
+
+        // Réserve listée, mais le volume reste cohérent
+        let reserve = Problem::ChainTooLong {
+            path: "/LOG.BIN".into(),
+            size: 3,
+            clusters: 128,
+        };
+        let report = rw.as_read().check().unwrap();
+        assert_eq!(report.problems, vec![reserve.clone()]);
+        assert!(reserve.is_reserve());
+        assert!(report.is_clean());
+
+        // `repair` n’y touche pas
+        let report = rw.repair(&RepairOptions::default()).unwrap();
+        assert!(report.repaired.is_empty());
+        assert!(report.changes.is_empty());
+        assert_eq!(report.remaining, vec![reserve.clone()]);
+        let ro = rw.as_read();
+        assert_eq!(ro.follow_chain(first).unwrap().len(), 128);
+        assert!(!ro.is_dirty().unwrap());
+
+        // Sauf si on lui demande de couper les chaînes à la taille
+        let options = RepairOptions {
+            trim_reserved: true,
+            ..RepairOptions::default()
+        };
+        let report = rw.repair(&options).unwrap();
+        assert_eq!(report.repaired, vec![reserve]);
+        assert!(report.remaining.is_empty());
+        let ro = rw.as_read();
+        assert_eq!(ro.follow_chain(first).unwrap(), vec![first]);
+        assert_eq!(
+            ro.fs_info().unwrap().free_count,
+            Some(ro.free_cluster_count().unwrap())
+        );
+    }
+
+    #[test]
+    fn fat_type_follows_cluster_count_thresholds() {
+        assert_eq!(FatType::from_cluster_count(4084), FatType::Fat12);
+        assert_eq!(FatType::from_cluster_count(4085), FatType::Fat16);
+        assert_eq!(FatType::from_cluster_count(65524), FatType::Fat16);
+        assert_eq!(FatType::from_cluster_count(65525), FatType::Fat32);
+
+        let fat12 = build_legacy_image(FatType::Fat12);
+        let fat16 = build_legacy_image(FatType::Fat16);
+        let fat32 = build_formatted_image();
+        assert_eq!(Fat32::new(&fat12).unwrap().fat_type(), FatType::Fat12);
+        assert_eq!(Fat32::new(&fat16).unwrap().fat_type(), FatType::Fat16);
+        assert_eq!(Fat32::new(&fat32).unwrap().fat_type(), FatType::Fat32);
+
+        // BPB FAT32 avec trop peu de clusters : un autre pilote y verrait du FAT16
+        let mut small = fat32.clone();
+        small[32..36].copy_from_slice(&(FORMATTED_DATA_SECTOR as u32 + 65_524).to_le_bytes());
+        assert_eq!(
+            Fat32::new(&small).unwrap_err(),
+            FatError::ClusterCountMismatch
+        );
+
+        // BPB FAT16 avec trop de clusters pour des entrées de 16 bits
+        let mut bad = vec![0u8; 70_000 * 512];
+        bad[..512].copy_from_slice(&fat16[..512]);
+        bad[19..21].copy_from_slice(&0u16.to_le_bytes());
+        bad[32..36].copy_from_slice(&70_000u32.to_le_bytes());
+        assert_eq!(
+            Fat32::new(&bad).unwrap_err(),
+            FatError::ClusterCountMismatch
+        );
+    }
+
+    #[test]
+    fn fat12_volume_packs_entries_and_uses_fixed_root() {
+        let mut disk = build_legacy_image(FatType::Fat12);
+        let content: Vec<u8> = (0..1500u32).map(|i| (i % 241) as u8).collect();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/HELLO.TXT", &content).unwrap();
+            rw.create_dir("/SUB").unwrap();
+            rw.write_file_by_path("/SUB/Note longue.txt", b"note")
+                .unwrap();
+        }
+
+        // Clusters 2 -> 3 -> 4 -> EOC, puis SUB (5) et la note (6)
+        for fat in [512usize, 10 * 512] {
+            assert_eq!(
+                &disk[fat..fat + 12],
+                &[0xF0, 0xFF, 0xFF, 0x03, 0x40, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x00]
+            );
+        }
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert!(ro.fs_info().is_none());
+        assert_eq!(
+            ro.read_file_by_path("/HELLO.TXT").unwrap().unwrap(),
+            content
+        );
+        assert_eq!(
+            ro.read_file_by_path("/SUB/NOTE LONGUE.TXT")
+                .unwrap()
+                .unwrap(),
+            b"note"
+        );
+        // `..` d’un sous-répertoire de la racine : cluster 0 = racine fixe
+        let names: Vec<String> = ro
+            .list_dir_path("/SUB/..")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["HELLO.TXT", "SUB"]);
+        assert_eq!(ro.free_cluster_count().unwrap(), 2847 - 5);
+
+        // Libérer un cluster pair ne touche pas son voisin impair
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.remove_file("/HELLO.TXT").unwrap();
+        assert_eq!(rw.as_read().read_fat_entry(4).unwrap(), 0);
+        assert!(rw.as_read().read_fat_entry(5).unwrap() >= 0x0FFF_FFF8);
+
+        // La racine fixe ne grandit pas : 224 entrées au plus
+        let mut created = 0;
+        let err = loop {
+            match rw.write_file_by_path(&format!("/F{created}.TXT"), &[]) {
+                Ok(()) => created += 1,
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(err, FatError::NoSpaceLeft);
+        assert_eq!(created, 224 - 1);
+    }
+
+    #[test]
+    fn fat16_volume_uses_two_byte_entries() {
+        let mut disk = build_legacy_image(FatType::Fat16);
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/A.BIN", &[0xAA; 600]).unwrap();
+            rw.write_file_by_path("/B.BIN", &[0xBB; 10]).unwrap();
+            rw.set_len("/A.BIN", 1100).unwrap();
+        }
+
+        // A : 2 -> 3 -> 5 (B a pris le 4)
+        let fat = 512usize;
+        let entry = |cl: usize| u16::from_le_bytes([disk[fat + cl * 2], disk[fat + cl * 2 + 1]]);
+        assert_eq!(
+            [entry(0), entry(1), entry(2), entry(3), entry(4), entry(5)],
+            [0xFFF0, 0xFFFF, 3, 5, 0xFFFF, 0xFFFF]
+        );
+        let copy = fat + 17 * 512;
+        assert_eq!(disk[fat..fat + 12], disk[copy..copy + 12]);
+
+        let ro = Fat32::new(&disk).unwrap();
+        let a = ro.read_file_by_path("/A.BIN").unwrap().unwrap();
+        assert_eq!(a.len(), 1100);
+        assert!(a[..600].iter().all(|&b| b == 0xAA));
+        assert!(a[600..].iter().all(|&b| b == 0));
+        assert_eq!(ro.free_cluster_count().unwrap(), 4200 - 4);
+        // Le dernier cluster est limité par la taille du volume
+        assert_eq!(ro.max_cluster_number().unwrap(), 4201);
+    }
+
+    #[test]
+    fn mbr_partitions_are_listed_and_opened_by_index() {
+        let mut disk = build_partitioned_disk();
+
+        let parts = read_partitions(&disk[..]).unwrap();
+        let layout: Vec<_> = parts
+            .iter()
+            .map(|p| (p.index, p.kind, p.start_lba, p.num_sectors))
+            .collect();
+        let fat32 = FORMATTED_SECTORS as u64;
+        assert_eq!(
+            layout,
+            [
+                (1, 0x0C, 8, fat32),
+                (5, 0x0C, EXTENDED_LBA as u64 + 1, fat32),
+                (6, 0x01, SECOND_EBR_LBA as u64 + 1, 2880)
+            ]
+        );
+        assert!(parts[0].bootable && !parts[1].bootable);
+        assert!(parts.iter().all(|p| p.is_fat()));
+
+        // Le secteur 0 est un MBR, pas un BPB
+        assert_eq!(
+            Fat32::new(&disk).unwrap_err(),
+            FatError::InvalidBytesPerSector
+        );
+
+        {
+            let mut rw = Fat32Mut::open_partition(&mut disk[..], 5).unwrap();
+            rw.write_file_by_path("/LOGIQUE.TXT", b"partition 5")
+                .unwrap();
+        }
+        {
+            let mut rw = Fat32Mut::open_partition(&mut disk[..], 6).unwrap();
+            assert_eq!(rw.as_read().fat_type(), FatType::Fat12);
+            rw.write_file_by_path("/FLOPPY.TXT", b"partition 6")
+                .unwrap();
+        }
+
+        let p1 = Fat32::open_partition(&disk[..], 1).unwrap();
+        assert!(p1.list_root().unwrap().is_empty());
+        let p5 = Fat32::open_partition(&disk[..], 5).unwrap();
+        assert_eq!(
+            p5.read_file_by_path("/LOGIQUE.TXT").unwrap().unwrap(),
+            b"partition 5"
+        );
+        assert!(p5.open_path("/FLOPPY.TXT").unwrap().is_none());
+        let p6 = Fat32::open_partition(&disk[..], 6).unwrap();
+        assert_eq!(
+            p6.read_file_by_path("/FLOPPY.TXT").unwrap().unwrap(),
+            b"partition 6"
+        );
+
+        // Conteneur étendu, entrées vides et numéros inconnus
+        for index in [0, 2, 3, 4, 7] {
+            assert_eq!(
+                Fat32::open_partition(&disk[..], index).unwrap_err(),
+                FatError::PartitionNotFound
+            );
+        }
+
+        // La fenêtre ne déborde pas de la partition
+        let dev = PartitionDevice::new(&disk[..], 8, fat32).unwrap();
+        let mut block = [0u8; 512];
+        dev.read_blocks(fat32 - 1, &mut block).unwrap();
+        assert_eq!(
+            dev.read_blocks(fat32, &mut block).unwrap_err(),
+            FatError::OutOfBounds
+        );
+        let end = (disk.len() / 512) as u64;
+        assert_eq!(
+            PartitionDevice::new(&disk[..], end - 18, 64).unwrap_err(),
+            FatError::OutOfBounds
+        );
+    }
+
+    #[test]
+    fn invalid_mbr_and_looping_ebr_chain_are_rejected() {
+        let mut disk = build_partitioned_disk();
+        disk[510] = 0;
+        assert_eq!(
+            read_partitions(&disk[..]).unwrap_err(),
+            FatError::InvalidPartitionTable
+        );
+
+        // Octet de statut invalide (ex: code de boot à la place de la table)
+        let mut disk = build_partitioned_disk();
+        disk[446 + 16] = 0x12;
+        assert_eq!(
+            read_partitions(&disk[..]).unwrap_err(),
+            FatError::InvalidPartitionTable
+        );
+
+        // Le second EBR pointe vers lui-même
+        let mut disk = build_partitioned_disk();
+        let off = SECOND_EBR_LBA * 512 + 446 + 16;
+        disk[off + 4] = 0x05;
+        disk[off + 8..off + 12]
+            .copy_from_slice(&((SECOND_EBR_LBA - EXTENDED_LBA) as u32).to_le_bytes());
+        assert_eq!(
+            read_partitions(&disk[..]).unwrap_err(),
+            FatError::InvalidPartitionTable
+        );
+    }
+
+    #[test]
+    fn gpt_partitions_expose_type_guids_and_names() {
+        assert_eq!(gpt::crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            Guid::EFI_SYSTEM.to_string(),
+            "C12A7328-F81F-11D2-BA4B-00A0C93EC93B"
+        );
+
+        let mut disk = build_gpt_disk();
+        let table = read_gpt(&disk[..]).unwrap();
+        assert!(!table.from_backup);
+        assert_eq!(table.disk_guid, Guid([0x42; 16]));
+
+        let layout: Vec<_> = table
+            .partitions
+            .iter()
+            .map(|p| (p.index, p.first_lba, p.num_sectors(), p.name.as_str()))
+            .collect();
+        let fat32 = FORMATTED_SECTORS as u64;
+        assert_eq!(
+            layout,
+            [
+                (1, 40, fat32, "EFI system partition"),
+                (3, 40 + fat32, fat32, "Données")
+            ]
+        );
+        assert_eq!(table.partitions[1].type_guid, Guid::BASIC_DATA);
+
+        // Le MBR ne montre que l’entrée protectrice
+        let mbr = read_partitions(&disk[..]).unwrap();
+        assert_eq!(mbr.len(), 1);
+        assert_eq!(mbr[0].kind, 0xEE);
+
+        // On retrouve l’ESP par son type, et on l’ouvre comme un volume FAT
+        let esp = table.partitions.iter().find(|p| p.is_efi_system()).unwrap();
+        {
+            let dev =
+                PartitionDevice::new(&mut disk[..], esp.first_lba, esp.num_sectors()).unwrap();
+            let mut rw = Fat32Mut::open(dev).unwrap();
+            rw.create_dir("/EFI").unwrap();
+            rw.write_file_by_path("/EFI/BOOTX64.EFI", b"MZ").unwrap();
+        }
+
+        // `open_partition` suit la numérotation GPT
+        let esp = Fat32::open_partition(&disk[..], 1).unwrap();
+        assert_eq!(
+            esp.read_file_by_path("/EFI/BOOTX64.EFI").unwrap().unwrap(),
+            b"MZ"
+        );
+        let data = Fat32::open_partition(&disk[..], 3).unwrap();
+        assert!(data.list_root().unwrap().is_empty());
+        assert_eq!(
+            Fat32::open_partition(&disk[..], 2).unwrap_err(),
+            FatError::PartitionNotFound
+        );
+    }
+
+    #[test]
+    fn gpt_falls_back_to_backup_header() {
+        let expected = read_gpt(&build_gpt_disk()[..]).unwrap().partitions;
+
+        // En-tête principal abîmé (CRC faux)
+        let mut disk = build_gpt_disk();
+        disk[512 + 40] ^= 0xFF;
+        let table = read_gpt(&disk[..]).unwrap();
+        assert!(table.from_backup);
+        assert_eq!(table.partitions, expected);
+
+        // En-tête principal intact, mais tableau d’entrées abîmé
+        let mut disk = build_gpt_disk();
+        disk[2 * 512 + 60] ^= 0xFF;
+        let table = read_gpt(&disk[..]).unwrap();
+        assert!(table.from_backup);
+        assert_eq!(table.partitions, expected);
+        assert!(Fat32::open_partition(&disk[..], 1).is_ok());
+
+        // Les deux copies abîmées
+        let backup_header = disk.len() - 512;
+        disk[backup_header + 20] ^= 0xFF;
+        assert_eq!(
+            read_gpt(&disk[..]).unwrap_err(),
+            FatError::InvalidPartitionTable
+        );
+
+        // Sans MBR protecteur, ce n’est pas un disque GPT
+        let disk = build_partitioned_disk();
+        assert_eq!(
+            read_gpt(&disk[..]).unwrap_err(),
+            FatError::InvalidPartitionTable
+        );
+    }
+
+    #[test]
+    fn bpb_validation_reports_the_faulty_field() {
+        type Patch = fn(&mut [u8]);
+
+        let fat32_cases: [(Patch, FatError); 13] = [
+            (|b| b[511] = 0, FatError::MissingBootSignature),
+            (
+                |b| b[11..13].copy_from_slice(&1000u16.to_le_bytes()),
+                FatError::InvalidBytesPerSector,
+            ),
+            (
+                |b| b[11..13].copy_from_slice(&8192u16.to_le_bytes()),
+                FatError::InvalidBytesPerSector,
+            ),
+            (|b| b[13] = 0, FatError::InvalidSectorsPerCluster),
+            (|b| b[13] = 3, FatError::InvalidSectorsPerCluster),
+            (|b| b[14..16].fill(0), FatError::InvalidReservedSectors),
+            (|b| b[16] = 0, FatError::InvalidFatCount),
+            (|b| b[36..40].fill(0), FatError::InvalidFatSize),
+            (|b| b[32..36].fill(0), FatError::InvalidTotalSectors),
+            // Réservés + FAT = tout le volume
+            (
+                |b| b[32..36].copy_from_slice(&(FORMATTED_DATA_SECTOR as u32).to_le_bytes()),
+                FatError::InvalidTotalSectors,
+            ),
+            (
+                |b| b[32..36].copy_from_slice(&(FORMATTED_SECTORS as u32 + 1).to_le_bytes()),
+                FatError::VolumeExceedsDevice,
+            ),
+            (
+                |b| b[17..19].copy_from_slice(&512u16.to_le_bytes()),
+                FatError::InvalidRootEntryCount,
+            ),
+            // 65 534 clusters : 2..=65535
+            (
+                |b| b[44..48].copy_from_slice(&65_536u32.to_le_bytes()),
+                FatError::InvalidRootCluster,
+            ),
+        ];
+        for (patch, expected) in fat32_cases {
+            let mut disk = build_formatted_image();
+            patch(&mut disk);
+            assert_eq!(Fat32::new(&disk).unwrap_err(), expected);
+        }
+
+        let mut disk = build_formatted_image();
+        disk[44..48].fill(0);
+        assert_eq!(Fat32::new(&disk).unwrap_err(), FatError::InvalidRootCluster);
+
+        // FAT12 : FAT d’un seul secteur pour 2863 clusters, puis racine absente
+        let mut disk = build_legacy_image(FatType::Fat12);
+        disk[22..24].copy_from_slice(&1u16.to_le_bytes());
+        assert_eq!(Fat32::new(&disk).unwrap_err(), FatError::InvalidFatSize);
+        let mut disk = build_legacy_image(FatType::Fat12);
+        disk[17..19].fill(0);
+        assert_eq!(
+            Fat32::new(&disk).unwrap_err(),
+            FatError::InvalidRootEntryCount
+        );
+
+        // Les images de référence restent valides
+        assert!(Fat32::new(&build_test_image()).is_ok());
+        assert!(Fat32::new(&build_formatted_image()).is_ok());
+        assert!(Fat32::new(&build_legacy_image(FatType::Fat16)).is_ok());
+    }
+
+    #[test]
+    fn check_reports_a_clean_volume() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/DOCS").unwrap();
+        rw.write_file_by_path("/DOCS/Rapport annuel.txt", &[7u8; 600])
+            .unwrap();
+        rw.write_file_by_path("/B.TXT", b"b").unwrap();
+        rw.write_file_by_path("/VIDE.TXT", &[]).unwrap();
+
+        let report = rw.as_read().check().unwrap();
+        assert!(report.is_clean(), "{:?}", report.problems);
+        assert_eq!(report.files, 3);
+        assert_eq!(report.directories, 1);
+        // racine + DOCS + 2 clusters + 1 cluster
+        assert_eq!(report.used_clusters, 5);
+
+        let mut disk = build_legacy_image(FatType::Fat12);
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.write_file_by_path("/DATA.BIN", &[1u8; 1500]).unwrap();
+        let report = rw.as_read().check().unwrap();
+        assert!(report.is_clean(), "{:?}", report.problems);
+        assert_eq!((report.files, report.used_clusters), (1, 3));
+    }
+
+    #[test]
+    fn check_finds_each_kind_of_corruption() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/DOCS").unwrap();
+        rw.write_file_by_path("/DOCS/A.TXT", &[1u8; 600]).unwrap();
+        rw.write_file_by_path("/B.TXT", b"b").unwrap();
+        rw.write_file_by_path("/LOOP.BIN", &[2u8; 1500]).unwrap();
+        rw.write_file_by_path("/BAD.TXT", &[3u8; 600]).unwrap();
+        rw.write_file_by_path("/SHORT.TXT", b"s").unwrap();
+        rw.write_file_by_path("/LONG.BIN", &[]).unwrap();
+        rw.preallocate("/LONG.BIN", 1024).unwrap();
+
+        let ro = rw.as_read();
+        let first = |p: &str| ro.open_path(p).unwrap().unwrap().first_cluster;
+        let a = ro.follow_chain(first("/DOCS/A.TXT")).unwrap();
+        let b = first("/B.TXT");
+        let looped = ro.follow_chain(first("/LOOP.BIN")).unwrap();
+        let bad = first("/BAD.TXT");
+
+        // B.TXT pointe sur le 2e cluster de A.TXT : son ancien cluster est perdu
+        let b_entry = rw.locate_path("/B.TXT").unwrap();
+        rw.set_entry_first_cluster(b_entry.offset, a[1]).unwrap();
+        // LOOP.BIN : le dernier cluster repointe sur le premier
+        rw.write_fat_entry_all(looped[2], looped[0]).unwrap();
+        // BAD.TXT : chaîne qui tombe sur un cluster libre
+        rw.write_fat_entry_all(bad, 0).unwrap();
+        // SHORT.TXT annonce 2000 octets pour un seul cluster
+        let short = rw.locate_path("/SHORT.TXT").unwrap();
+        rw.write_at(short.offset + 28, &2000u32.to_le_bytes())
+            .unwrap();
+        // Chaîne orpheline 40 -> 41
+        rw.write_fat_entry_all(40, 41).unwrap();
+        rw.write_fat_entry_all(41, FAT32_EOC).unwrap();
+        drop(rw);
+        // Seconde FAT désynchronisée sur deux entrées
+        disk[FORMATTED_FAT2_SECTOR * 512 + 50 * 4] = 0x07;
+        disk[FORMATTED_FAT2_SECTOR * 512 + 52 * 4] = 0x07;
+
+        let report = Fat32::new(&disk).unwrap().check().unwrap();
+        // Ordre de découverte : FAT, arborescence (racine puis DOCS), chaînes perdues
+        let expected = vec![
+            Problem::FatMismatch {
+                copy: 1,
+                cluster: 50,
+                entries: 2,
+            },
+            Problem::ChainLoop {
+                path: "/LOOP.BIN".into(),
+                from: looped[2],
+                cluster: looped[0],
+            },
+            Problem::InvalidCluster {
+                path: "/BAD.TXT".into(),
+                from: Some(bad),
+                value: 0,
+            },
+            Problem::ChainTooShort {
+                path: "/SHORT.TXT".into(),
+                size: 2000,
+                clusters: 1,
+            },
+            Problem::ChainTooLong {
+                path: "/LONG.BIN".into(),
+                size: 0,
+                clusters: 2,
+            },
+            Problem::CrossLinked {
+                cluster: a[1],
+                first: "/B.TXT".into(),
+                second: "/DOCS/A.TXT".into(),
+                from: Some(a[0]),
+            },
+            Problem::LostChain {
+                start: b,
+                clusters: 1,
+            },
+            // Second cluster de BAD.TXT, détaché par la coupure
+            Problem::LostChain {
+                start: bad + 1,
+                clusters: 1,
+            },
+            Problem::LostChain {
+                start: 40,
+                clusters: 2,
+            },
+        ];
+        assert_eq!(report.problems, expected);
+        assert!(!report.is_clean());
+
+        assert_eq!(
+            expected[2].to_string(),
+            format!("invalid-cluster path=\"/BAD.TXT\" from={bad} value=0x0")
+        );
+        assert_eq!(
+            expected[6].to_string(),
+            format!("lost-chain start={b} clusters=1")
+        );
+
+        // Un répertoire qui pointe sur son parent ne fait pas boucler le parcours
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/D").unwrap();
+        let d = rw.locate_path("/D").unwrap();
+        rw.set_entry_first_cluster(d.offset, 2).unwrap();
+        let report = rw.as_read().check().unwrap();
+        assert_eq!(
+            report.problems[0],
+            Problem::CrossLinked {
+                cluster: 2,
+                first: "/".into(),
+                second: "/D".into(),
+                from: None,
+            }
+        );
+    }
+
+    #[test]
+    fn repair_dry_run_lists_the_changes_a_real_repair_writes() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.create_dir("/DOCS").unwrap();
+        let mut a_data = vec![1u8; 512];
+        a_data.extend_from_slice(&[2u8; 88]);
+        rw.write_file_by_path("/DOCS/A.TXT", &a_data).unwrap();
+        rw.write_file_by_path("/B.TXT", b"b").unwrap();
+        rw.write_file_by_path("/LOOP.BIN", &[3u8; 1500]).unwrap();
+        rw.write_file_by_path("/SHORT.TXT", b"s").unwrap();
+        rw.write_file_by_path("/LONG.BIN", &[]).unwrap();
+        rw.preallocate("/LONG.BIN", 1024).unwrap();
+
+        let ro = rw.as_read();
+        let a = ro
+            .follow_chain(ro.open_path("/DOCS/A.TXT").unwrap().unwrap().first_cluster)
+            .unwrap();
+        let looped = ro
+            .follow_chain(ro.open_path("/LOOP.BIN").unwrap().unwrap().first_cluster)
+            .unwrap();
+
+        let b_entry = rw.locate_path("/B.TXT").unwrap();
+        rw.set_entry_first_cluster(b_entry.offset, a[1]).unwrap();
+        rw.write_fat_entry_all(looped[2], looped[0]).unwrap();
+        let short = rw.locate_path("/SHORT.TXT").unwrap();
+        rw.write_at(short.offset + 28, &2000u32.to_le_bytes())
+            .unwrap();
+        rw.write_fat_entry_all(40, 41).unwrap();
+        rw.write_fat_entry_all(41, FAT32_EOC).unwrap();
+        drop(rw);
+        disk[FORMATTED_FAT2_SECTOR * 512 + 50 * 4] = 0x07;
+
+        let problems = Fat32::new(&disk).unwrap().check().unwrap().problems;
+        assert_eq!(problems.len(), 7, "{problems:?}");
+
+        // Dry-run : rien n’est écrit, mais les changements sont listés
+        let before = disk.clone();
+        let options = RepairOptions {
+            dry_run: true,
+            trim_reserved: true,
+            ..RepairOptions::default()
+        };
+        let planned = Fat32Mut::new(&mut disk).unwrap().repair(&options).unwrap();
+        assert_eq!(disk, before);
+        assert_eq!(planned.repaired, problems);
+        assert!(planned.remaining.is_empty(), "{:?}", planned.remaining);
+        assert!(planned
+            .changes
+            .windows(2)
+            .all(|w| w[0].offset < w[1].offset));
+        // Seconde FAT resynchronisée : l’octet 0x07 redevient 0x00
+        let fat2 = (FORMATTED_FAT2_SECTOR * 512 + 50 * 4) as u64;
+        let resync = planned.changes.iter().find(|c| c.offset == fat2).unwrap();
+        assert_eq!((resync.old[0], resync.new[0]), (0x07, 0x00));
+        assert_eq!(
+            resync.to_string(),
+            format!("offset={fat2:#x} old=07 new=00")
+        );
+
+        // Appliquer ces changements donne exactement le résultat de la réparation
+        let mut expected = before.clone();
+        for c in &planned.changes {
+            let at = c.offset as usize;
+            assert_eq!(&expected[at..at + c.old.len()], &c.old[..]);
+            expected[at..at + c.new.len()].copy_from_slice(&c.new);
+        }
+        let options = RepairOptions {
+            trim_reserved: true,
+            ..RepairOptions::default()
+        };
+        let done = Fat32Mut::new(&mut disk).unwrap().repair(&options).unwrap();
+        assert_eq!(done.changes, planned.changes);
+        assert_eq!(disk, expected);
+
+        let ro = Fat32::new(&disk).unwrap();
+        assert!(ro.check().unwrap().is_clean());
+        // B.TXT (vu en premier) garde le cluster partagé, A.TXT reçoit une copie
+        assert_eq!(ro.open_path("/B.TXT").unwrap().unwrap().first_cluster, a[1]);
+        assert_eq!(ro.read_file_by_path("/B.TXT").unwrap().unwrap(), vec![2u8]);
+        let a_now = ro.follow_chain(a[0]).unwrap();
+        assert!(a_now.len() == 2 && a_now[1] != a[1]);
+        assert_eq!(
+            ro.read_file_by_path("/DOCS/A.TXT").unwrap().unwrap(),
+            a_data
+        );
+        // Boucle coupée sur son dernier cluster, tailles recalées
+        assert_eq!(ro.follow_chain(looped[0]).unwrap(), looped);
+        assert_eq!(ro.open_path("/SHORT.TXT").unwrap().unwrap().size, 512);
+        assert_eq!(ro.open_path("/LONG.BIN").unwrap().unwrap().first_cluster, 0);
+        assert_eq!(fat_entry_raw(&disk[7 * 512..], 40), 0);
+        assert_eq!(
+            ro.fs_info().unwrap().free_count,
+            Some(ro.free_cluster_count().unwrap())
+        );
+    }
+
+    #[test]
+    fn repair_can_recover_lost_chains_into_found_dir() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.write_file_by_path("/KEEP.TXT", b"keep").unwrap();
+        rw.write_fat_entry_all(40, 41).unwrap();
+        rw.write_fat_entry_all(41, FAT32_EOC).unwrap();
+        let off = rw.as_read().cluster_to_offset(40).unwrap();
+        rw.write_at(off, b"orphelin").unwrap();
+
+        let options = RepairOptions {
+            recover_lost_chains: true,
+            ..RepairOptions::default()
+        };
+        let report = rw.repair(&options).unwrap();
+        assert_eq!(
+            report.repaired,
+            vec![Problem::LostChain {
+                start: 40,
+                clusters: 2
+            }]
+        );
+        assert!(report.remaining.is_empty());
+
+        let ro = rw.as_read();
+        let chk = ro.open_path("/FOUND.000/FILE0000.CHK").unwrap().unwrap();
+        assert_eq!((chk.first_cluster, chk.size), (40, 1024));
+        assert_eq!(&ro.read_file(&chk).unwrap()[..8], b"orphelin");
+        assert!(ro.check().unwrap().is_clean());
+
+        // Une seconde chaîne perdue prend le numéro suivant
+        rw.write_fat_entry_all(45, FAT32_EOC).unwrap();
+        rw.repair(&options).unwrap();
+        let names: Vec<_> = rw
+            .as_read()
+            .list_dir_path("/FOUND.000")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, [".", "..", "FILE0000.CHK", "FILE0001.CHK"]);
+    }
+
+    #[test]
+    fn deleted_files_are_listed_and_undeleted() {
+        let mut disk = build_formatted_image();
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        let content: Vec<u8> = (0..1200u32).map(|i| i as u8).collect();
+        rw.write_file_by_path("/Rapport annuel.txt", &content)
+            .unwrap();
+        rw.write_file_by_path("/XA.TXT", b"xa").unwrap();
+        rw.write_file_by_path("/YA.TXT", b"ya").unwrap();
+        let free = rw.as_read().free_cluster_count().unwrap();
+
+        rw.remove_file("/Rapport annuel.txt").unwrap();
+        rw.remove_file("/XA.TXT").unwrap();
+
+        let deleted = rw.as_read().list_deleted("/").unwrap();
+        assert_eq!(deleted.len(), 2);
+        let rapport = &deleted[0];
+        assert_eq!(rapport.entry.name, "Rapport annuel.txt");
+        assert!(rapport.entry.short_name.starts_with('?'));
+        assert_eq!((rapport.entry.first_cluster, rapport.entry.size), (3, 1200));
+        assert_eq!(rapport.first_char_hint, Some('R'));
+        assert!(rapport.clusters_free);
+        let xa = &deleted[1];
+        assert_eq!(
+            (xa.entry.name.as_str(), xa.first_char_hint),
+            ("?A.TXT", None)
+        );
+
+        assert_eq!(rw.undelete(xa, '*').unwrap_err(), FatError::InvalidName);
+        assert_eq!(rw.undelete(xa, 'y').unwrap_err(), FatError::AlreadyExists);
+
+        rw.undelete(rapport, 'R').unwrap();
+        assert_eq!(
+            rw.undelete(rapport, 'R').unwrap_err(),
+            FatError::PathNotFound
+        );
+        let ro = rw.as_read();
+        let e = ro.open_path("/rapport ANNUEL.txt").unwrap().unwrap();
+        assert_eq!(ro.read_file(&e).unwrap(), content);
+        assert_eq!(ro.free_cluster_count().unwrap(), free + 1);
+        assert!(ro.check().unwrap().is_clean());
+
+        // Cluster de nouveau alloué : la récupération est refusée
+        rw.write_fat_entry_all(6, FAT32_EOC).unwrap();
+        let xa = rw.as_read().list_deleted("/").unwrap().remove(0);
+        assert_eq!(xa.entry.first_cluster, 6);
+        assert!(!xa.clusters_free);
+        assert_eq!(rw.undelete(&xa, 'X').unwrap_err(), FatError::InvalidCluster);
+    }
+
+    #[test]
+    fn format_creates_an_empty_volume_ready_for_writes() {
+        let mut disk = vec![0xAAu8; 48 * 1024 * 1024];
+        let options = FormatOptions {
+            label: "Données".into(),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            Fat32Mut::format(&mut disk[..], &options).unwrap_err(),
+            FatError::InvalidName
+        );
+
+        let options = FormatOptions {
+            size: Some(40 * 1024 * 1024),
+            label: "backup".into(),
+            serial: 0x1234_ABCD,
+            ..FormatOptions::default()
+        };
+        let mut rw = Fat32Mut::format(&mut disk[..], &options).unwrap();
+        let ro = rw.as_read();
+        assert_eq!(ro.fat_type(), FatType::Fat32);
+        assert_eq!(ro.cluster_size(), 512);
+        // 81 920 secteurs - 32 réservés - 2 × 640 de FAT
+        let clusters = 81_920 - 32 - 2 * 640;
+        assert_eq!(ro.max_cluster_number().unwrap(), clusters + 1);
+        assert_eq!(ro.free_cluster_count().unwrap(), clusters - 1);
+        assert_eq!(ro.fs_info().unwrap().free_count, Some(clusters - 1));
+        assert!(ro.list_root().unwrap().is_empty());
+        assert!(ro.check().unwrap().is_clean());
+
+        rw.write_file_by_path("/Premier fichier.txt", b"bonjour")
+            .unwrap();
+        rw.create_dir("/DOCS").unwrap();
+        assert!(rw.as_read().check().unwrap().is_clean());
+        drop(rw);
+
+        assert_eq!(disk[0..512], disk[6 * 512..7 * 512]);
+        assert_eq!(&disk[67..71], &0x1234_ABCDu32.to_le_bytes());
+        assert_eq!(&disk[71..82], b"BACKUP     ");
+        // Entrée de nom de volume en tête de la racine
+        let root = (32 + 2 * 640) * 512;
+        assert_eq!(
+            (&disk[root..root + 11], disk[root + 11]),
+            (&b"BACKUP     "[..], 0x08)
+        );
+        // La zone data n’est pas effacée au-delà de la racine
+        assert_eq!(disk[root + 512 * 10], 0xAA);
+        assert_eq!(disk[40 * 1024 * 1024], 0xAA);
+
+        let cases = [
+            (
+                FormatOptions {
+                    cluster_size: Some(3000),
+                    ..FormatOptions::default()
+                },
+                FatError::InvalidSectorsPerCluster,
+            ),
+            (
+                FormatOptions {
+                    num_fats: 0,
+                    ..FormatOptions::default()
+                },
+                FatError::InvalidFatCount,
+            ),
+            (
+                FormatOptions {
+                    size: Some(64 * 1024 * 1024),
+                    ..FormatOptions::default()
+                },
+                FatError::VolumeExceedsDevice,
+            ),
+            (
+                FormatOptions {
+                    size: Some(32 * 512),
+                    ..FormatOptions::default()
+                },
+                FatError::InvalidTotalSectors,
+            ),
+            // Trop petit pour 65 525 clusters, même de 512 octets
+            (
+                FormatOptions {
+                    size: Some(8 * 1024 * 1024),
+                    ..FormatOptions::default()
+                },
+                FatError::ClusterCountMismatch,
+            ),
+            // Taille de cluster imposée : pas réduite
+            (
+                FormatOptions {
+                    size: Some(40 * 1024 * 1024),
+                    cluster_size: Some(1024),
+                    ..FormatOptions::default()
+                },
+                FatError::ClusterCountMismatch,
+            ),
+        ];
+        for (options, err) in cases {
+            assert_eq!(Fat32Mut::format(&mut disk[..], &options).unwrap_err(), err);
+        }
+    }
+
+    #[test]
+    fn chains_longer_than_4096_clusters_are_read_and_freed_whole() {
+        let mut disk = vec![0u8; 40 * 1024 * 1024];
+        let mut rw = Fat32Mut::format(&mut disk[..], &FormatOptions::default()).unwrap();
+        let free = rw.as_read().free_cluster_count().unwrap();
+
+        // 5000 clusters de 512 octets : au-delà de l’ancien plafond de 4096
+        let big: Vec<u8> = (0..5000 * 512u32).map(|i| (i / 512) as u8).collect();
+        rw.write_file_by_path("/BIG.BIN", &big).unwrap();
+        let ro = rw.as_read();
+        let e = ro.open_path("/BIG.BIN").unwrap().unwrap();
+        assert_eq!(ro.follow_chain(e.first_cluster).unwrap().len(), 5000);
+        assert_eq!(ro.read_file(&e).unwrap(), big);
+        assert!(ro.check().unwrap().is_clean());
+
+        rw.remove_file("/BIG.BIN").unwrap();
+        assert_eq!(rw.as_read().free_cluster_count().unwrap(), free);
+
+        // Boucle : erreur explicite au lieu d’un contenu tronqué ou répété
+        rw.write_file_by_path("/LOOP.BIN", &[1u8; 1536]).unwrap();
+        let e = rw.as_read().open_path("/LOOP.BIN").unwrap().unwrap();
+        let chain = rw.as_read().follow_chain(e.first_cluster).unwrap();
+        rw.write_fat_entry_all(chain[2], chain[1]).unwrap();
+        assert_eq!(
+            rw.as_read().read_file(&e).unwrap_err(),
+            FatError::ClusterLoop
+        );
+        assert_eq!(
+            rw.remove_file("/LOOP.BIN").unwrap_err(),
+            FatError::ClusterLoop
+        );
+
+        // Chaîne plus courte que la taille annoncée
+        rw.write_fat_entry_all(chain[1], FAT32_EOC).unwrap();
+        assert_eq!(
+            rw.as_read().read_file(&e).unwrap_err(),
+            FatError::InvalidCluster
+        );
+    }
+
+    #[test]
+    fn ext_flags_select_the_active_fat_and_disable_mirroring() {
+        let mut disk = build_formatted_image();
+        // ext_flags : miroir désactivé, FAT active = 1
+        disk[40..42].copy_from_slice(&0x0081u16.to_le_bytes());
+        let fat0 = disk[8 * 512..9 * 512].to_vec();
+
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            assert_eq!(rw.as_read().active_fat(), Some(1));
+            rw.write_file_by_path("/DATA.BIN", &[7u8; 1500]).unwrap();
+
+            let ro = rw.as_read();
+            assert_eq!(
+                ro.read_file_by_path("/DATA.BIN").unwrap().unwrap(),
+                vec![7u8; 1500]
+            );
+            assert!(ro.check().unwrap().is_clean());
+            assert_eq!(
+                ro.compare_fats().unwrap(),
+                vec![FatDivergence {
+                    copy: 0,
+                    first: 3,
+                    last: 5
+                }]
+            );
+        }
+        // Seule la FAT active a été écrite
+        assert_eq!(disk[8 * 512..9 * 512], fat0[..]);
+        assert_eq!(
+            fat_entry_raw(&disk[(FORMATTED_FAT2_SECTOR - 1) * 512..], 3),
+            4
+        );
+
+        // Miroir réactivé : la première FAT redevient la référence
+        disk[40..42].copy_from_slice(&0u16.to_le_bytes());
+        let ro = Fat32::new(&disk).unwrap();
+        assert_eq!(ro.active_fat(), None);
+        let diff = ro.compare_fats().unwrap();
+        assert_eq!(
+            diff,
+            vec![FatDivergence {
+                copy: 1,
+                first: 3,
+                last: 5
+            }]
+        );
+        assert_eq!(diff[0].to_string(), "fat-divergence copy=1 first=3 last=5");
+        assert_eq!(
+            ro.check().unwrap().problems[0],
+            Problem::FatMismatch {
+                copy: 1,
+                cluster: 3,
+                entries: 3
+            }
+        );
+
+        disk[40] = 0x82;
+        assert_eq!(Fat32::new(&disk).unwrap_err(), FatError::InvalidActiveFat);
+    }
+
+    #[test]
+    fn backup_boot_sector_and_fs_info_are_used_when_the_primary_is_damaged() {
+        let mut disk = build_formatted_image();
+        // Copie de secours de FSInfo juste après celle du secteur de boot
+        let fs_info = disk[512..1024].to_vec();
+        disk[7 * 512..8 * 512].copy_from_slice(&fs_info);
+        let boot = disk[0..512].to_vec();
+
+        // Secteur 0 et FSInfo principal effacés
+        disk[0..1024].fill(0);
+        assert_eq!(
+            Fat32::new(&disk).unwrap_err(),
+            FatError::MissingBootSignature
+        );
+        {
+            let ro = Fat32::open_with_backup(&disk[..]).unwrap();
+            assert_eq!(ro.boot_source(), BootSector::Backup);
+            assert!(ro.list_root().unwrap().is_empty());
+            assert_eq!(ro.fs_info().unwrap().free_count, Some(65_533));
+        }
+
+        {
+            let mut rw = Fat32Mut::open_with_backup(&mut disk[..]).unwrap();
+            rw.restore_boot_sector(BootSector::Backup).unwrap();
+            rw.write_file_by_path("/A.TXT", b"a").unwrap();
+        }
+        assert_eq!(disk[0..512], boot[..]);
+        let ro = Fat32::new(&disk).unwrap();
+        assert_eq!(ro.boot_source(), BootSector::Primary);
+        // Le FSInfo principal, restauré, est celui qui a été mis à jour
+        assert_eq!(ro.fs_info().unwrap().free_count, Some(65_532));
+
+        // Dans l’autre sens : la copie de secours reprend le secteur 0
+        disk[6 * 512 + 510] = 0;
+        disk[0..4].copy_from_slice(&[0xEB, 0x58, 0x90, b'X']);
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.restore_boot_sector(BootSector::Primary).unwrap();
+        }
+        assert_eq!(disk[0..512], disk[6 * 512..7 * 512]);
+        assert_eq!(disk[512..1024], disk[7 * 512..8 * 512]);
+
+        // Source invalide : rien n’est recopié
+        disk[6 * 512 + 510] = 0;
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        assert_eq!(
+            rw.restore_boot_sector(BootSector::Backup).unwrap_err(),
+            FatError::MissingBootSignature
+        );
+
+        let mut fat16 = build_legacy_image(FatType::Fat16);
+        let mut rw = Fat32Mut::new(&mut fat16).unwrap();
+        assert_eq!(
+            rw.restore_boot_sector(BootSector::Primary).unwrap_err(),
+            FatError::MissingBackupBootSector
+        );
+    }
+
+    #[test]
+    fn backup_fs_info_follows_the_primary_counters() {
+        let mut disk = build_formatted_image();
+        let fs_info = disk[512..1024].to_vec();
+        disk[7 * 512..8 * 512].copy_from_slice(&fs_info);
+
+        let free = {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/A.BIN", &[7u8; 100 * 1024]).unwrap();
+            rw.as_read().free_cluster_count().unwrap()
+        };
+        assert_eq!(free, 65_533 - 200);
+        // Compteur et indice identiques dans les deux copies
+        assert_eq!(
+            disk[512 + 488..512 + 496],
+            disk[7 * 512 + 488..7 * 512 + 496]
+        );
+
+        // FSInfo principal abîmé : la copie de secours donne le bon compte
+        disk[512..1024].fill(0);
+        let mut rw = Fat32Mut::open_with_backup(&mut disk[..]).unwrap();
+        assert_eq!(rw.as_read().fs_info().unwrap().free_count, Some(free));
+        rw.remove_file("/A.BIN").unwrap();
+        assert_eq!(rw.as_read().fs_info().unwrap().free_count, Some(65_533));
+    }
+
+    #[test]
+    fn volume_label_is_kept_in_sync_in_the_bpb_and_the_root() {
+        let mut disk = build_formatted_image();
+        let info = Fat32::new(&disk).unwrap().volume_info().unwrap();
+        assert_eq!(
+            info,
+            VolumeInfo {
+                oem_name: "MSWIN4.1".into(),
+                serial: Some(0x1234_ABCD),
+                bpb_label: None,
+                root_label: None,
+                fs_type: Some("FAT32".into()),
+            }
+        );
+        assert_eq!(info.label(), None);
+
+        let root = FORMATTED_DATA_SECTOR * 512;
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            for bad in ["NOM TROP LONG", "A*B", " DEBUT"] {
+                assert_eq!(rw.set_label(bad).unwrap_err(), FatError::InvalidName);
+            }
+            rw.set_label("Mes docs").unwrap();
+            rw.write_file_by_path("/A.TXT", b"a").unwrap();
+            // Le second nom réutilise l’entrée Volume ID existante
+            rw.set_label("data").unwrap();
+
+            let ro = rw.as_read();
+            let info = ro.volume_info().unwrap();
+            assert_eq!(info.bpb_label.as_deref(), Some("DATA"));
+            assert_eq!(info.root_label.as_deref(), Some("DATA"));
+            assert_eq!(info.label(), Some("DATA"));
+            // L’entrée Volume ID n’apparaît pas dans les listages
+            let names: Vec<String> = ro
+                .list_root()
+                .unwrap()
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+            assert_eq!(names, ["A.TXT"]);
+            assert!(ro.check().unwrap().is_clean());
+        }
+        assert_eq!(&disk[71..82], b"DATA       ");
+        assert_eq!(&disk[6 * 512 + 71..6 * 512 + 82], b"DATA       ");
+        assert_eq!(
+            (&disk[root..root + 11], disk[root + 11]),
+            (&b"DATA       "[..], 0x08)
+        );
+        assert_eq!(&disk[root + 32..root + 43], b"A       TXT");
+
+        // Un nom vide efface les deux
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.set_label("").unwrap();
+            let info = rw.as_read().volume_info().unwrap();
+            assert_eq!((info.bpb_label, info.root_label), (None, None));
+        }
+        assert_eq!(&disk[71..82], b"NO NAME    ");
+        assert_eq!(disk[root], 0xE5);
+
+        // FAT16 : BPB étendu à l’offset 36, racine fixe
+        let mut disk = build_legacy_image(FatType::Fat16);
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        rw.set_label("CLE USB").unwrap();
+        let info = rw.as_read().volume_info().unwrap();
+        assert_eq!(info.serial, Some(0x1234_ABCD));
+        assert_eq!(info.fs_type.as_deref(), Some("FAT16"));
+        assert_eq!(info.bpb_label.as_deref(), Some("CLE USB"));
+        assert_eq!(info.root_label.as_deref(), Some("CLE USB"));
+        drop(rw);
+        assert_eq!(&disk[43..54], b"CLE USB    ");
+    }
+
+    #[test]
+    fn volume_serial_is_written_to_both_boot_sectors() {
+        let mut disk = build_formatted_image();
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.set_serial(0xCAFE_F00D).unwrap();
+            let info = rw.as_read().volume_info().unwrap();
+            assert_eq!(info.serial, Some(0xCAFE_F00D));
+            assert!(!rw.as_read().is_dirty().unwrap());
+        }
+        assert_eq!(disk[67..71], 0xCAFE_F00Du32.to_le_bytes());
+        assert_eq!(disk[6 * 512 + 67..6 * 512 + 71], disk[67..71]);
+
+        // FAT16 : BPB étendu à l’offset 36, pas de copie de secours
+        let mut fat16 = build_legacy_image(FatType::Fat16);
+        Fat32Mut::new(&mut fat16)
+            .unwrap()
+            .set_serial(0x0BAD_BEEF)
+            .unwrap();
+        assert_eq!(fat16[39..43], 0x0BAD_BEEFu32.to_le_bytes());
+
+        // Sans BPB étendu, le numéro n’a pas de champ : rien n’est écrit
+        fat16[38] = 0;
+        let before = fat16.clone();
+        let mut rw = Fat32Mut::new(&mut fat16).unwrap();
+        assert_eq!(rw.set_serial(1).unwrap_err(), FatError::MissingExtendedBpb);
+        assert_eq!(rw.as_read().volume_info().unwrap().serial, None);
+        drop(rw);
+        assert_eq!(fat16, before);
+    }
+
+    #[test]
+    fn writes_mark_the_volume_dirty_until_they_complete() {
+        use crate::io::Write;
+
+        let mut disk = build_formatted_image();
+        let fat1 = |disk: &[u8], copy: usize| {
+            fat_entry_raw(&disk[(7 + copy * FAT32_FAT_SECTORS) * 512..], 1)
+        };
+        {
+            let ro = Fat32::new(&disk).unwrap();
+            assert!(!ro.is_dirty().unwrap());
+            assert!(!ro.has_hard_error().unwrap());
+        }
+
+        // Opération terminée : le volume redevient propre
+        Fat32Mut::new(&mut disk)
+            .unwrap()
+            .write_file_by_path("/A.TXT", b"a")
+            .unwrap();
+        assert_eq!((fat1(&disk, 0), fat1(&disk, 1)), (0x0FFF_FFFF, 0x0FFF_FFFF));
+
+        // Écriture interrompue (FileWriter jamais vidé) : le volume reste sale
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.create_file("/LOG.TXT").unwrap();
+            w.write_all(b"perdu").unwrap();
+            core::mem::forget(w);
+            assert!(rw.as_read().is_dirty().unwrap());
+        }
+        assert_eq!((fat1(&disk, 0), fat1(&disk, 1)), (0x07FF_FFFF, 0x07FF_FFFF));
+
+        // Déjà sale à l’ouverture : une écriture réussie ne le remet pas propre
+        Fat32Mut::new(&mut disk)
+            .unwrap()
+            .write_file_by_path("/B.TXT", b"b")
+            .unwrap();
+        assert!(Fat32::new(&disk).unwrap().is_dirty().unwrap());
+
+        // C’est `repair` qui le fait, une fois le volume cohérent
+        let before = disk.clone();
+        let options = RepairOptions {
+            dry_run: true,
+            ..RepairOptions::default()
+        };
+        let planned = Fat32Mut::new(&mut disk).unwrap().repair(&options).unwrap();
+        assert_eq!(disk, before);
+        // Octet de poids fort de FAT[1], dans chaque copie
+        for fat in [8, FORMATTED_FAT2_SECTOR as u64] {
+            let flag = planned
+                .changes
+                .iter()
+                .find(|c| c.offset == fat * 512 + 7)
+                .unwrap();
+            assert_eq!((&flag.old[..], &flag.new[..]), (&[0x07][..], &[0x0F][..]));
+        }
+
+        let report = Fat32Mut::new(&mut disk)
+            .unwrap()
+            .repair(&RepairOptions::default())
+            .unwrap();
+        assert!(report.remaining.is_empty(), "{:?}", report.remaining);
+        assert!(!Fat32::new(&disk).unwrap().is_dirty().unwrap());
+        assert_eq!((fat1(&disk, 0), fat1(&disk, 1)), (0x0FFF_FFFF, 0x0FFF_FFFF));
+
+        // Bit “pas d’erreur” à 0 : erreur matérielle signalée
+        disk[8 * 512 + 7] = 0x0B;
+        assert!(Fat32::new(&disk).unwrap().has_hard_error().unwrap());
+
+        // FAT16 : bit 15 de FAT[1] ; FAT12 : pas de drapeau
+        let mut disk = build_legacy_image(FatType::Fat16);
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.create_file("/LOG.TXT").unwrap();
+            w.write_all(b"perdu").unwrap();
+            core::mem::forget(w);
+        }
+        assert_eq!(disk[512 + 2..512 + 4], [0xFF, 0x7F]);
+        assert!(Fat32::new(&disk).unwrap().is_dirty().unwrap());
+
+        let mut disk = build_legacy_image(FatType::Fat12);
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.create_file("/LOG.TXT").unwrap();
+            w.write_all(b"perdu").unwrap();
+            core::mem::forget(w);
+        }
+        assert_eq!(disk[512..515], [0xF0, 0xFF, 0xFF]);
+        assert!(!Fat32::new(&disk).unwrap().is_dirty().unwrap());
+    }
+
+    #[test]
+    fn a_failed_operation_leaves_the_volume_dirty() {
+        use crate::io::{Seek, SeekFrom, Write};
+
+        let mut disk = build_formatted_image();
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            rw.write_file_by_path("/KEEP.BIN", b"keep").unwrap();
+
+            // L’ancienne chaîne est libérée avant que l’allocation échoue :
+            // l’entrée pointe sur un cluster libre
+            let too_big = vec![0u8; 65_534 * 512];
+            assert_eq!(
+                rw.write_file_by_path("/KEEP.BIN", &too_big).unwrap_err(),
+                FatError::NoSpaceLeft
+            );
+            assert!(rw.as_read().is_dirty().unwrap());
+
+            // Une opération réussie ensuite ne remet pas le volume propre
+            rw.write_file_by_path("/X.TXT", b"x").unwrap();
+            assert!(rw.as_read().is_dirty().unwrap());
+        }
+        assert!(Fat32::new(&disk).unwrap().is_dirty().unwrap());
+
+        // FileWriter : le `flush` du drop ne remet pas propre après une erreur
+        let mut disk = build_formatted_image();
+        Fat32Mut::new(&mut disk)
+            .unwrap()
+            .write_file_by_path("/LOG.BIN", &[1u8; 600])
+            .unwrap();
+        // Chaîne 3 -> 4 coupée dans les deux FAT
+        for fat in [8, FORMATTED_FAT2_SECTOR] {
+            disk[fat * 512 + 3 * 4..fat * 512 + 4 * 4].fill(0);
+        }
+        {
+            let mut rw = Fat32Mut::new(&mut disk).unwrap();
+            let mut w = rw.open_file_mut("/LOG.BIN").unwrap();
+            w.write_all(b"debut").unwrap();
+            w.seek(SeekFrom::Start(600)).unwrap();
+            assert_eq!(w.write(b"fin").unwrap_err(), FatError::InvalidCluster);
+        }
+        assert!(Fat32::new(&disk).unwrap().is_dirty().unwrap());
+
+        // `create_dir` qui échoue après avoir alloué son cluster : racine
+        // fixe FAT16 pleine
+        let mut disk = build_legacy_image(FatType::Fat16);
+        let mut rw = Fat32Mut::new(&mut disk).unwrap();
+        for i in 0..512 {
+            rw.write_file_by_path(&format!("/F{i}.TXT"), &[]).unwrap();
+        }
+        assert!(!rw.as_read().is_dirty().unwrap());
+        assert_eq!(rw.create_dir("/SUB").unwrap_err(), FatError::NoSpaceLeft);
+        assert!(rw.as_read().is_dirty().unwrap());
+    }
 }
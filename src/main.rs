@@ -2,34 +2,111 @@
 //!
 //! Cette CLI s’appuie sur la bibliothèque `fat32_parser`:
 //! - lecture: `ls`, `cat`, navigation avec `cd` et `pwd`
-//! - écriture simple: `put` pour créer/écraser un fichier 8.3
+//! - écriture simple: `put` pour créer/écraser un fichier (noms longs acceptés),
+//!   `mkdir` pour créer un répertoire, `rm`/`rmdir` pour supprimer
 //! - mode non interactif via options ou mode shell interactif
-//! 
+//! - `--partition N` pour une image de disque complet (table MBR ou GPT)
+//! - `--fsck` pour vérifier la cohérence du volume (une ligne par problème),
+//!   `--repair` pour la rétablir (`--dry-run` : liste les octets sans écrire)
+//! - `--lsdel` pour lister les entrées supprimées d’un répertoire,
+//!   `--undelete` pour en restaurer une
+//! - `mkfs` pour formater une image (ou une partition) en FAT32
+//! - si le secteur de boot principal est abîmé, la copie de secours est
+//!   utilisée (avec un avertissement) ; `--restore-boot` réécrit une copie
+//!   à partir de l’autre
+//! - un volume marqué sale (écriture précédente interrompue) est signalé à
+//!   l’ouverture ; `--repair` le remet propre
+//! - `--info` pour afficher l’identité du volume (OEM, série, nom),
+//!   `--set-label` pour changer son nom
+//!
 //! Exemple rapide:
 //! ```
 //! fat32_cli --file disk.img --ls /
 //! fat32_cli --file disk.img --cat /HELLO.TXT
 //! fat32_cli --file disk.img --put /NEW.TXT ./local.txt
+//! fat32_cli --file disk.img --mkdir /DOCS
+//! fat32_cli --file sdcard.img --partition 1 --ls /
+//! fat32_cli --file disk.img --fsck
+//! fat32_cli --file disk.img --repair --dry-run
+//! fat32_cli --file disk.img --lsdel /DOCS
+//! fat32_cli --file disk.img --undelete /DOCS 2 --first-char R
+//! fat32_cli mkfs --file new.img --size 64M --label DATA
+//! fat32_cli --file disk.img --restore-boot backup
+//! fat32_cli --file disk.img --set-label DATA
 //! ```
-use fat32_parser::{Fat32, Fat32Mut};
+use fat32_parser::{
+    BlockDevice, BootSector, Fat32, Fat32Mut, FatError, FatTimestamp, FormatOptions,
+    PartitionDevice, Problem, RepairOptions,
+};
 use std::env;
-use std::fs;
+use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Affiche l’usage de la CLI avec les commandes disponibles.
 fn print_usage() {
     eprintln!(
         "Usage:
-  fat32_cli --file <disk.img> [--ls <path>] [--cat <path>] [--put <fat_path> <host_file>]
+  fat32_cli --file <disk.img> [--partition <N>]
+            [--ls <path>] [--cat <path>] [--put <fat_path> <host_file>]
+            [--mkdir <path>] [--rm <path>] [--rmdir <path>] [--fsck]
+            [--repair [--dry-run] [--recover-lost] [--trim-reserved]]
+            [--lsdel <dir>] [--undelete <dir> <N> [--first-char <c>]]
+            [--restore-boot <backup|primary>] [--info] [--set-label <nom>]
+  fat32_cli mkfs --file <disk.img> [--partition <N>] [--size <taille>]
+            [--cluster-size <octets>] [--fats <N>] [--label <nom>]
+            [--serial <XXXX-XXXX>]
+
+  --partition N : volume de la partition N du disque
+                  (MBR : 1 à 4 primaires, 5 et plus logiques ;
+                   GPT : numéro d'entrée dans la table)
+  --fsck        : vérifie le volume sans le modifier ; une ligne par
+                  problème, code de sortie 1 si le volume est incohérent
+                  (une réserve de clusters au-delà de la taille d'un
+                  fichier est listée mais n'est pas une incohérence)
+  --repair      : corrige les problèmes trouvés par --fsck et affiche les
+                  octets modifiés ; avec --dry-run, n'écrit rien ;
+                  avec --recover-lost, les chaînes perdues sont rangées
+                  dans /FOUND.000/FILEnnnn.CHK au lieu d'être libérées ;
+                  un volume marqué sale redevient propre ; les
+                  clusters réservés au-delà de la taille d'un fichier
+                  sont gardés, sauf avec --trim-reserved
+  --lsdel       : liste les entrées supprimées d'un répertoire, numérotées
+                  à partir de 1, avec l'état de leurs clusters
+  --undelete    : restaure l'entrée N de --lsdel ; --first-char donne le
+                  premier caractère du nom court (par défaut celui déduit
+                  du nom long)
+  --restore-boot: recopie le secteur de boot (et FSInfo) : 'backup'
+                  répare le secteur 0 depuis la copie de secours, 'primary'
+                  remet la copie de secours à jour depuis le secteur 0
+  --info        : affiche le nom OEM, le numéro de série, le type, le nom
+                  du volume (BPB et racine) et son état (propre ou sale)
+  --set-label   : change le nom du volume (BPB et racine) ; '' l'efface
+  mkfs          : formate en FAT32 ; l'image est créée (ou agrandie) à
+                  --size (suffixes K, M, G acceptés) ; sans --size, tout
+                  le fichier ou toute la partition est utilisé ; la taille
+                  de cluster est choisie d'après la taille du volume si
+                  --cluster-size est absent ; un volume FAT32 fait au
+                  moins 65525 clusters, soit 33M environ
 
 Exemples:
   fat32_cli --file disk.img --ls /
   fat32_cli --file disk.img --cat /HELLO.TXT
   fat32_cli --file disk.img --put /NEW.TXT ./local.txt
+  fat32_cli --file disk.img --mkdir /DOCS
+  fat32_cli --file sdcard.img --partition 1 --ls /
+  fat32_cli --file disk.img --fsck
+  fat32_cli --file disk.img --repair --dry-run
+  fat32_cli --file disk.img --lsdel /DOCS
+  fat32_cli --file disk.img --undelete /DOCS 2 --first-char R
+  fat32_cli mkfs --file new.img --size 64M --label DATA
+  fat32_cli --file disk.img --restore-boot backup
+  fat32_cli --file disk.img --set-label DATA
 
 Mode shell:
   fat32_cli --file disk.img
-  (puis: ls, cd, cat, put, pwd, help, exit)"
+  (puis: ls, cd, cat, put, mkdir, rm, rmdir, fsck, repair, lsdel, undelete,
+   info, label, pwd, help, exit)"
     );
 }
 
@@ -41,6 +118,18 @@ fn print_shell_help() {
   cat <path>           - lire un fichier
   cd [path]            - changer de répertoire courant
   put <fat_path> <src> - écrire un fichier dans l'image (persistant)
+  mkdir <path>         - créer un répertoire (persistant)
+  rm <path>            - supprimer un fichier (persistant)
+  rmdir <path>         - supprimer un répertoire vide (persistant)
+  fsck                 - vérifier la cohérence du volume
+  repair [--dry-run]   - corriger le volume (ou lister les octets à changer)
+  lsdel [path]         - lister les entrées supprimées d'un répertoire
+  undelete <path> <N> [c]
+                       - restaurer l'entrée N de lsdel, premier caractère c
+                         (persistant)
+  info                 - afficher l'identité du volume
+  label [nom]          - changer le nom du volume, sans nom l'effacer
+                         (persistant)
   pwd                  - afficher le répertoire courant
   help                 - cette aide
   exit                 - quitter"
@@ -48,19 +137,32 @@ fn print_shell_help() {
 }
 
 /// Point d’entrée de la CLI: parse les arguments,
-/// ouvre l’image en mémoire, puis exécute la commande
+/// ouvre le fichier image, puis exécute la commande
 /// demandée ou bascule en mode shell interactif.
 fn main() {
     let mut args = env::args().skip(1);
 
     let mut dump_path: Option<String> = None;
+    let mut partition: Option<usize> = None;
     let mut command: Option<String> = None;
     let mut target_a: Option<String> = None;
     let mut target_b: Option<String> = None;
+    let mut repair = RepairOptions::default();
+    let mut first_char: Option<char> = None;
+    let mut format = FormatOptions::default();
+    let mut serial: Option<u32> = None;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--file" | "-f" => dump_path = args.next(),
+            "--partition" | "-p" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => partition = Some(n),
+                None => {
+                    eprintln!("--partition nécessite un numéro (1, 2, ...)");
+                    print_usage();
+                    return;
+                }
+            },
             "--ls" => {
                 command = Some("ls".to_string());
                 target_a = args.next();
@@ -74,6 +176,74 @@ fn main() {
                 target_a = args.next();
                 target_b = args.next();
             }
+            "--fsck" | "--repair" => command = Some(arg.trim_start_matches('-').to_string()),
+            "--dry-run" => repair.dry_run = true,
+            "--recover-lost" => repair.recover_lost_chains = true,
+            "--trim-reserved" => repair.trim_reserved = true,
+            "--lsdel" => {
+                command = Some("lsdel".to_string());
+                target_a = args.next();
+            }
+            "--undelete" => {
+                command = Some("undelete".to_string());
+                target_a = args.next();
+                target_b = args.next();
+            }
+            "--first-char" => match args.next().and_then(|c| single_char(&c)) {
+                Some(c) => first_char = Some(c),
+                None => {
+                    eprintln!("--first-char nécessite un seul caractère");
+                    print_usage();
+                    return;
+                }
+            },
+            "--mkdir" | "--rm" | "--rmdir" => {
+                command = Some(arg.trim_start_matches('-').to_string());
+                target_a = args.next();
+            }
+            "--restore-boot" => {
+                command = Some("restore-boot".to_string());
+                target_a = args.next();
+            }
+            "--info" => command = Some("info".to_string()),
+            "--set-label" => {
+                command = Some("set-label".to_string());
+                target_a = args.next();
+            }
+            "mkfs" | "--mkfs" => command = Some("mkfs".to_string()),
+            "--size" => match args.next().and_then(|s| parse_size(&s)) {
+                Some(n) => format.size = Some(n),
+                None => {
+                    eprintln!("--size nécessite une taille (ex: 64M, 1G, 1048576)");
+                    print_usage();
+                    return;
+                }
+            },
+            "--cluster-size" => match args.next().and_then(|s| parse_size(&s)) {
+                Some(n) => format.cluster_size = u32::try_from(n).ok(),
+                None => {
+                    eprintln!("--cluster-size nécessite une taille en octets (ex: 4096, 4K)");
+                    print_usage();
+                    return;
+                }
+            },
+            "--fats" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => format.num_fats = n,
+                None => {
+                    eprintln!("--fats nécessite un nombre (1 ou 2)");
+                    print_usage();
+                    return;
+                }
+            },
+            "--label" => format.label = args.next().unwrap_or_default(),
+            "--serial" => match args.next().and_then(|s| parse_serial(&s)) {
+                Some(n) => serial = Some(n),
+                None => {
+                    eprintln!("--serial nécessite un numéro hexadécimal (ex: 1234-ABCD)");
+                    print_usage();
+                    return;
+                }
+            },
             _ => {
                 eprintln!("Argument inconnu : {arg}");
                 print_usage();
@@ -90,17 +260,37 @@ fn main() {
         }
     };
 
-    let mut data = match fs::read(&dump_path) {
-        Ok(v) => v,
+    // mkfs peut créer l’image : on ne l’ouvre pas comme les autres commandes
+    if command.as_deref() == Some("mkfs") {
+        format.serial = serial.unwrap_or_else(default_serial);
+        match run_mkfs(&dump_path, partition, &format) {
+            Ok(msg) => println!("{msg}"),
+            Err(msg) => {
+                eprintln!("{msg}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // L’image n’est jamais chargée en entier : la lib lit/écrit les secteurs
+    // à la demande directement dans le fichier.
+    let mut image = match open_image(&dump_path) {
+        Ok(file) => Image { file, partition },
         Err(e) => {
-            eprintln!("Impossible de lire {dump_path}: {e}");
+            eprintln!("Impossible d'ouvrir {dump_path}: {e}");
             return;
         }
     };
+    // Une seule fois, même en mode shell ; si le volume ne s’ouvre pas, la
+    // commande affichera l’erreur
+    if let Ok(fs) = image.read() {
+        warn_on_open(&fs);
+    }
 
     match command.as_deref() {
         Some("ls") => {
-            let ro = match Fat32::new(&data) {
+            let ro = match image.read() {
                 Ok(fs) => fs,
                 Err(e) => {
                     eprintln!("Erreur FAT32: {e:?}");
@@ -115,7 +305,7 @@ fn main() {
             run_ls(&ro, &path);
         }
         Some("cat") => {
-            let ro = match Fat32::new(&data) {
+            let ro = match image.read() {
                 Ok(fs) => fs,
                 Err(e) => {
                     eprintln!("Erreur FAT32: {e:?}");
@@ -152,41 +342,118 @@ fn main() {
                 }
             };
 
-            let content = match fs::read(&src) {
-                Ok(v) => v,
-                Err(e) => {
-                    eprintln!("Impossible de lire {src}: {e}");
+            match run_put(&mut image, &fat_path, &src) {
+                Ok(msg) => println!("{msg}"),
+                Err(msg) => eprintln!("{msg}"),
+            }
+        }
+        Some("restore-boot") => {
+            let from = match target_a.as_deref() {
+                Some("backup") => BootSector::Backup,
+                Some("primary") => BootSector::Primary,
+                _ => {
+                    eprintln!("--restore-boot nécessite 'backup' ou 'primary'");
+                    print_usage();
                     return;
                 }
             };
 
-            {
-                let mut rw = match Fat32Mut::new(&mut data) {
-                    Ok(fs) => fs,
-                    Err(e) => {
-                        eprintln!("Erreur FAT32: {e:?}");
-                        return;
-                    }
-                };
-
-                if let Err(e) = rw.write_file_by_path(&fat_path, &content) {
-                    eprintln!("Erreur put {fat_path}: {e:?}");
+            match run_restore_boot(&mut image, from) {
+                Ok(msg) => println!("{msg}"),
+                Err(msg) => eprintln!("{msg}"),
+            }
+        }
+        Some(cmd @ ("mkdir" | "rm" | "rmdir")) => {
+            let path = match target_a {
+                Some(p) => resolve_path("/", &p),
+                None => {
+                    eprintln!("--{cmd} nécessite un chemin");
+                    print_usage();
                     return;
                 }
+            };
+
+            match run_path_edit(&mut image, cmd, &path) {
+                Ok(msg) => println!("{msg}"),
+                Err(msg) => eprintln!("{msg}"),
+            }
+        }
+        Some("fsck") => {
+            let ro = match image.read() {
+                Ok(fs) => fs,
+                Err(e) => {
+                    eprintln!("Erreur FAT32: {e:?}");
+                    std::process::exit(2);
+                }
+            };
+            if !run_fsck(&ro) {
+                std::process::exit(1);
+            }
+        }
+        Some("repair") => {
+            if !run_repair(&mut image, &repair) {
+                std::process::exit(1);
             }
+        }
+        Some("lsdel") => {
+            let ro = match image.read() {
+                Ok(fs) => fs,
+                Err(e) => {
+                    eprintln!("Erreur FAT32: {e:?}");
+                    return;
+                }
+            };
+            let path = target_a
+                .as_deref()
+                .map(|p| resolve_path("/", p))
+                .unwrap_or_else(|| "/".to_string());
+            run_lsdel(&ro, &path);
+        }
+        Some("undelete") => {
+            let (dir, index) = match (target_a, target_b.and_then(|n| n.parse().ok())) {
+                (Some(dir), Some(n)) => (resolve_path("/", &dir), n),
+                _ => {
+                    eprintln!("--undelete nécessite un répertoire et un numéro (1, 2, ...)");
+                    print_usage();
+                    return;
+                }
+            };
 
-            if let Err(e) = fs::write(&dump_path, &data) {
-                eprintln!("Impossible d'écrire {dump_path}: {e}");
-                return;
+            match run_undelete(&mut image, &dir, index, first_char) {
+                Ok(msg) => println!("{msg}"),
+                Err(msg) => eprintln!("{msg}"),
             }
+        }
+        Some("info") => {
+            let ro = match image.read() {
+                Ok(fs) => fs,
+                Err(e) => {
+                    eprintln!("Erreur FAT32: {e:?}");
+                    return;
+                }
+            };
+            run_info(&ro);
+        }
+        Some("set-label") => {
+            let label = match target_a {
+                Some(l) => l,
+                None => {
+                    eprintln!("--set-label nécessite un nom ('' pour l'effacer)");
+                    print_usage();
+                    return;
+                }
+            };
 
-            println!("OK: {src} -> {fat_path} (image mise à jour)");
+            match run_set_label(&mut image, &label) {
+                Ok(msg) => println!("{msg}"),
+                Err(msg) => eprintln!("{msg}"),
+            }
         }
         Some(other) => {
             eprintln!("Commande inconnue : {other}");
             print_usage();
         }
-        None => run_shell(&dump_path, &mut data),
+        None => run_shell(&mut image),
     }
 }
 
@@ -232,15 +499,159 @@ fn push_component(components: &mut Vec<String>, part: &str) {
     }
 }
 
+/// Image ouverte par la CLI, avec la partition choisie (`--partition`).
+struct Image {
+    file: File,
+    /// Numéro de partition (MBR ou GPT) ; `None` si le volume FAT commence à l’octet 0.
+    partition: Option<usize>,
+}
+
+/// Volume FAT en lecture seule sur l’image.
+type ReadFs<'a> = Fat32<PartitionDevice<&'a File>>;
+
+/// Volume FAT en lecture/écriture sur l’image.
+type WriteFs<'a> = Fat32Mut<PartitionDevice<&'a mut File>>;
+
+impl Image {
+    /// Ouvre le volume FAT en lecture seule (avec repli sur la copie de
+    /// secours du secteur de boot).
+    fn read(&self) -> Result<ReadFs<'_>, FatError> {
+        Fat32::open_with_backup(volume(&self.file, self.partition)?)
+    }
+}
+
+/// Prévient de ce qui cloche à l’ouverture de l’image : volume ouvert grâce
+/// à la copie de secours du secteur de boot, ou marqué sale dans `FAT[1]` (un
+/// outil s’est arrêté au milieu d’une écriture).
+fn warn_on_open(fs: &ReadFs<'_>) {
+    if fs.boot_source() == BootSector::Backup {
+        eprintln!(
+            "Attention : secteur de boot principal invalide, copie de secours utilisée \
+             (--restore-boot backup pour le réparer)"
+        );
+    }
+    if fs.is_dirty().unwrap_or(false) {
+        eprintln!(
+            "Attention : volume marqué sale, une écriture précédente a été interrompue \
+             (--fsck pour vérifier, --repair pour le remettre propre)"
+        );
+    }
+    if fs.has_hard_error().unwrap_or(false) {
+        eprintln!("Attention : le volume signale des erreurs d'entrée/sortie");
+    }
+}
+
+/// Support sur lequel ouvrir le volume : la partition demandée, ou toute l’image.
+fn volume<D: BlockDevice>(
+    dev: D,
+    partition: Option<usize>,
+) -> Result<PartitionDevice<D>, FatError> {
+    match partition {
+        Some(n) => PartitionDevice::open(dev, n),
+        None => {
+            let blocks = dev.num_blocks();
+            PartitionDevice::new(dev, 0, blocks)
+        }
+    }
+}
+
+/// Ouvre le fichier image en lecture/écriture, ou en lecture seule si
+/// l’écriture n’est pas permise (les commandes d’écriture échoueront alors).
+fn open_image(path: &str) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .or_else(|_| File::open(path))
+}
+
+/// Ouvre l’image en écriture avec une horloge pour horodater les entrées.
+///
+/// Si `SOURCE_DATE_EPOCH` est défini (builds reproductibles), on l’utilise ;
+/// sinon on prend l’heure système (UTC, FAT ne stocke pas de fuseau).
+fn open_rw(image: &mut Image) -> Result<WriteFs<'_>, FatError> {
+    let mut rw = Fat32Mut::open_with_backup(volume(&mut image.file, image.partition)?)?;
+
+    let fixed = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok());
+
+    match fixed {
+        Some(secs) => rw.set_clock(FatTimestamp::from_unix_seconds(secs)),
+        None => rw.set_clock(|| {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            FatTimestamp::from_unix_seconds(secs)
+        }),
+    }
+
+    Ok(rw)
+}
+
+/// Applique une opération d’écriture sur l’image.
+///
+/// Les secteurs sont écrits directement dans le fichier, la modification
+/// est donc persistante. En cas d’échec, retourne un message prêt à afficher.
+fn apply_edit<F>(image: &mut Image, op: F) -> Result<(), String>
+where
+    F: FnOnce(&mut WriteFs<'_>) -> Result<(), FatError>,
+{
+    let mut rw = open_rw(image).map_err(|e| format!("{e:?}"))?;
+    op(&mut rw).map_err(|e| format!("{e:?}"))
+}
+
+/// Copie un fichier de l’hôte dans l’image (`put`).
+///
+/// Le contenu est copié en flux : le fichier source n’est jamais chargé
+/// entièrement en mémoire. Retourne le message à afficher (succès ou erreur).
+fn run_put(image: &mut Image, fat_path: &str, src: &str) -> Result<String, String> {
+    let mut input = File::open(src).map_err(|e| format!("Impossible de lire {src}: {e}"))?;
+
+    let mut rw = open_rw(image).map_err(|e| format!("Erreur FAT32: {e:?}"))?;
+    let mut writer = rw
+        .create_file(fat_path)
+        .map_err(|e| format!("Erreur put {fat_path}: {e:?}"))?;
+
+    io::copy(&mut input, &mut writer)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Erreur put {fat_path}: {e}"))?;
+
+    Ok(format!("OK: {src} -> {fat_path} (image mise à jour)"))
+}
+
+/// Exécute une commande d’écriture qui ne prend qu’un chemin FAT
+/// (`mkdir`, `rm`, `rmdir`) sur l’image.
+///
+/// Retourne le message à afficher (succès ou erreur).
+fn run_path_edit(image: &mut Image, cmd: &str, path: &str) -> Result<String, String> {
+    let res = apply_edit(image, |rw| match cmd {
+        "mkdir" => rw.create_dir(path),
+        "rm" => rw.remove_file(path),
+        _ => rw.remove_dir(path),
+    });
+
+    match (res, cmd) {
+        (Ok(()), "mkdir") => Ok(format!("OK: répertoire {path} créé (image mise à jour)")),
+        (Ok(()), _) => Ok(format!("OK: {path} supprimé (image mise à jour)")),
+        (Err(msg), _) => Err(format!("Erreur {cmd} {path}: {msg}")),
+    }
+}
+
 /// Liste un répertoire et affiche une vue simple
 /// (type + nom + taille) pour chaque entrée.
-fn run_ls(fs: &Fat32, path: &str) {
+fn run_ls(fs: &ReadFs<'_>, path: &str) {
     match fs.list_dir_path(path) {
         Ok(entries) => {
             println!("Listing de {path}:");
             for e in entries {
                 let kind = if e.is_dir() { "DIR " } else { "FILE" };
-                println!("{kind} {:<24} {:>8} bytes", e.name, e.size);
+                let modified = e
+                    .modified
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                println!("{kind} {:<24} {:>8} bytes  {modified}", e.name, e.size);
             }
         }
         Err(e) => eprintln!("Erreur ls {path}: {e:?}"),
@@ -248,19 +659,288 @@ fn run_ls(fs: &Fat32, path: &str) {
 }
 
 /// Lit un fichier et écrit son contenu sur la sortie standard.
-fn run_cat(fs: &Fat32, path: &str) {
-    match fs.read_file_by_path(path) {
-        Ok(Some(bytes)) => {
-            print!("{}", String::from_utf8_lossy(&bytes));
+///
+/// Le fichier est copié cluster par cluster, sans être chargé en mémoire.
+fn run_cat(fs: &ReadFs<'_>, path: &str) {
+    match fs.open_file(path) {
+        Ok(mut reader) => {
+            let mut out = io::stdout().lock();
+            if let Err(e) = io::copy(&mut reader, &mut out).and_then(|_| out.flush()) {
+                eprintln!("Erreur cat {path}: {e}");
+            }
         }
-        Ok(None) => eprintln!("Fichier introuvable : {path}"),
+        Err(FatError::PathNotFound) => eprintln!("Fichier introuvable : {path}"),
         Err(e) => eprintln!("Erreur cat {path}: {e:?}"),
     }
 }
 
+/// Vérifie le volume et affiche le rapport : une ligne `type clé=valeur`
+/// par problème, puis un résumé.
+///
+/// Retourne `true` si le volume est cohérent.
+fn run_fsck(fs: &ReadFs<'_>) -> bool {
+    match fs.check() {
+        Ok(report) => {
+            for problem in &report.problems {
+                println!("{problem}");
+            }
+            println!(
+                "fsck: {} fichier(s), {} répertoire(s), {} cluster(s) utilisé(s), {} problème(s)",
+                report.files,
+                report.directories,
+                report.used_clusters,
+                report.problems.len()
+            );
+            report.is_clean()
+        }
+        Err(e) => {
+            eprintln!("Erreur fsck: {e:?}");
+            false
+        }
+    }
+}
+
+/// Répare le volume et affiche ce qui a été fait : les problèmes corrigés,
+/// les octets modifiés (ou qui le seraient, en `dry_run`), puis ceux qui restent.
+///
+/// Retourne `true` si le volume est (ou serait) cohérent après réparation.
+fn run_repair(image: &mut Image, options: &RepairOptions) -> bool {
+    let report = match open_rw(image).and_then(|mut rw| rw.repair(options)) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Erreur repair: {e:?}");
+            return false;
+        }
+    };
+
+    let verb = if options.dry_run {
+        "would-write"
+    } else {
+        "write"
+    };
+    for problem in &report.repaired {
+        println!("repaired {problem}");
+    }
+    for change in &report.changes {
+        println!("{verb} {change}");
+    }
+    for problem in &report.remaining {
+        println!("remaining {problem}");
+    }
+    println!(
+        "repair: {} problème(s) corrigé(s), {} zone(s) modifiée(s){}, {} restant(s)",
+        report.repaired.len(),
+        report.changes.len(),
+        if options.dry_run { " (dry-run)" } else { "" },
+        report.remaining.len()
+    );
+    report.remaining.iter().all(Problem::is_reserve)
+}
+
+/// Recopie le secteur de boot `from` sur l’autre copie.
+///
+/// Retourne le message à afficher (succès ou erreur).
+fn run_restore_boot(image: &mut Image, from: BootSector) -> Result<String, String> {
+    apply_edit(image, |rw| rw.restore_boot_sector(from))
+        .map_err(|msg| format!("Erreur restore-boot: {msg}"))?;
+
+    Ok(match from {
+        BootSector::Backup => "OK: secteur de boot restauré depuis la copie de secours".to_string(),
+        BootSector::Primary => "OK: copie de secours du secteur de boot mise à jour".to_string(),
+    })
+}
+
+/// Formate l’image (ou la partition `partition`) en FAT32.
+///
+/// Sans partition et avec une taille, le fichier est créé s’il n’existe pas
+/// et agrandi jusqu’à `options.size` s’il est plus petit ; il n’est jamais
+/// raccourci.
+fn run_mkfs(
+    path: &str,
+    partition: Option<usize>,
+    options: &FormatOptions,
+) -> Result<String, String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(partition.is_none() && options.size.is_some())
+        .truncate(false)
+        .open(path)
+        .map_err(|e| format!("Impossible d'ouvrir {path}: {e}"))?;
+
+    if let (None, Some(size)) = (partition, options.size) {
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < size {
+            file.set_len(size)
+                .map_err(|e| format!("Impossible d'agrandir {path}: {e}"))?;
+        }
+    }
+
+    let rw = volume(&mut file, partition)
+        .and_then(|dev| Fat32Mut::format(dev, options))
+        .map_err(|e| format!("Erreur mkfs {path}: {e:?}"))?;
+    let free = rw
+        .as_read()
+        .fs_info()
+        .and_then(|info| info.free_count)
+        .unwrap_or(0);
+
+    Ok(format!(
+        "OK: {path} formaté en FAT32 ({free} clusters libres, série {:04X}-{:04X})",
+        options.serial >> 16,
+        options.serial & 0xFFFF
+    ))
+}
+
+/// Taille en octets, avec un suffixe binaire optionnel (`512`, `4K`, `64M`, `1G`).
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (digits, unit) = match s.char_indices().last()? {
+        (i, 'K' | 'k') => (&s[..i], 1u64 << 10),
+        (i, 'M' | 'm') => (&s[..i], 1 << 20),
+        (i, 'G' | 'g') => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// Numéro de série écrit comme l’affiche `vol` (`1234-ABCD`), tiret optionnel.
+fn parse_serial(s: &str) -> Option<u32> {
+    let hex: String = s.chars().filter(|&c| c != '-').collect();
+    if hex.is_empty() || hex.len() > 8 {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+/// Numéro de série par défaut, tiré de l’heure comme le fait `mkfs.fat`
+/// (`SOURCE_DATE_EPOCH` le rend reproductible).
+fn default_serial() -> u32 {
+    env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        }) as u32
+}
+
+/// Liste les entrées supprimées d’un répertoire, numérotées à partir de 1
+/// (le numéro à passer à `undelete`).
+fn run_lsdel(fs: &ReadFs<'_>, path: &str) {
+    match fs.list_deleted(path) {
+        Ok(entries) => {
+            println!("Entrées supprimées de {path}:");
+            for (i, d) in entries.iter().enumerate() {
+                let kind = if d.entry.is_dir() { "DIR " } else { "FILE" };
+                let hint = d.first_char_hint.unwrap_or('?');
+                let state = if d.clusters_free {
+                    "libre"
+                } else {
+                    "réutilisé"
+                };
+                println!(
+                    "{:>3} {kind} {:<24} {:>8} bytes  cluster={} first-char={hint} {state}",
+                    i + 1,
+                    d.entry.name,
+                    d.entry.size,
+                    d.entry.first_cluster
+                );
+            }
+        }
+        Err(e) => eprintln!("Erreur lsdel {path}: {e:?}"),
+    }
+}
+
+/// Affiche l’identité du volume (`Fat32::volume_info`).
+fn run_info(fs: &ReadFs<'_>) {
+    match fs.volume_info() {
+        Ok(info) => {
+            let or_none = |s: Option<&str>| s.unwrap_or("(aucun)").to_string();
+            println!("OEM          : {}", info.oem_name);
+            println!(
+                "Type         : {:?} (BPB : {})",
+                fs.fat_type(),
+                or_none(info.fs_type.as_deref())
+            );
+            match info.serial {
+                Some(n) => println!("Série        : {:04X}-{:04X}", n >> 16, n & 0xFFFF),
+                None => println!("Série        : (aucune)"),
+            }
+            println!("Nom (BPB)    : {}", or_none(info.bpb_label.as_deref()));
+            println!("Nom (racine) : {}", or_none(info.root_label.as_deref()));
+            let state = match fs.is_dirty() {
+                Ok(true) => "sale (écriture interrompue)",
+                Ok(false) => "propre",
+                Err(_) => "inconnu",
+            };
+            println!("État         : {state}");
+        }
+        Err(e) => eprintln!("Erreur info: {e:?}"),
+    }
+}
+
+/// Change le nom du volume ; un nom vide l’efface.
+///
+/// Retourne le message à afficher (succès ou erreur).
+fn run_set_label(image: &mut Image, label: &str) -> Result<String, String> {
+    apply_edit(image, |rw| rw.set_label(label))
+        .map_err(|msg| format!("Erreur label {label}: {msg}"))?;
+
+    Ok(if label.trim().is_empty() {
+        "OK: nom du volume effacé".to_string()
+    } else {
+        format!("OK: volume renommé en {}", label.to_ascii_uppercase())
+    })
+}
+
+/// Restaure l’entrée supprimée numéro `index` (1, 2, ...) de `dir`.
+///
+/// Sans `first_char`, on prend celui déduit du nom long ; retourne le
+/// message à afficher (succès ou erreur).
+fn run_undelete(
+    image: &mut Image,
+    dir: &str,
+    index: usize,
+    first_char: Option<char>,
+) -> Result<String, String> {
+    let mut rw = open_rw(image).map_err(|e| format!("Erreur FAT32: {e:?}"))?;
+    let deleted = rw
+        .as_read()
+        .list_deleted(dir)
+        .map_err(|e| format!("Erreur undelete {dir}: {e:?}"))?
+        .into_iter()
+        .nth(index.wrapping_sub(1))
+        .ok_or_else(|| format!("Pas d'entrée supprimée n°{index} dans {dir}"))?;
+
+    let c = first_char
+        .or(deleted.first_char_hint)
+        .ok_or_else(|| format!("Premier caractère inconnu pour {}", deleted.entry.name))?;
+    rw.undelete(&deleted, c)
+        .map_err(|e| format!("Erreur undelete {}: {e:?}", deleted.entry.name))?;
+
+    let name = match deleted.entry.name.strip_prefix('?') {
+        Some(rest) => format!("{}{rest}", c.to_ascii_uppercase()),
+        None => deleted.entry.name.clone(),
+    };
+    Ok(format!(
+        "OK: {name} restauré dans {dir} (image mise à jour)"
+    ))
+}
+
+/// Un argument d’un seul caractère (`--first-char`, `undelete`).
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
 /// Lance un petit shell interactif pour manipuler l’image:
-/// navigation (`cd`, `pwd`), listage (`ls`), lecture (`cat`) et écriture (`put`).
-fn run_shell(img_path: &str, data: &mut Vec<u8>) {
+/// navigation (`cd`, `pwd`), listage (`ls`), lecture (`cat`), écriture
+/// (`put`, `mkdir`, `rm`, `rmdir`), vérification et réparation (`fsck`, `repair`),
+/// récupération (`lsdel`, `undelete`).
+fn run_shell(image: &mut Image) {
     println!("FAT32 shell. Tapez 'help' pour l'aide, 'exit' pour quitter.");
 
     let stdin = io::stdin();
@@ -294,7 +974,7 @@ fn run_shell(img_path: &str, data: &mut Vec<u8>) {
             "help" => print_shell_help(),
             "pwd" => println!("{current_dir}"),
             "ls" => {
-                let ro = match Fat32::new(&data) {
+                let ro = match image.read() {
                     Ok(fs) => fs,
                     Err(e) => {
                         println!("Erreur FAT32: {e:?}");
@@ -310,7 +990,7 @@ fn run_shell(img_path: &str, data: &mut Vec<u8>) {
                 run_ls(&ro, &path);
             }
             "cat" => {
-                let ro = match Fat32::new(&data) {
+                let ro = match image.read() {
                     Ok(fs) => fs,
                     Err(e) => {
                         println!("Erreur FAT32: {e:?}");
@@ -326,7 +1006,7 @@ fn run_shell(img_path: &str, data: &mut Vec<u8>) {
                 }
             }
             "cd" => {
-                let ro = match Fat32::new(&data) {
+                let ro = match image.read() {
                     Ok(fs) => fs,
                     Err(e) => {
                         println!("Erreur FAT32: {e:?}");
@@ -363,35 +1043,77 @@ fn run_shell(img_path: &str, data: &mut Vec<u8>) {
                     }
                 };
 
-                let content = match fs::read(&src) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        println!("Impossible de lire {src}: {e}");
-                        continue;
-                    }
+                match run_put(image, &fat_path, &src) {
+                    Ok(msg) | Err(msg) => println!("{msg}"),
+                }
+            }
+            "fsck" => match image.read() {
+                Ok(ro) => {
+                    run_fsck(&ro);
+                }
+                Err(e) => println!("Erreur FAT32: {e:?}"),
+            },
+            "repair" => {
+                let options = RepairOptions {
+                    dry_run: parts.next() == Some("--dry-run"),
+                    ..RepairOptions::default()
                 };
-
-                {
-                    let mut rw = match Fat32Mut::new(data) {
-                        Ok(fs) => fs,
-                        Err(e) => {
-                            println!("Erreur FAT32: {e:?}");
+                run_repair(image, &options);
+            }
+            "lsdel" => match image.read() {
+                Ok(ro) => {
+                    let path = match parts.next() {
+                        Some(p) => resolve_path(&current_dir, p),
+                        None => current_dir.clone(),
+                    };
+                    run_lsdel(&ro, &path);
+                }
+                Err(e) => println!("Erreur FAT32: {e:?}"),
+            },
+            "undelete" => {
+                let dir = parts.next().map(|p| resolve_path(&current_dir, p));
+                let index = parts.next().and_then(|n| n.parse().ok());
+                let first_char = match parts.next() {
+                    Some(c) => match single_char(c) {
+                        Some(c) => Some(c),
+                        None => {
+                            println!("Usage: undelete <path> <N> [c]");
                             continue;
                         }
-                    };
+                    },
+                    None => None,
+                };
 
-                    if let Err(e) = rw.write_file_by_path(&fat_path, &content) {
-                        println!("Erreur put {fat_path}: {e:?}");
+                match (dir, index) {
+                    (Some(dir), Some(n)) => match run_undelete(image, &dir, n, first_char) {
+                        Ok(msg) | Err(msg) => println!("{msg}"),
+                    },
+                    _ => println!("Usage: undelete <path> <N> [c]"),
+                }
+            }
+            "info" => match image.read() {
+                Ok(ro) => run_info(&ro),
+                Err(e) => println!("Erreur FAT32: {e:?}"),
+            },
+            "label" => {
+                // Le nom peut contenir des espaces
+                let label = parts.collect::<Vec<_>>().join(" ");
+                match run_set_label(image, &label) {
+                    Ok(msg) | Err(msg) => println!("{msg}"),
+                }
+            }
+            "mkdir" | "rm" | "rmdir" => {
+                let path = match parts.next() {
+                    Some(p) => resolve_path(&current_dir, p),
+                    None => {
+                        println!("Usage: {cmd} <path>");
                         continue;
                     }
-                }
+                };
 
-                if let Err(e) = fs::write(img_path, &*data) {
-                    println!("Impossible d'écrire {img_path}: {e}");
-                    continue;
+                match run_path_edit(image, cmd, &path) {
+                    Ok(msg) | Err(msg) => println!("{msg}"),
                 }
-
-                println!("OK: {src} -> {fat_path} (image mise à jour)");
             }
             _ => println!("Commande inconnue: {cmd}. Tapez 'help'."),
         }
@@ -400,7 +1122,7 @@ fn run_shell(img_path: &str, data: &mut Vec<u8>) {
 
 #[cfg(test)]
 mod cli_path_tests {
-    use super::resolve_path;
+    use super::{parse_serial, parse_size, resolve_path};
 
     #[test]
     fn chemin_parent_depuis_dir() {
@@ -419,4 +1141,17 @@ mod cli_path_tests {
         let r = resolve_path("/DIR", "/AUTRE/XX");
         assert_eq!(r, "/AUTRE/XX");
     }
+
+    #[test]
+    fn tailles_et_numero_de_serie_mkfs() {
+        assert_eq!(parse_size("1048576"), Some(1 << 20));
+        assert_eq!(parse_size("4K"), Some(4096));
+        assert_eq!(parse_size("64M"), Some(64 << 20));
+        assert_eq!(parse_size("2g"), Some(2 << 30));
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("12X"), None);
+        assert_eq!(parse_serial("1234-ABCD"), Some(0x1234_ABCD));
+        assert_eq!(parse_serial("beef"), Some(0xBEEF));
+        assert_eq!(parse_serial("123456789"), None);
+    }
 }
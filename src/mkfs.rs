@@ -0,0 +1,279 @@
+//! Formatage d’un volume FAT32 (équivalent de `mkfs.fat -F 32`).
+//!
+//! Disposition écrite, dans l’ordre du support :
+//! - 32 secteurs réservés : secteur de boot (0), FSInfo (1), copies de
+//!   secours du secteur de boot (6) et de FSInfo (7)
+//! - les FAT, dont seules les entrées réservées 0 et 1 et celle de la racine
+//!   (cluster 2) sont remplies
+//! - la zone data, dont seul le premier cluster (la racine vide) est effacé
+//!
+//! Le reste de la zone data n’est pas touché : le formatage est “rapide”.
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::block::{device_len, write_bytes, BlockDeviceMut};
+use crate::volume::{encode_label, ATTR_VOLUME_ID, NO_NAME};
+use crate::{
+    Fat32Mut, FatError, FatType, FAT32_EOC, FSINFO_LEAD_SIG, FSINFO_STRUC_SIG, FSINFO_TRAIL_SIG,
+};
+
+/// Secteurs réservés avant la première FAT (valeur de `mkfs.fat` pour FAT32).
+const RESERVED_SECTORS: u32 = 32;
+
+/// Secteur de la copie de secours du secteur de boot (FSInfo juste après).
+const BACKUP_BOOT_SECTOR: u16 = 6;
+
+/// Octet “media” : disque fixe.
+const MEDIA_FIXED: u8 = 0xF8;
+
+/// Paramètres de `Fat32Mut::format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Taille du volume en octets ; `None` prend tout le support.
+    pub size: Option<u64>,
+    /// Taille d’un cluster en octets ; `None` la choisit d’après la taille
+    /// du volume (table de Microsoft : 512 o jusqu’à 260 Mio, puis 4 Kio
+    /// jusqu’à 8 Gio, 8 Kio, 16 Kio et 32 Kio au-delà de 32 Gio), réduite
+    /// si besoin pour atteindre les 65 525 clusters d’un FAT32.
+    pub cluster_size: Option<u32>,
+    /// Nombre de FAT (2 en général, la seconde sert de copie).
+    pub num_fats: u8,
+    /// Nom du volume (11 caractères au plus, ceux d’un nom court ou des
+    /// espaces) ; vide pour `NO NAME`.
+    pub label: String,
+    /// Numéro de série du volume.
+    pub serial: u32,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            size: None,
+            cluster_size: None,
+            num_fats: 2,
+            label: String::new(),
+            serial: 0,
+        }
+    }
+}
+
+/// Géométrie calculée pour un formatage.
+struct Layout {
+    bytes_per_sector: u32,
+    sectors_per_cluster: u32,
+    total_sectors: u32,
+    sectors_per_fat: u32,
+    num_fats: u32,
+    clusters: u32,
+}
+
+impl Layout {
+    fn compute<D: BlockDeviceMut + ?Sized>(
+        dev: &D,
+        options: &FormatOptions,
+    ) -> Result<Self, FatError> {
+        let bps = dev.block_size() as u32;
+        if !bps.is_power_of_two() || !(512..=4096).contains(&bps) {
+            return Err(FatError::InvalidBytesPerSector);
+        }
+        if options.num_fats == 0 {
+            return Err(FatError::InvalidFatCount);
+        }
+
+        let size = options.size.unwrap_or_else(|| device_len(dev));
+        if size > device_len(dev) {
+            return Err(FatError::VolumeExceedsDevice);
+        }
+        let total_sectors =
+            u32::try_from(size / bps as u64).map_err(|_| FatError::InvalidTotalSectors)?;
+
+        let cluster_size = options
+            .cluster_size
+            .unwrap_or_else(|| default_cluster_size(size))
+            .max(bps);
+        // Puissance de 2 au moins égale au secteur : un nombre entier de secteurs
+        let mut sectors_per_cluster = cluster_size / bps;
+        if !cluster_size.is_power_of_two() || sectors_per_cluster > 128 {
+            return Err(FatError::InvalidSectorsPerCluster);
+        }
+
+        // Sous 65 525 clusters, le volume serait lu comme du FAT16 : la
+        // taille choisie d’office est réduite jusqu’à les atteindre, une
+        // taille imposée est refusée.
+        let num_fats = options.num_fats as u32;
+        let (sectors_per_fat, clusters) = loop {
+            let (sectors_per_fat, clusters) =
+                fat_size(bps, total_sectors, sectors_per_cluster, num_fats)?;
+            if FatType::from_cluster_count(clusters) == FatType::Fat32 {
+                break (sectors_per_fat, clusters);
+            }
+            if options.cluster_size.is_some() || sectors_per_cluster == 1 {
+                return Err(FatError::ClusterCountMismatch);
+            }
+            sectors_per_cluster /= 2;
+        };
+
+        if clusters > FatType::Fat32.max_cluster() as u64 - 1 {
+            return Err(FatError::ClusterCountMismatch);
+        }
+
+        Ok(Self {
+            bytes_per_sector: bps,
+            sectors_per_cluster,
+            total_sectors,
+            sectors_per_fat,
+            num_fats,
+            clusters: clusters as u32,
+        })
+    }
+
+    /// Offset en octets d’un secteur.
+    fn sector(&self, n: u32) -> u64 {
+        n as u64 * self.bytes_per_sector as u64
+    }
+
+    /// Premier secteur de la zone data (cluster 2, la racine).
+    fn data_start(&self) -> u32 {
+        RESERVED_SECTORS + self.num_fats * self.sectors_per_fat
+    }
+
+    fn boot_sector(&self, options: &FormatOptions, label: &[u8; 11]) -> Vec<u8> {
+        let mut b = vec![0u8; 512];
+        b[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+        b[3..11].copy_from_slice(b"MSWIN4.1");
+        b[11..13].copy_from_slice(&(self.bytes_per_sector as u16).to_le_bytes());
+        b[13] = self.sectors_per_cluster as u8;
+        b[14..16].copy_from_slice(&(RESERVED_SECTORS as u16).to_le_bytes());
+        b[16] = self.num_fats as u8;
+        // root_entry_count, total_sectors_16 et sectors_per_fat_16 à 0 : FAT32
+        b[21] = MEDIA_FIXED;
+        b[24..26].copy_from_slice(&32u16.to_le_bytes()); // secteurs par piste
+        b[26..28].copy_from_slice(&64u16.to_le_bytes()); // têtes
+        b[32..36].copy_from_slice(&self.total_sectors.to_le_bytes());
+        b[36..40].copy_from_slice(&self.sectors_per_fat.to_le_bytes());
+        // ext_flags à 0 : toutes les FAT sont tenues à jour
+        b[44..48].copy_from_slice(&2u32.to_le_bytes()); // cluster racine
+        b[48..50].copy_from_slice(&1u16.to_le_bytes()); // FSInfo
+        b[50..52].copy_from_slice(&BACKUP_BOOT_SECTOR.to_le_bytes());
+        b[64] = 0x80; // disque dur
+        b[66] = 0x29; // les trois champs suivants sont présents
+        b[67..71].copy_from_slice(&options.serial.to_le_bytes());
+        b[71..82].copy_from_slice(label);
+        b[82..90].copy_from_slice(b"FAT32   ");
+        // Code de boot : `int 18h` (“pas de système amorçable”)
+        b[90..92].copy_from_slice(&[0xCD, 0x18]);
+        b[510] = 0x55;
+        b[511] = 0xAA;
+        b
+    }
+
+    fn fs_info_sector(&self) -> Vec<u8> {
+        let mut f = vec![0u8; 512];
+        f[0..4].copy_from_slice(&FSINFO_LEAD_SIG.to_le_bytes());
+        f[484..488].copy_from_slice(&FSINFO_STRUC_SIG.to_le_bytes());
+        // Tout est libre sauf la racine
+        f[488..492].copy_from_slice(&(self.clusters - 1).to_le_bytes());
+        f[492..496].copy_from_slice(&3u32.to_le_bytes());
+        f[508..512].copy_from_slice(&FSINFO_TRAIL_SIG.to_le_bytes());
+        f
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Formate le support en FAT32 puis ouvre le volume vide obtenu.
+    ///
+    /// La taille de secteur est celle des blocs du support. Les erreurs
+    /// reprennent celles de la validation du BPB :
+    /// - `InvalidBytesPerSector` si les blocs ne font pas 512 à 4096 octets
+    /// - `InvalidSectorsPerCluster` pour une taille de cluster invalide
+    /// - `InvalidFatCount` si `num_fats` vaut 0
+    /// - `VolumeExceedsDevice` si `size` dépasse le support
+    /// - `InvalidTotalSectors` si le volume est trop petit pour une zone data
+    /// - `ClusterCountMismatch` s’il a trop de clusters (clusters trop petits)
+    ///   ou moins des 65 525 qui font un FAT32 (volume sous 33 Mio environ
+    ///   avec des secteurs de 512 octets, ou `cluster_size` trop grand)
+    /// - `InvalidName` pour un nom de volume invalide
+    pub fn format(mut dev: D, options: &FormatOptions) -> Result<Self, FatError> {
+        let label = encode_label(&options.label)?;
+        let layout = Layout::compute(&dev, options)?;
+
+        // Secteurs réservés, FAT et racine repartent de zéro
+        let cluster_bytes = layout.sector(layout.sectors_per_cluster) as usize;
+        let zero_end = layout.sector(layout.data_start()) + cluster_bytes as u64;
+        let zeros = vec![0u8; cluster_bytes.max(64 * 1024)];
+        let mut off = 0u64;
+        while off < zero_end {
+            let n = (zero_end - off).min(zeros.len() as u64) as usize;
+            write_bytes(&mut dev, off, &zeros[..n])?;
+            off += n as u64;
+        }
+
+        let boot = layout.boot_sector(options, &label);
+        let fs_info = layout.fs_info_sector();
+        let backup = BACKUP_BOOT_SECTOR as u32;
+        write_bytes(&mut dev, 0, &boot)?;
+        write_bytes(&mut dev, layout.sector(1), &fs_info)?;
+        write_bytes(&mut dev, layout.sector(backup), &boot)?;
+        write_bytes(&mut dev, layout.sector(backup + 1), &fs_info)?;
+
+        // Entrées réservées (media, puis “volume propre”) et racine en fin de chaîne
+        let mut reserved = [0u8; 12];
+        reserved[0..4].copy_from_slice(&(0x0FFF_FF00 | MEDIA_FIXED as u32).to_le_bytes());
+        reserved[4..8].copy_from_slice(&FAT32_EOC.to_le_bytes());
+        reserved[8..12].copy_from_slice(&FAT32_EOC.to_le_bytes());
+        for fat in 0..layout.num_fats {
+            let start = RESERVED_SECTORS + fat * layout.sectors_per_fat;
+            write_bytes(&mut dev, layout.sector(start), &reserved)?;
+        }
+
+        if &label != NO_NAME {
+            let mut entry = [0u8; 32];
+            entry[0..11].copy_from_slice(&label);
+            entry[11] = ATTR_VOLUME_ID;
+            write_bytes(&mut dev, layout.sector(layout.data_start()), &entry)?;
+        }
+
+        Fat32Mut::open(dev)
+    }
+}
+
+/// Taille de cluster par défaut selon la taille du volume (table FAT32 de
+/// la spécification Microsoft, pour des secteurs de 512 octets).
+fn default_cluster_size(size: u64) -> u32 {
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = 1024 * MIB;
+    match size {
+        s if s <= 260 * MIB => 512,
+        s if s <= 8 * GIB => 4096,
+        s if s <= 16 * GIB => 8192,
+        s if s <= 32 * GIB => 16384,
+        _ => 32768,
+    }
+}
+
+/// Taille d’une FAT (en secteurs) et nombre de clusters de données.
+///
+/// La taille de la FAT dépend du nombre de clusters, qui dépend de la place
+/// laissée par la FAT : on itère jusqu’à ce qu’elle suffise.
+fn fat_size(
+    bps: u32,
+    total_sectors: u32,
+    sectors_per_cluster: u32,
+    num_fats: u32,
+) -> Result<(u32, u64), FatError> {
+    let mut sectors_per_fat = 1u32;
+    loop {
+        let meta = RESERVED_SECTORS as u64 + num_fats as u64 * sectors_per_fat as u64;
+        if meta + sectors_per_cluster as u64 > total_sectors as u64 {
+            return Err(FatError::InvalidTotalSectors);
+        }
+        let clusters = (total_sectors as u64 - meta) / sectors_per_cluster as u64;
+        let needed = ((clusters + 2) * 4).div_ceil(bps as u64) as u32;
+        if needed <= sectors_per_fat {
+            return Ok((sectors_per_fat, clusters));
+        }
+        sectors_per_fat = needed;
+    }
+}
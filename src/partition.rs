@@ -0,0 +1,265 @@
+//! Table de partitions MBR.
+//!
+//! Une image de disque complet (`dd if=/dev/sdb`) commence par un MBR, pas
+//! par le BPB : le volume FAT se trouve plus loin, au début d’une partition.
+//!
+//! - les 4 entrées primaires sont lues dans le secteur 0 (offset 446)
+//! - une partition étendue contient une chaîne d’EBR : chaque EBR décrit
+//!   une partition logique, puis pointe vers l’EBR suivant
+//!
+//! La numérotation suit celle de Linux : 1 à 4 pour les primaires (position
+//! dans le MBR), 5 et plus pour les logiques, dans l’ordre de la chaîne.
+//! Les LBA sont exprimés en blocs du support (en général 512 octets).
+//!
+//! Un disque GPT n’a qu’une entrée MBR de type 0xEE (MBR protecteur) ; ses
+//! partitions sont lues par le module `gpt`.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use crate::block::{read_bytes, BlockDevice, BlockDeviceMut};
+use crate::gpt::read_gpt;
+use crate::FatError;
+
+/// Offset de la première entrée de partition dans le MBR / un EBR.
+const PARTITION_TABLE_OFFSET: usize = 446;
+
+/// Nombre maximal de partitions logiques suivies (garde-fou contre une chaîne qui boucle).
+const MAX_LOGICAL_PARTITIONS: usize = 128;
+
+/// Partition décrite par le MBR (primaire) ou par un EBR (logique).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partition {
+    /// Numéro de partition : 1 à 4 pour les primaires, 5 et plus pour les logiques.
+    pub index: usize,
+    /// Drapeau “active” (0x80) du MBR.
+    pub bootable: bool,
+    /// Octet de type (ex: 0x0C FAT32 LBA, 0x06 FAT16, 0x01 FAT12).
+    pub kind: u8,
+    /// Premier bloc de la partition, depuis le début du support.
+    pub start_lba: u64,
+    /// Nombre de blocs de la partition.
+    pub num_sectors: u64,
+}
+
+impl Partition {
+    /// Indique si l’octet de type annonce un volume FAT (variantes cachées comprises).
+    pub fn is_fat(&self) -> bool {
+        matches!(
+            self.kind,
+            0x01 | 0x04 | 0x06 | 0x0B | 0x0C | 0x0E | 0x11 | 0x14 | 0x16 | 0x1B | 0x1C | 0x1E
+        )
+    }
+}
+
+/// Entrée brute de 16 octets : (statut, type, LBA de départ, nombre de blocs).
+type RawEntry = (u8, u8, u64, u64);
+
+/// Type d’entrée MBR qui protège un disque GPT.
+const GPT_PROTECTIVE_KIND: u8 = 0xEE;
+
+/// Indique si l’octet de type désigne une partition étendue (conteneur d’EBR).
+fn is_extended(kind: u8) -> bool {
+    matches!(kind, 0x05 | 0x0F | 0x85)
+}
+
+/// Lit les 4 entrées d’un MBR ou d’un EBR situé au bloc `lba`.
+///
+/// Retourne `InvalidPartitionTable` si la signature `0x55AA` manque ou si
+/// un octet de statut n’est ni 0x00 ni 0x80 (ex: un secteur de boot FAT).
+fn read_table<D: BlockDevice + ?Sized>(dev: &D, lba: u64) -> Result<[RawEntry; 4], FatError> {
+    let offset = lba
+        .checked_mul(dev.block_size() as u64)
+        .ok_or(FatError::InvalidPartitionTable)?;
+    let mut s = [0u8; 512];
+    read_bytes(dev, offset, &mut s)?;
+
+    if s[510] != 0x55 || s[511] != 0xAA {
+        return Err(FatError::InvalidPartitionTable);
+    }
+
+    let mut entries = [(0, 0, 0, 0); 4];
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let e = &s[PARTITION_TABLE_OFFSET + i * 16..PARTITION_TABLE_OFFSET + (i + 1) * 16];
+        if e[0] != 0x00 && e[0] != 0x80 {
+            return Err(FatError::InvalidPartitionTable);
+        }
+        let start = u32::from_le_bytes([e[8], e[9], e[10], e[11]]);
+        let count = u32::from_le_bytes([e[12], e[13], e[14], e[15]]);
+        *entry = (e[0], e[4], start as u64, count as u64);
+    }
+
+    Ok(entries)
+}
+
+/// Indique si le MBR du support est un MBR protecteur GPT.
+pub(crate) fn is_protective_mbr<D: BlockDevice + ?Sized>(dev: &D) -> Result<bool, FatError> {
+    Ok(read_table(dev, 0)?
+        .iter()
+        .any(|&(_, kind, _, _)| kind == GPT_PROTECTIVE_KIND))
+}
+
+/// Lit la table de partitions MBR du support, partitions logiques comprises.
+///
+/// Les entrées vides et les conteneurs étendus ne sont pas retournés ; les
+/// numéros des primaires restent ceux de leur position dans le MBR.
+/// Sur un disque GPT, on ne voit que l’entrée protectrice 0xEE (voir `read_gpt`).
+pub fn read_partitions<D: BlockDevice + ?Sized>(dev: &D) -> Result<Vec<Partition>, FatError> {
+    let mut parts = Vec::new();
+    let mut extended = None;
+
+    for (i, &(status, kind, start, count)) in read_table(dev, 0)?.iter().enumerate() {
+        if kind == 0 || count == 0 {
+            continue;
+        }
+        if is_extended(kind) {
+            // Un MBR n’a qu’une partition étendue ; on garde la première
+            extended.get_or_insert(start);
+            continue;
+        }
+        parts.push(Partition {
+            index: i + 1,
+            bootable: status == 0x80,
+            kind,
+            start_lba: start,
+            num_sectors: count,
+        });
+    }
+
+    // Chaîne d’EBR : l’entrée 0 est relative à l’EBR courant, l’entrée 1
+    // (EBR suivant) est relative au début de la partition étendue.
+    if let Some(ext_start) = extended {
+        let mut ebr = ext_start;
+        let mut index = 5;
+        let mut hops = 0;
+
+        loop {
+            hops += 1;
+            if hops > MAX_LOGICAL_PARTITIONS {
+                return Err(FatError::InvalidPartitionTable);
+            }
+
+            let table = read_table(dev, ebr)?;
+            let (status, kind, rel, count) = table[0];
+            if kind != 0 && count != 0 {
+                parts.push(Partition {
+                    index,
+                    bootable: status == 0x80,
+                    kind,
+                    start_lba: ebr + rel,
+                    num_sectors: count,
+                });
+                index += 1;
+            }
+
+            let (_, next_kind, next_rel, _) = table[1];
+            if !is_extended(next_kind) || next_rel == 0 {
+                break;
+            }
+            ebr = ext_start + next_rel;
+        }
+    }
+
+    Ok(parts)
+}
+
+/// Cherche la partition numéro `index` (numérotation de `read_partitions`).
+pub fn find_partition<D: BlockDevice + ?Sized>(
+    dev: &D,
+    index: usize,
+) -> Result<Partition, FatError> {
+    read_partitions(dev)?
+        .into_iter()
+        .find(|p| p.index == index)
+        .ok_or(FatError::PartitionNotFound)
+}
+
+/// Fenêtre sur une partition : les blocs 0..n du `PartitionDevice` sont
+/// les blocs `start_lba..start_lba + n` du support sous-jacent.
+///
+/// Le système de fichiers s’ouvre dessus comme sur un support à part entière
+/// (voir aussi `Fat32::open_partition`).
+#[derive(Debug)]
+pub struct PartitionDevice<D> {
+    dev: D,
+    start_lba: u64,
+    num_blocks: u64,
+}
+
+impl<D: BlockDevice> PartitionDevice<D> {
+    /// Fenêtre de `num_blocks` blocs à partir du bloc `start_lba`.
+    ///
+    /// Retourne `OutOfBounds` si la fenêtre dépasse la fin du support.
+    pub fn new(dev: D, start_lba: u64, num_blocks: u64) -> Result<Self, FatError> {
+        match start_lba.checked_add(num_blocks) {
+            Some(end) if end <= dev.num_blocks() => Ok(Self {
+                dev,
+                start_lba,
+                num_blocks,
+            }),
+            _ => Err(FatError::OutOfBounds),
+        }
+    }
+
+    /// Ouvre la partition numéro `index` du support.
+    ///
+    /// Sur un disque GPT (MBR protecteur), `index` est le numéro d’entrée GPT ;
+    /// sinon c’est la numérotation MBR de `read_partitions`.
+    pub fn open(dev: D, index: usize) -> Result<Self, FatError> {
+        let (start, count) = if is_protective_mbr(&dev)? {
+            let p = read_gpt(&dev)?
+                .partitions
+                .into_iter()
+                .find(|p| p.index == index)
+                .ok_or(FatError::PartitionNotFound)?;
+            (p.first_lba, p.num_sectors())
+        } else {
+            let p = find_partition(&dev, index)?;
+            (p.start_lba, p.num_sectors)
+        };
+        Self::new(dev, start, count)
+    }
+
+    /// Premier bloc de la fenêtre sur le support sous-jacent.
+    pub fn start_lba(&self) -> u64 {
+        self.start_lba
+    }
+
+    /// Rend le support sous-jacent.
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+
+    /// Traduit un LBA de la partition en LBA du support, après vérification des bornes.
+    fn translate(&self, lba: u64, len: usize) -> Result<u64, FatError> {
+        let bs = self.dev.block_size() as u64;
+        let count = (len as u64).div_ceil(bs.max(1));
+        match lba.checked_add(count) {
+            Some(end) if end <= self.num_blocks => Ok(self.start_lba + lba),
+            _ => Err(FatError::OutOfBounds),
+        }
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for PartitionDevice<D> {
+    fn block_size(&self) -> usize {
+        self.dev.block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        let lba = self.translate(lba, buf.len())?;
+        self.dev.read_blocks(lba, buf)
+    }
+}
+
+impl<D: BlockDeviceMut> BlockDeviceMut for PartitionDevice<D> {
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+        let lba = self.translate(lba, buf.len())?;
+        self.dev.write_blocks(lba, buf)
+    }
+}
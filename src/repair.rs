@@ -0,0 +1,434 @@
+//! Réparation automatique des incohérences trouvées par `Fat32::check`.
+//!
+//! `Fat32Mut::repair` corrige le volume à la manière de `fsck.fat -a` :
+//! - les copies secondaires de la FAT sont recopiées depuis la première
+//! - une chaîne qui boucle ou qui pointe vers un cluster invalide est coupée
+//! - un cluster partagé est dupliqué pour la seconde chaîne (avec la suite)
+//! - la taille d’un fichier est ramenée à sa chaîne ; une chaîne plus longue
+//!   que la taille (réserve de `preallocate`) n’est coupée qu’avec
+//!   `trim_reserved`
+//! - une chaîne perdue est libérée, ou récupérée dans `FOUND.000/FILE0000.CHK`
+//! - un volume marqué sale (`FAT[1]`) redevient propre s’il ne reste plus
+//!   que des réserves
+//!
+//! Les réparations sont d’abord jouées sur une surcouche qui garde les blocs
+//! modifiés en mémoire : on en tire la liste exacte des octets changés
+//! (`ByteChange`). En mode `dry_run`, rien de plus n’est fait ; sinon ces
+//! mêmes changements sont écrits sur le support.
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::block::{BlockDevice, BlockDeviceMut};
+use crate::dirty::DirtyState;
+use crate::{normalize_name, Fat32Mut, FatError, Problem, ATTR_ARCHIVE, FAT32_EOC, MAX_FILE_SIZE};
+
+/// Nombre maximal de passes « vérifier puis réparer » (une réparation peut
+/// en révéler une autre, ex: les sous-répertoires d’un répertoire dupliqué).
+const MAX_REPAIR_PASSES: usize = 4;
+
+/// Répertoire où sont rangées les chaînes perdues récupérées.
+const FOUND_DIR: &str = "FOUND.000";
+
+/// Options de `Fat32Mut::repair`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairOptions {
+    /// Ne rien écrire : seulement calculer les changements.
+    pub dry_run: bool,
+    /// Récupérer les chaînes perdues dans `FOUND.000/FILEnnnn.CHK` au lieu
+    /// de libérer leurs clusters.
+    pub recover_lost_chains: bool,
+    /// Couper les chaînes plus longues que la taille du fichier et libérer
+    /// les clusters en trop, au lieu de les garder comme réserve.
+    pub trim_reserved: bool,
+}
+
+/// Octets modifiés par une réparation, à un offset du support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteChange {
+    /// Offset du premier octet modifié, depuis le début du support.
+    pub offset: u64,
+    /// Contenu avant réparation.
+    pub old: Vec<u8>,
+    /// Contenu après réparation (même longueur que `old`).
+    pub new: Vec<u8>,
+}
+
+/// Forme `offset=0x... old=<hex> new=<hex>`, une ligne par changement.
+impl fmt::Display for ByteChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "offset={:#x} old=", self.offset)?;
+        for b in &self.old {
+            write!(f, "{b:02x}")?;
+        }
+        f.write_str(" new=")?;
+        for b in &self.new {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Résultat de `Fat32Mut::repair`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Problèmes corrigés, dans l’ordre où ils ont été traités.
+    pub repaired: Vec<Problem>,
+    /// Problèmes encore présents après réparation (dont les réserves
+    /// gardées, voir `Problem::is_reserve`).
+    pub remaining: Vec<Problem>,
+    /// Octets modifiés, triés par offset (déjà écrits, sauf en `dry_run`).
+    pub changes: Vec<ByteChange>,
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Répare le volume (voir le module `repair`).
+    ///
+    /// Retourne une erreur si le support ne peut pas être lu ou écrit, ou
+    /// s’il manque de place pour une copie ; si l’erreur survient pendant le
+    /// calcul des réparations, rien n’a été écrit.
+    pub fn repair(&mut self, options: &RepairOptions) -> Result<RepairReport, FatError> {
+        let result = self.apply_repairs(options);
+        self.finish(result)
+    }
+
+    /// Corps de `repair`.
+    fn apply_repairs(&mut self, options: &RepairOptions) -> Result<RepairReport, FatError> {
+        let clock = self.clock.take();
+        let mut shadow = Fat32Mut {
+            fs: self.fs.with_device(Overlay::new(&self.fs.dev)),
+            clock,
+            dirty: DirtyState::Untracked,
+        };
+
+        let result = shadow.repair_passes(options);
+        let changes = shadow.fs.dev.changes();
+        self.clock = shadow.clock.take();
+        drop(shadow);
+
+        let (repaired, remaining) = result?;
+        let changes = changes?;
+
+        if !options.dry_run {
+            for c in &changes {
+                self.write_at(c.offset, &c.new)?;
+            }
+            // Un volume sale à l’ouverture a pu être remis propre
+            if self.dirty == DirtyState::Untracked && !self.fs.is_dirty()? {
+                self.dirty = DirtyState::Clean;
+            }
+        }
+
+        Ok(RepairReport {
+            repaired,
+            remaining,
+            changes,
+        })
+    }
+
+    /// Enchaîne vérification et réparations jusqu’à un volume propre (ou
+    /// jusqu’à ce que plus rien ne puisse être réparé).
+    fn repair_passes(
+        &mut self,
+        options: &RepairOptions,
+    ) -> Result<(Vec<Problem>, Vec<Problem>), FatError> {
+        let mut repaired = Vec::new();
+
+        for _ in 0..MAX_REPAIR_PASSES {
+            let report = self.fs.check()?;
+            let mut progress = false;
+            for problem in report.problems {
+                if self.repair_problem(&problem, options)? {
+                    repaired.push(problem);
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        self.sync_free_count()?;
+        let remaining = self.fs.check()?.problems;
+        if remaining.iter().all(Problem::is_reserve) && self.fs.is_dirty()? {
+            self.set_clean_flag(true)?;
+        }
+        Ok((repaired, remaining))
+    }
+
+    /// Corrige un problème ; retourne `false` s’il n’y a rien à faire de sûr.
+    fn repair_problem(
+        &mut self,
+        problem: &Problem,
+        options: &RepairOptions,
+    ) -> Result<bool, FatError> {
+        match problem {
+            Problem::FatMismatch { copy, .. } => self.resync_fat_copy(*copy)?,
+            Problem::ChainLoop { from, .. } => self.write_fat_entry_all(*from, FAT32_EOC)?,
+            Problem::InvalidCluster {
+                from: Some(from), ..
+            } => self.write_fat_entry_all(*from, FAT32_EOC)?,
+            Problem::InvalidCluster {
+                path, from: None, ..
+            } => {
+                if path == "/" {
+                    return Ok(false);
+                }
+                let found = self.locate_path(path)?;
+                if found.entry.is_dir() {
+                    // Répertoire sans contenu lisible : on retire l’entrée
+                    self.mark_deleted(&found)?;
+                } else {
+                    self.set_entry_first_cluster(found.offset, 0)?;
+                    self.write_at(found.offset + 28, &0u32.to_le_bytes())?;
+                }
+            }
+            Problem::CrossLinked {
+                cluster,
+                second,
+                from,
+                ..
+            } => return self.copy_shared_tail(*cluster, second, *from),
+            Problem::ChainTooShort { path, clusters, .. } => {
+                let found = self.locate_path(path)?;
+                let len = (*clusters as u64 * self.fs.cluster_size() as u64).min(MAX_FILE_SIZE);
+                self.write_at(found.offset + 28, &(len as u32).to_le_bytes())?;
+            }
+            Problem::ChainTooLong { .. } if !options.trim_reserved => return Ok(false),
+            Problem::ChainTooLong { path, size, .. } => {
+                let found = self.locate_path(path)?;
+                let chain = self.fs.follow_chain(found.entry.first_cluster)?;
+                let keep = (*size as usize).div_ceil(self.fs.cluster_size());
+
+                if keep == 0 {
+                    self.set_entry_first_cluster(found.offset, 0)?;
+                } else {
+                    self.write_fat_entry_all(chain[keep - 1], FAT32_EOC)?;
+                }
+                for &cl in &chain[keep..] {
+                    self.write_fat_entry_all(cl, 0)?;
+                }
+            }
+            Problem::LostChain { start, clusters } => {
+                let chain = self.lost_chain(*start, *clusters)?;
+                if options.recover_lost_chains {
+                    self.recover_chain(&chain)?;
+                } else {
+                    for &cl in &chain {
+                        self.write_fat_entry_all(cl, 0)?;
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Recopie la FAT de référence sur la copie `copy`, secteur par secteur.
+    fn resync_fat_copy(&mut self, copy: u8) -> Result<(), FatError> {
+        let bps = self.fs.bytes_per_sector() as u64;
+        let src = self.fs.fat_copy_start(self.fs.reference_fat());
+        let dst = self.fs.fat_copy_start(copy);
+
+        let mut sector = vec![0u8; bps as usize];
+        for i in 0..self.fs.sectors_per_fat as u64 {
+            self.fs.read_at(src + i * bps, &mut sector)?;
+            self.write_at(dst + i * bps, &sector)?;
+        }
+        Ok(())
+    }
+
+    /// Donne à `second` sa propre copie de la fin de chaîne partagée, à
+    /// partir de `cluster` : nouveaux clusters, mêmes données.
+    fn copy_shared_tail(
+        &mut self,
+        cluster: u32,
+        second: &str,
+        from: Option<u32>,
+    ) -> Result<bool, FatError> {
+        let max = self.fs.max_cluster_number()?;
+
+        // Fin de chaîne partagée, sans repasser deux fois par un cluster
+        let mut tail: Vec<u32> = Vec::new();
+        let mut cl = cluster;
+        while (2..=max).contains(&cl) && !tail.contains(&cl) {
+            tail.push(cl);
+            cl = self.fs.read_fat_entry(cl)?;
+        }
+
+        let copy = self.alloc_chain(tail.len())?;
+        for (&src, &dst) in tail.iter().zip(&copy) {
+            let data = self.fs.read_cluster(src)?;
+            let off = self.fs.cluster_to_offset(dst)?;
+            self.write_at(off, &data)?;
+        }
+
+        match from {
+            Some(prev) => self.write_fat_entry_all(prev, copy[0])?,
+            None => {
+                if second == "/" {
+                    return Ok(false);
+                }
+                let found = self.locate_path(second)?;
+                self.set_entry_first_cluster(found.offset, copy[0])?;
+                if found.entry.is_dir() {
+                    // L’entrée `.` de la copie désigne la copie elle-même
+                    let dot = self.fs.cluster_to_offset(copy[0])?;
+                    self.set_entry_first_cluster(dot, copy[0])?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Clusters d’une chaîne perdue, dans l’ordre de la FAT.
+    fn lost_chain(&self, start: u32, clusters: u32) -> Result<Vec<u32>, FatError> {
+        let mut chain = Vec::with_capacity(clusters as usize);
+        let mut cl = start;
+        for _ in 0..clusters {
+            chain.push(cl);
+            cl = self.fs.read_fat_entry(cl)?;
+        }
+        Ok(chain)
+    }
+
+    /// Range une chaîne perdue dans `FOUND.000/FILEnnnn.CHK`, avec une
+    /// taille égale à la chaîne complète (comme `fsck.fat`).
+    fn recover_chain(&mut self, chain: &[u32]) -> Result<(), FatError> {
+        let found_path = format!("/{FOUND_DIR}");
+        let dir_cluster = match self.fs.find_in_dir(self.fs.root_cluster, FOUND_DIR)? {
+            Some(l) if l.entry.is_dir() => l.entry.first_cluster,
+            Some(_) => return Err(FatError::AlreadyExists),
+            None => {
+                self.create_dir(&found_path)?;
+                self.fs.resolve_dir_cluster(&found_path)?
+            }
+        };
+
+        let taken: Vec<String> = self
+            .fs
+            .list_dir_cluster(dir_cluster)?
+            .into_iter()
+            .map(|e| normalize_name(&e.name))
+            .collect();
+        let name = (0..10_000)
+            .map(|n| format!("FILE{n:04}.CHK"))
+            .find(|name| !taken.contains(name))
+            .ok_or(FatError::NoSpaceLeft)?;
+
+        // La chaîne peut finir sur un cluster d’une autre chaîne : on la ferme
+        self.write_fat_entry_all(chain[chain.len() - 1], FAT32_EOC)?;
+
+        let len = (chain.len() as u64 * self.fs.cluster_size() as u64).min(MAX_FILE_SIZE);
+        self.create_dir_entry(dir_cluster, &name, ATTR_ARCHIVE, chain[0], len as u32, None)?;
+        Ok(())
+    }
+
+    /// Remet le compteur de clusters libres de FSInfo en accord avec la FAT.
+    fn sync_free_count(&mut self) -> Result<(), FatError> {
+        let info = match self.fs.fs_info() {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+
+        let free = self.fs.free_cluster_count()?;
+        if info.free_count != Some(free) {
+            let delta = info.free_count.map_or(0, |n| free as i64 - n as i64);
+            self.update_fs_info(delta, None)?;
+        }
+        Ok(())
+    }
+}
+
+/// Surcouche copy-on-write : les blocs écrits restent en mémoire, les
+/// autres sont lus sur le support, qui n’est jamais modifié.
+struct Overlay<'a, D> {
+    dev: &'a D,
+    /// Taille du support en blocs, relevée une fois pour toutes.
+    num_blocks: u64,
+    /// Blocs modifiés, par LBA.
+    blocks: BTreeMap<u64, Vec<u8>>,
+}
+
+impl<'a, D: BlockDevice> Overlay<'a, D> {
+    fn new(dev: &'a D) -> Self {
+        Self {
+            num_blocks: dev.num_blocks(),
+            dev,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Compare chaque bloc modifié à l’original : une entrée par suite
+    /// d’octets différents (les suites contiguës entre blocs sont fusionnées).
+    fn changes(&self) -> Result<Vec<ByteChange>, FatError> {
+        let bs = self.dev.block_size();
+        let mut original = vec![0u8; bs];
+        let mut changes: Vec<ByteChange> = Vec::new();
+
+        for (&lba, block) in &self.blocks {
+            self.dev.read_blocks(lba, &mut original)?;
+            let base = lba * bs as u64;
+
+            for (i, (&old, &new)) in original.iter().zip(block).enumerate() {
+                if old == new {
+                    continue;
+                }
+                let offset = base + i as u64;
+                match changes.last_mut() {
+                    Some(c) if c.offset + c.old.len() as u64 == offset => {
+                        c.old.push(old);
+                        c.new.push(new);
+                    }
+                    _ => changes.push(ByteChange {
+                        offset,
+                        old: vec![old],
+                        new: vec![new],
+                    }),
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for Overlay<'_, D> {
+    fn block_size(&self) -> usize {
+        self.dev.block_size()
+    }
+
+    fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> Result<(), FatError> {
+        self.dev.read_blocks(lba, buf)?;
+
+        let bs = self.block_size();
+        for (i, chunk) in buf.chunks_exact_mut(bs).enumerate() {
+            if let Some(block) = self.blocks.get(&(lba + i as u64)) {
+                chunk.copy_from_slice(block);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> BlockDeviceMut for Overlay<'_, D> {
+    fn write_blocks(&mut self, lba: u64, buf: &[u8]) -> Result<(), FatError> {
+        let end = lba
+            .checked_add((buf.len() / self.block_size()) as u64)
+            .ok_or(FatError::OutOfBounds)?;
+        if end > self.num_blocks() {
+            return Err(FatError::OutOfBounds);
+        }
+
+        let bs = self.block_size();
+        for (i, chunk) in buf.chunks_exact(bs).enumerate() {
+            self.blocks.insert(lba + i as u64, chunk.to_vec());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Dates et heures FAT.
+//!
+//! Une entrée de répertoire stocke trois horodatages :
+//! - création : date + heure (2 s) + centièmes (offsets 13..18)
+//! - dernier accès : date seule (offset 18)
+//! - modification : date + heure (offsets 22..26)
+//!
+//! Format des champs bruts (little endian) :
+//! - date : `((année - 1980) << 9) | (mois << 5) | jour`
+//! - heure : `(heure << 11) | (minute << 5) | (seconde / 2)`
+//!
+//! Le cœur étant `no_std`, il n’y a pas d’horloge système : c’est l’appelant
+//! qui fournit l’heure via le trait `Clock` (voir `Fat32Mut::set_clock`).
+
+use core::fmt;
+
+/// Date FAT (année 1980 à 2107).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FatDate {
+    /// Année complète (ex: 2024).
+    pub year: u16,
+    /// Mois, de 1 à 12.
+    pub month: u8,
+    /// Jour du mois, de 1 à 31.
+    pub day: u8,
+}
+
+/// Date + heure FAT.
+///
+/// La précision native est de 2 secondes ; l’horodatage de création ajoute
+/// un champ en centièmes, ce qui donne une précision de 10 ms (`millis`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FatTimestamp {
+    /// Date.
+    pub date: FatDate,
+    /// Heure, de 0 à 23.
+    pub hour: u8,
+    /// Minute, de 0 à 59.
+    pub minute: u8,
+    /// Seconde, de 0 à 59.
+    pub second: u8,
+    /// Millisecondes (seulement conservées pour l’horodatage de création).
+    pub millis: u16,
+}
+
+impl FatDate {
+    /// Plus petite date représentable : 1980-01-01.
+    pub const EPOCH: FatDate = FatDate {
+        year: 1980,
+        month: 1,
+        day: 1,
+    };
+
+    /// Décode un champ date brut. Retourne `None` pour 0 (champ non renseigné)
+    /// ou pour un mois ou un jour hors plage (image abîmée).
+    pub fn from_raw(raw: u16) -> Option<Self> {
+        let month = ((raw >> 5) & 0x0F) as u8;
+        let day = (raw & 0x1F) as u8;
+        if !(1..=12).contains(&month) || day == 0 {
+            return None;
+        }
+        Some(Self {
+            year: 1980 + (raw >> 9),
+            month,
+            day,
+        })
+    }
+
+    /// Encode la date au format FAT (année bornée à 1980..=2107).
+    pub fn to_raw(&self) -> u16 {
+        let year = self.year.clamp(1980, 2107) - 1980;
+        (year << 9) | ((self.month as u16 & 0x0F) << 5) | (self.day as u16 & 0x1F)
+    }
+}
+
+impl FatTimestamp {
+    /// Plus petit horodatage représentable : 1980-01-01 00:00:00.
+    pub const EPOCH: FatTimestamp = FatTimestamp {
+        date: FatDate::EPOCH,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        millis: 0,
+    };
+
+    /// Décode date + heure (+ centièmes pour la création).
+    ///
+    /// Retourne `None` si la date vaut 0 (champ non renseigné) ou si un
+    /// champ est hors plage : date invalide, heure au-delà de 23, minute ou
+    /// seconde au-delà de 59, centièmes au-delà de 199.
+    pub fn from_raw(date: u16, time: u16, hundredths: u8) -> Option<Self> {
+        let date = FatDate::from_raw(date)?;
+        let hour = (time >> 11) as u8;
+        let minute = ((time >> 5) & 0x3F) as u8;
+        let second = ((time & 0x1F) * 2) as u8;
+        if hour > 23 || minute > 59 || second > 58 || hundredths > 199 {
+            return None;
+        }
+        let extra_ms = hundredths as u16 * 10;
+        Some(Self {
+            date,
+            hour,
+            minute,
+            second: second + (extra_ms / 1000) as u8,
+            millis: extra_ms % 1000,
+        })
+    }
+
+    /// Encode au format FAT : `(date, heure, centièmes)`.
+    pub fn to_raw(&self) -> (u16, u16, u8) {
+        let time = ((self.hour as u16 & 0x1F) << 11)
+            | ((self.minute as u16 & 0x3F) << 5)
+            | ((self.second as u16 / 2) & 0x1F);
+        let hundredths = ((self.second % 2) as u16 * 100 + self.millis / 10) as u8;
+        (self.date.to_raw(), time, hundredths)
+    }
+
+    /// Convertit un temps Unix (secondes depuis 1970-01-01 UTC) en horodatage FAT.
+    ///
+    /// FAT ne connaît pas les fuseaux : le résultat est en UTC. Les valeurs hors
+    /// de la plage FAT sont bornées à 1980-01-01 / 2107-12-31 23:59:58.
+    pub fn from_unix_seconds(secs: i64) -> Self {
+        // 1980-01-01 et 2107-12-31 23:59:59 en secondes Unix
+        const MIN: i64 = 315_532_800;
+        const MAX: i64 = 4_354_819_199;
+        let secs = secs.clamp(MIN, MAX);
+
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            date: FatDate {
+                year: year as u16,
+                month,
+                day,
+            },
+            hour: (rem / 3600) as u8,
+            minute: (rem % 3600 / 60) as u8,
+            second: (rem % 60) as u8,
+            millis: 0,
+        }
+    }
+}
+
+impl fmt::Display for FatDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for FatTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:02}:{:02}:{:02}",
+            self.date, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Convertit un nombre de jours depuis 1970-01-01 en (année, mois, jour).
+///
+/// Algorithme “days from civil” inversé (calendrier grégorien proleptique).
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Source d’heure utilisée par `Fat32Mut` pour horodater les écritures.
+///
+/// Implémentée pour :
+/// - `FatTimestamp` : horloge figée (builds reproductibles, tests)
+/// - toute closure `Fn() -> FatTimestamp` (RTC d’une carte, horloge système…)
+pub trait Clock {
+    /// Retourne l’heure courante.
+    fn now(&self) -> FatTimestamp;
+}
+
+impl Clock for FatTimestamp {
+    fn now(&self) -> FatTimestamp {
+        *self
+    }
+}
+
+impl<F: Fn() -> FatTimestamp> Clock for F {
+    fn now(&self) -> FatTimestamp {
+        self()
+    }
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
@@ -0,0 +1,253 @@
+//! Récupération des entrées supprimées (`0xE5`).
+//!
+//! Supprimer un fichier FAT ne fait que deux choses : le premier octet de
+//! l’entrée (et de ses slots LFN) devient `0xE5`, et la chaîne de clusters est
+//! remise à zéro dans la FAT. La taille, le premier cluster et les données
+//! restent en place tant que rien ne les réutilise.
+//!
+//! - `Fat32::list_deleted` liste ces entrées, avec le nom long quand ses slots
+//!   sont intacts, et indique si leurs clusters sont encore libres
+//! - `Fat32Mut::undelete` restaure l’entrée avec un premier caractère choisi
+//!   et refait une chaîne contiguë à partir du premier cluster
+//!
+//! La chaîne d’origine est perdue : on suppose le fichier contigu, comme les
+//! outils `undelete` classiques. Un fichier fragmenté revient avec une fin
+//! qui ne lui appartient pas.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use crate::block::{BlockDevice, BlockDeviceMut};
+use crate::dir_entry::short_name_to_string;
+use crate::lfn::{
+    decode_deleted_lfn, is_lfn_slot, is_short_name_char, lfn_order, short_name_checksum,
+};
+use crate::{normalize_name, DirEntry, Fat32, Fat32Mut, FatError, FAT32_EOC};
+
+/// Entrée supprimée trouvée par `Fat32::list_deleted`.
+#[derive(Debug, Clone)]
+pub struct DeletedEntry {
+    /// Entrée telle qu’elle était : `short_name` commence par `?` (le premier
+    /// caractère est perdu) ; `name` est le nom long s’il a pu être relu.
+    pub entry: DirEntry,
+    /// Premier caractère probable du nom court, déduit du checksum des slots
+    /// LFN ; `None` sans nom long.
+    pub first_char_hint: Option<char>,
+    /// Les clusters du fichier (supposé contigu) sont tous encore libres :
+    /// la récupération est possible, même si rien ne garantit que les
+    /// données n’ont pas été écrasées entre-temps.
+    pub clusters_free: bool,
+    /// Premier cluster du répertoire qui contient l’entrée.
+    dir_cluster: u32,
+    /// Offset de l’entrée courte sur le support.
+    offset: u64,
+    /// Nom court brut, premier octet `0xE5` compris.
+    short_raw: [u8; 11],
+    /// Offsets des slots LFN supprimés (ordre du disque) et leur checksum.
+    lfn: Option<(Vec<u64>, u8)>,
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Liste les entrées supprimées d’un répertoire (chemin absolu).
+    ///
+    /// Le parcours s’arrête à la fin de répertoire (`0x00`), comme pour les
+    /// entrées actives.
+    pub fn list_deleted(&self, dir_path: &str) -> Result<Vec<DeletedEntry>, FatError> {
+        let dir_cluster = self.resolve_dir_cluster(dir_path)?;
+        let mut found = Vec::new();
+        let mut lfn_slots: Vec<(u64, [u8; 32])> = Vec::new();
+
+        for (off, slot) in self.dir_slots(dir_cluster)? {
+            if slot[0] == 0x00 {
+                break;
+            }
+            if slot[0] != 0xE5 {
+                lfn_slots.clear();
+                continue;
+            }
+            if is_lfn_slot(&slot) {
+                lfn_slots.push((off, slot));
+                continue;
+            }
+
+            let pending = core::mem::take(&mut lfn_slots);
+
+            let mut visible = slot;
+            visible[0] = b'?';
+            let mut entry = match DirEntry::parse(&visible) {
+                Some(e) => e,
+                None => continue,
+            };
+
+            let mut short_raw = [0u8; 11];
+            short_raw.copy_from_slice(&slot[0..11]);
+
+            let slots: Vec<[u8; 32]> = pending.iter().map(|&(_, s)| s).collect();
+            let long = decode_deleted_lfn(&slots);
+            let first_char_hint = long
+                .as_ref()
+                .and_then(|(name, sum)| guess_first_char(&short_raw, *sum, name));
+            let lfn = long.map(|(name, sum)| {
+                entry.name = name;
+                (pending.iter().map(|&(o, _)| o).collect(), sum)
+            });
+
+            let clusters_free = self.clusters_free(&entry)?;
+            found.push(DeletedEntry {
+                entry,
+                first_char_hint,
+                clusters_free,
+                dir_cluster,
+                offset: off,
+                short_raw,
+                lfn,
+            });
+        }
+
+        Ok(found)
+    }
+
+    /// Clusters occupés par une entrée supprimée, en la supposant contiguë
+    /// (un cluster au moins pour un répertoire).
+    fn deleted_clusters(
+        &self,
+        entry: &DirEntry,
+    ) -> Result<Option<core::ops::Range<u32>>, FatError> {
+        let cs = self.cluster_size() as u64;
+        let mut count = (entry.size as u64).div_ceil(cs);
+        if entry.is_dir() {
+            count = count.max(1);
+        }
+        if count == 0 {
+            return Ok(Some(0..0));
+        }
+
+        let start = entry.first_cluster;
+        let max = self.max_cluster_number()? as u64;
+        if start < 2 || start as u64 + count - 1 > max {
+            return Ok(None);
+        }
+        Ok(Some(start..start + count as u32))
+    }
+
+    /// Indique si les clusters d’une entrée supprimée sont tous libres.
+    fn clusters_free(&self, entry: &DirEntry) -> Result<bool, FatError> {
+        match self.deleted_clusters(entry)? {
+            Some(range) => {
+                for cl in range {
+                    if self.read_fat_entry(cl)? != 0 {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Restaure une entrée supprimée, avec `first_char` comme premier
+    /// caractère du nom court (voir `DeletedEntry::first_char_hint`).
+    ///
+    /// - `InvalidName` si `first_char` n’est pas accepté dans un nom court
+    /// - `PathNotFound` si le slot a été réutilisé depuis `list_deleted`
+    /// - `AlreadyExists` si le nom est repris dans le répertoire
+    /// - `InvalidCluster` si un des clusters est de nouveau alloué
+    ///
+    /// Le nom long n’est restauré que si le checksum de ses slots correspond
+    /// au nom court obtenu ; sinon l’entrée revient sous son nom 8.3.
+    pub fn undelete(&mut self, deleted: &DeletedEntry, first_char: char) -> Result<(), FatError> {
+        let result = self.restore_entry(deleted, first_char);
+        self.finish(result)
+    }
+
+    /// Corps de `undelete`.
+    fn restore_entry(&mut self, deleted: &DeletedEntry, first_char: char) -> Result<(), FatError> {
+        let c = first_char.to_ascii_uppercase();
+        if !c.is_ascii() || !is_short_name_char(c as u8) {
+            return Err(FatError::InvalidName);
+        }
+
+        let mut slot = [0u8; 32];
+        self.fs.read_at(deleted.offset, &mut slot)?;
+        if slot[0..11] != deleted.short_raw {
+            return Err(FatError::PathNotFound);
+        }
+
+        let mut short_raw = deleted.short_raw;
+        short_raw[0] = c as u8;
+        let lfn = deleted
+            .lfn
+            .as_ref()
+            .filter(|(_, sum)| *sum == short_name_checksum(&short_raw));
+
+        let short_name = normalize_name(&short_name_to_string(&short_raw));
+        let long_name = lfn.map(|_| normalize_name(&deleted.entry.name));
+        let taken = self
+            .fs
+            .list_dir_located(deleted.dir_cluster)?
+            .into_iter()
+            .any(|l| {
+                let names = [
+                    normalize_name(&l.entry.name),
+                    normalize_name(&l.entry.short_name),
+                ];
+                l.short_raw == short_raw
+                    || names.contains(&short_name)
+                    || long_name.as_ref().is_some_and(|n| names.contains(n))
+            });
+        if taken {
+            return Err(FatError::AlreadyExists);
+        }
+
+        if !self.fs.clusters_free(&deleted.entry)? {
+            return Err(FatError::InvalidCluster);
+        }
+        let clusters: Vec<u32> = self
+            .fs
+            .deleted_clusters(&deleted.entry)?
+            .ok_or(FatError::InvalidCluster)?
+            .collect();
+
+        // Chaîne contiguë : chaque cluster pointe sur le suivant
+        for pair in clusters.windows(2) {
+            self.write_fat_entry_all(pair[0], pair[1])?;
+        }
+        if let Some(&last) = clusters.last() {
+            self.write_fat_entry_all(last, FAT32_EOC)?;
+            self.update_fs_info(-(clusters.len() as i64), None)?;
+        }
+
+        self.write_at(deleted.offset, &[c as u8])?;
+        if let Some((offsets, _)) = lfn {
+            for (i, &off) in offsets.iter().enumerate() {
+                self.write_at(off, &[lfn_order(i, offsets.len())])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Cherche le premier caractère du nom court qui redonne le checksum des
+/// slots LFN ; on essaie d’abord l’initiale du nom long.
+fn guess_first_char(short_raw: &[u8; 11], checksum: u8, long_name: &str) -> Option<char> {
+    let initial = long_name
+        .chars()
+        .next()
+        .filter(char::is_ascii)
+        .map(|c| c.to_ascii_uppercase() as u8);
+
+    let mut raw = *short_raw;
+    initial
+        .into_iter()
+        .chain(0x21..0x7F)
+        .filter(|&b| is_short_name_char(b))
+        .find(|&b| {
+            raw[0] = b;
+            short_name_checksum(&raw) == checksum
+        })
+        .map(|b| b as char)
+}
@@ -0,0 +1,249 @@
+//! Identité du volume : nom OEM, numéro de série et nom de volume.
+//!
+//! Le nom de volume est rangé à deux endroits :
+//! - dans le BPB étendu (offset 71 en FAT32, 43 en FAT12/16), avec le numéro
+//!   de série et la chaîne de type (`FAT32   `)
+//! - dans une entrée de la racine portant l’attribut Volume ID (`0x08`)
+//!
+//! Windows n’affiche que le second, `mkfs.fat` et les BIOS lisent le premier :
+//! `Fat32Mut::set_label` écrit donc les deux (et la copie de secours du
+//! secteur de boot). Le numéro de série n’existe que dans le BPB étendu :
+//! `Fat32Mut::set_serial` le change dans le secteur de boot et sa copie.
+
+extern crate alloc;
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::block::{BlockDevice, BlockDeviceMut};
+use crate::lfn::{is_lfn_slot, is_short_name_char};
+use crate::{BootSector, Fat32, Fat32Mut, FatError, FatType};
+
+/// Attribut d’une entrée de nom de volume.
+pub(crate) const ATTR_VOLUME_ID: u8 = 0x08;
+
+/// Étiquette d’un volume sans nom.
+pub(crate) const NO_NAME: &[u8; 11] = b"NO NAME    ";
+
+/// Signature du BPB étendu complet (série, nom et type présents).
+const EXT_BOOT_SIG: u8 = 0x29;
+
+/// Ancienne signature : seul le numéro de série est présent.
+const EXT_BOOT_SIG_SERIAL_ONLY: u8 = 0x28;
+
+/// Informations d’identité du volume (`Fat32::volume_info`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeInfo {
+    /// Nom OEM du secteur de boot (ex: `MSWIN4.1`, `mkfs.fat`).
+    pub oem_name: String,
+    /// Numéro de série (`None` sans BPB étendu).
+    pub serial: Option<u32>,
+    /// Nom de volume du BPB étendu (`None` s’il est absent ou vaut `NO NAME`).
+    pub bpb_label: Option<String>,
+    /// Nom de volume de l’entrée Volume ID de la racine (`None` sans entrée).
+    pub root_label: Option<String>,
+    /// Chaîne de type du BPB étendu (ex: `FAT32`), purement indicative :
+    /// le vrai type vient de `Fat32::fat_type`.
+    pub fs_type: Option<String>,
+}
+
+impl VolumeInfo {
+    /// Nom du volume, comme l’affiche Windows : celui de la racine, sinon
+    /// celui du BPB.
+    pub fn label(&self) -> Option<&str> {
+        self.root_label.as_deref().or(self.bpb_label.as_deref())
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Lit l’identité du volume dans le secteur de boot utilisé à
+    /// l’ouverture (voir `boot_source`) et dans la racine.
+    pub fn volume_info(&self) -> Result<VolumeInfo, FatError> {
+        let mut b = [0u8; 512];
+        self.read_at(self.boot_sector_offset(), &mut b)?;
+
+        let oem_name = String::from_utf8_lossy(&b[3..11]).trim_end().to_string();
+
+        let base = self.ext_bpb_offset();
+        let sig = b[base + 2];
+        let serial = match sig {
+            EXT_BOOT_SIG | EXT_BOOT_SIG_SERIAL_ONLY => Some(u32::from_le_bytes([
+                b[base + 3],
+                b[base + 4],
+                b[base + 5],
+                b[base + 6],
+            ])),
+            _ => None,
+        };
+        let (bpb_label, fs_type) = match sig {
+            EXT_BOOT_SIG => (
+                Some(&b[base + 7..base + 18])
+                    .filter(|raw| raw != NO_NAME)
+                    .map(decode_label),
+                Some(decode_label(&b[base + 18..base + 26])),
+            ),
+            _ => (None, None),
+        };
+
+        let root_label = self.find_label_slot()?.map(|(_, raw)| decode_label(&raw));
+
+        Ok(VolumeInfo {
+            oem_name,
+            serial,
+            bpb_label,
+            root_label,
+            fs_type,
+        })
+    }
+
+    /// Cherche l’entrée Volume ID de la racine ; retourne son offset et
+    /// son nom brut.
+    fn find_label_slot(&self) -> Result<Option<(u64, [u8; 11])>, FatError> {
+        for (off, slot) in self.dir_slots(self.root_cluster)? {
+            if slot[0] == 0x00 {
+                break;
+            }
+            if slot[0] == 0xE5 || is_lfn_slot(&slot) {
+                continue;
+            }
+            // Volume ID seul : un répertoire n’en est pas un
+            if slot[11] & 0x18 == ATTR_VOLUME_ID {
+                let mut raw = [0u8; 11];
+                raw.copy_from_slice(&slot[0..11]);
+                return Ok(Some((off, raw)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Offsets du secteur de boot et de sa copie de secours (s’il y en a une).
+    fn boot_sector_copies(&self) -> impl Iterator<Item = u64> {
+        let bps = self.bytes_per_sector() as u64;
+        let backup = match self.backup_boot_sector {
+            0 => None,
+            sector => Some(sector as u64 * bps),
+        };
+        core::iter::once(0).chain(backup)
+    }
+
+    /// Offset du secteur de boot d’où vient la géométrie du volume.
+    fn boot_sector_offset(&self) -> u64 {
+        match self.boot_source {
+            BootSector::Primary => 0,
+            BootSector::Backup => self.backup_boot_sector as u64 * self.bytes_per_sector() as u64,
+        }
+    }
+
+    /// Début du BPB étendu (numéro de lecteur) : après les champs FAT32
+    /// en FAT32, juste après le BPB commun en FAT12/16.
+    fn ext_bpb_offset(&self) -> usize {
+        match self.fat_type {
+            FatType::Fat32 => 64,
+            FatType::Fat12 | FatType::Fat16 => 36,
+        }
+    }
+}
+
+impl<D: BlockDeviceMut> Fat32Mut<D> {
+    /// Change le nom du volume, dans le BPB étendu (secteur de boot et copie
+    /// de secours) comme dans la racine ; un nom vide l’efface (`NO NAME`
+    /// dans le BPB, entrée de la racine supprimée).
+    ///
+    /// - `InvalidName` si le nom dépasse 11 caractères ou contient un
+    ///   caractère refusé dans un nom court
+    /// - `NoSpaceLeft` si la racine est pleine
+    ///
+    /// Un secteur de boot sans BPB étendu (signature `0x29`) n’a pas de champ
+    /// pour le nom : seule la racine est alors modifiée.
+    pub fn set_label(&mut self, label: &str) -> Result<(), FatError> {
+        let result = self.write_label(label);
+        self.finish(result)
+    }
+
+    /// Corps de `set_label`.
+    fn write_label(&mut self, label: &str) -> Result<(), FatError> {
+        let raw = encode_label(label)?;
+
+        let base = self.fs.ext_bpb_offset() as u64;
+        for boot in self.fs.boot_sector_copies().collect::<Vec<_>>() {
+            let mut sig = [0u8; 1];
+            self.fs.read_at(boot + base + 2, &mut sig)?;
+            if sig[0] == EXT_BOOT_SIG {
+                self.write_at(boot + base + 7, &raw)?;
+            }
+        }
+
+        let root = self.fs.root_cluster;
+        match (self.fs.find_label_slot()?, &raw == NO_NAME) {
+            (Some((off, _)), true) => self.write_at(off, &[0xE5])?,
+            (Some((off, _)), false) => self.write_at(off, &raw)?,
+            (None, true) => {}
+            (None, false) => {
+                let (offsets, end_marker) = self.find_free_dir_slots(root, 1)?;
+                self.write_dir_entry_at_offset(offsets[0], &raw, ATTR_VOLUME_ID, 0, 0)?;
+                if let Some(next) = end_marker {
+                    self.write_at(next, &[0x00])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Change le numéro de série du volume, dans le secteur de boot et sa
+    /// copie de secours.
+    ///
+    /// `MissingExtendedBpb` si le secteur de boot utilisé à l’ouverture n’a
+    /// pas de BPB étendu (signature `0x28` ou `0x29`) : rien n’est écrit.
+    pub fn set_serial(&mut self, serial: u32) -> Result<(), FatError> {
+        let result = self.write_serial(serial);
+        self.finish(result)
+    }
+
+    /// Corps de `set_serial`.
+    fn write_serial(&mut self, serial: u32) -> Result<(), FatError> {
+        let base = self.fs.ext_bpb_offset() as u64;
+        let has_serial = |fs: &Fat32<D>, boot: u64| -> Result<bool, FatError> {
+            let mut sig = [0u8; 1];
+            fs.read_at(boot + base + 2, &mut sig)?;
+            Ok(matches!(sig[0], EXT_BOOT_SIG | EXT_BOOT_SIG_SERIAL_ONLY))
+        };
+
+        if !has_serial(&self.fs, self.fs.boot_sector_offset())? {
+            return Err(FatError::MissingExtendedBpb);
+        }
+        for boot in self.fs.boot_sector_copies().collect::<Vec<_>>() {
+            if has_serial(&self.fs, boot)? {
+                self.write_at(boot + base + 3, &serial.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Nom de volume sur 11 octets, en majuscules et complété par des espaces.
+pub(crate) fn encode_label(label: &str) -> Result<[u8; 11], FatError> {
+    if label.trim().is_empty() {
+        return Ok(*NO_NAME);
+    }
+    if label.len() > 11 || label.starts_with(' ') {
+        return Err(FatError::InvalidName);
+    }
+
+    let mut raw = [b' '; 11];
+    for (dst, b) in raw.iter_mut().zip(label.bytes()) {
+        let b = b.to_ascii_uppercase();
+        if b != b' ' && !is_short_name_char(b) {
+            return Err(FatError::InvalidName);
+        }
+        *dst = b;
+    }
+    Ok(raw)
+}
+
+/// Nom brut du disque, sans les espaces de fin (les espaces internes d’un
+/// nom de volume sont gardés, contrairement à un nom 8.3).
+fn decode_label(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw).trim_end().to_string()
+}
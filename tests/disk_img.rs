@@ -50,3 +50,25 @@ fn read_real_disk_img_if_present() -> Result<(), FatError> {
 
     Ok(())
 }
+
+/// Même image, lue directement depuis le fichier (secteur par secteur)
+/// au lieu d'être chargée en mémoire.
+#[cfg(feature = "std")]
+#[test]
+fn read_real_disk_img_from_file_if_present() -> Result<(), FatError> {
+    let img_path = Path::new("tests/disk.img");
+
+    if !img_path.exists() {
+        eprintln!("(info) tests/disk.img absent -> test ignoré");
+        return Ok(());
+    }
+
+    let data = fs::read(img_path).map_err(|_| FatError::Other)?;
+    let file = fs::File::open(img_path).map_err(|_| FatError::Io)?;
+
+    let from_file = Fat32::open(&file)?.list_root()?;
+    let from_memory = Fat32::new(&data)?.list_root()?;
+    assert_eq!(from_file.len(), from_memory.len());
+
+    Ok(())
+}